@@ -0,0 +1,94 @@
+//! Benchmarks of the boards and of the solver, on boards from 3x3 to 6x6,
+//! run with `cargo bench`. The image work is measured in `image.rs`.
+
+use std::hint::black_box;
+use std::sync::atomic::AtomicBool;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sliding_puzzle::{solver, Board, GridSize};
+
+/// Sizes of the boards benchmarked.
+static SIZES: [usize; 4] = [3, 4, 5, 6];
+
+/// Seed of the scrambles, so each run measures the same boards.
+static SEED: u64 = 42;
+
+/// Boards solved to the end, one of each size small enough for it: the
+/// 4x4 one takes 42 moves, its solve tens of milliseconds.
+static SOLVE_BOARDS: [&str; 2] = [
+    "3x3:6,3,7/1,8,4/0,5,2",
+    "4x4:4,9,8,7/3,1,15,12/5,10,0,6/13,14,11,2",
+];
+
+/// Sizes of the boards too large to solve to the end in a benchmark.
+static LARGE_SIZES: [usize; 2] = [5, 6];
+
+/// Nodes the solver searches at most for a move on the large boards, as
+/// for a hint, so they take a bounded time, see `solver`.
+static SOLVER_NODES: u64 = 100_000;
+
+fn solved_board(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solved_board");
+    for size in SIZES {
+        let grid_size = GridSize::square(size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(grid_size),
+            &grid_size,
+            |b, &g| b.iter(|| Board::solved(black_box(g))),
+        );
+    }
+    group.finish();
+}
+
+fn scramble(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scramble");
+    for size in SIZES {
+        let grid_size = GridSize::square(size);
+        let moves = 100 * grid_size.cells() / 16;
+        group.bench_function(BenchmarkId::new("moves", grid_size), |b| {
+            let mut rng = StdRng::seed_from_u64(SEED);
+            b.iter(|| Board::solved(grid_size).scramble(moves, &mut rng))
+        });
+        group.bench_function(BenchmarkId::new("random", grid_size), |b| {
+            let mut rng = StdRng::seed_from_u64(SEED);
+            b.iter(|| Board::solved(grid_size).scramble_random(&mut rng))
+        });
+    }
+    group.finish();
+}
+
+fn solve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+    let abort = AtomicBool::new(false);
+    for text in SOLVE_BOARDS {
+        let board: Board = text.parse().unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.size()),
+            &board,
+            |b, board| b.iter(|| solver::solve(board, &abort, |_| {}).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn best_move(c: &mut Criterion) {
+    let mut group = c.benchmark_group("best_move");
+    group.sample_size(10);
+    for size in LARGE_SIZES {
+        let grid_size = GridSize::square(size);
+        let mut board = Board::solved(grid_size);
+        board.scramble_random(&mut StdRng::seed_from_u64(SEED));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(grid_size),
+            &board,
+            |b, board| b.iter(|| solver::best_move(board, SOLVER_NODES)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, solved_board, scramble, solve, best_move);
+criterion_main!(benches);
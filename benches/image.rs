@@ -0,0 +1,130 @@
+//! Benchmarks of the image work done on the CPU, run with `cargo bench`:
+//! the image resized to the board and cut into pieces, as by
+//! `Puzzle::resize`, and the pieces laid out as on the board, as by
+//! `Puzzle::picture` for the pictures and sheets. The window draws the
+//! pieces as textures on the GPU instead.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nannou::image::{imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sliding_puzzle::{Board, GridSize};
+
+#[path = "../src/jigsaw.rs"]
+mod jigsaw;
+#[path = "../src/pieces.rs"]
+mod pieces;
+
+/// Sizes of the boards benchmarked.
+static SIZES: [usize; 3] = [3, 4, 6];
+
+/// Longest side of the board images, in pixels, as on a large window.
+static IMAGE_SIZE: u32 = 1200;
+
+/// Seed of the scrambles, so each run measures the same boards.
+static SEED: u64 = 42;
+
+/// Photo with some detail in every pixel, as the boards are made of.
+fn photo() -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_fn(3000, 2000, |x, y| {
+        Rgba([x as u8, y as u8, (x ^ y) as u8, 255])
+    }))
+}
+
+/// Width and height of the images of a board of `grid_size`, see
+/// `puzzle::image_dimensions`.
+fn dimensions(grid_size: GridSize) -> (u32, u32) {
+    let cell_size = IMAGE_SIZE / grid_size.longest() as u32;
+    (
+        cell_size * grid_size.width as u32,
+        cell_size * grid_size.height as u32,
+    )
+}
+
+/// Solved image of a board of `grid_size` made of `photo`, with the
+/// default filter.
+fn solved_image(photo: &DynamicImage, grid_size: GridSize) -> DynamicImage {
+    let (width, height) = dimensions(grid_size);
+    pieces::fill(photo, None, width, height, FilterType::CatmullRom)
+}
+
+/// Scrambled board of `grid_size`, its pieces all the right way up.
+fn scrambled(grid_size: GridSize) -> (Board, Vec<usize>) {
+    let mut board = Board::solved(grid_size);
+    board.scramble_random(&mut StdRng::seed_from_u64(SEED));
+    (board, vec![0; grid_size.cells() + 1])
+}
+
+fn resize(c: &mut Criterion) {
+    let photo = photo();
+    let mut group = c.benchmark_group("resize");
+    group.sample_size(10);
+    for filter in [FilterType::Nearest, FilterType::CatmullRom] {
+        let grid_size = GridSize::square(4);
+        let (width, height) = dimensions(grid_size);
+        group.bench_function(BenchmarkId::new(format!("{filter:?}"), grid_size), |b| {
+            b.iter(|| {
+                let solved = pieces::fill(&photo, None, width, height, filter);
+                pieces::cut(&solved, grid_size, false)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn cut(c: &mut Criterion) {
+    let photo = photo();
+    let mut group = c.benchmark_group("cut");
+    for size in SIZES {
+        let grid_size = GridSize::square(size);
+        let solved = solved_image(&photo, grid_size);
+        for jigsaw in [false, true] {
+            let name = if jigsaw { "jigsaw" } else { "square" };
+            group.bench_function(BenchmarkId::new(name, grid_size), |b| {
+                b.iter(|| pieces::cut(&solved, grid_size, jigsaw))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn compose(c: &mut Criterion) {
+    let photo = photo();
+    let mut group = c.benchmark_group("compose");
+    for size in SIZES {
+        let grid_size = GridSize::square(size);
+        let solved = solved_image(&photo, grid_size);
+        let (board, turns) = scrambled(grid_size);
+        for jigsaw in [false, true] {
+            let name = if jigsaw { "jigsaw" } else { "square" };
+            let pieces = pieces::cut(&solved, grid_size, jigsaw);
+            group.bench_function(BenchmarkId::new(name, grid_size), |b| {
+                b.iter(|| pieces::compose(&solved, &board, &turns, &pieces, jigsaw))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn picture(c: &mut Criterion) {
+    let photo = photo();
+    let mut group = c.benchmark_group("picture");
+    for size in SIZES {
+        let grid_size = GridSize::square(size);
+        let solved = solved_image(&photo, grid_size);
+        let pieces = pieces::cut(&solved, grid_size, false);
+        let (board, turns) = scrambled(grid_size);
+        group.bench_function(BenchmarkId::from_parameter(grid_size), |b| {
+            b.iter(|| {
+                let mut picture =
+                    pieces::compose(&solved, &board, &turns, &pieces, false).to_rgba8();
+                pieces::draw_numbers(&mut picture, &board);
+                picture
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, resize, cut, compose, picture);
+criterion_main!(benches);
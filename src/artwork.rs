@@ -0,0 +1,97 @@
+//! Generated tile art, used instead of a photo with `J`. Every tile gets
+//! its own hue and pattern, textured with a bit of noise, so the board can
+//! be solved by eye without numbers nor a picture.
+//!
+//! The color mode, with `Shift+C`, is plainer: every tile is one solid color
+//! from a smooth gradient across the board, so the solved board is a clean
+//! gradient.
+
+use nannou::color::{hsv, Rgb};
+use nannou::image::{DynamicImage, Rgba, RgbaImage};
+use nannou::noise::{NoiseFn, OpenSimplex};
+use sliding_puzzle::GridSize;
+
+/// Hue step from one tile to the next, the golden ratio spreads them evenly.
+static HUE_STEP: f32 = 0.618_034;
+
+/// Size of the noise features, in tiles.
+static NOISE_SCALE: f64 = 0.25;
+
+/// Colors of the gradient of the color mode at the top left, top right,
+/// bottom left and bottom right corners of the board.
+static GRADIENT_CORNERS: [[f32; 3]; 4] = [
+    [0.95, 0.35, 0.35],
+    [0.95, 0.85, 0.30],
+    [0.30, 0.45, 0.95],
+    [0.30, 0.85, 0.55],
+];
+
+/// Image for a board of `grid_size` with tiles of `cell_size` pixels,
+/// laid out as in the solved board.
+pub fn generate(grid_size: GridSize, cell_size: u32) -> DynamicImage {
+    let cell_size = cell_size.max(1);
+    let width = cell_size * grid_size.width as u32;
+    let height = cell_size * grid_size.height as u32;
+    let noise = OpenSimplex::new();
+    let image = RgbaImage::from_fn(width, height, |x, y| {
+        let (col, row) = (x / cell_size, y / cell_size);
+        let tile = row as usize * grid_size.width + col as usize;
+        // position in the tile, from 0 to 1
+        let u = (x % cell_size) as f32 / cell_size as f32;
+        let v = (y % cell_size) as f32 / cell_size as f32;
+        let point = [
+            x as f64 / cell_size as f64 / NOISE_SCALE,
+            y as f64 / cell_size as f64 / NOISE_SCALE,
+        ];
+        let texture = noise.get(point) as f32 * 0.15;
+        let value = (0.55 + 0.3 * pattern(tile, u, v) + texture).clamp(0.0, 1.0);
+        let hue = (tile as f32 * HUE_STEP).fract();
+        let color: Rgb = hsv(hue, 0.75, value).into();
+        Rgba([
+            (color.red * 255.0) as u8,
+            (color.green * 255.0) as u8,
+            (color.blue * 255.0) as u8,
+            255,
+        ])
+    });
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Image for a board of `grid_size` with tiles of `cell_size` pixels, each
+/// tile the color of the gradient at its centre.
+pub fn gradient(grid_size: GridSize, cell_size: u32) -> DynamicImage {
+    let cell_size = cell_size.max(1);
+    let width = cell_size * grid_size.width as u32;
+    let height = cell_size * grid_size.height as u32;
+    let [top_left, top_right, bottom_left, bottom_right] = GRADIENT_CORNERS;
+    let image = RgbaImage::from_fn(width, height, |x, y| {
+        // centre of the tile, from 0 to 1 across the board
+        let u = ((x / cell_size) as f32 + 0.5) / grid_size.width as f32;
+        let v = ((y / cell_size) as f32 + 0.5) / grid_size.height as f32;
+        let mut pixel = [255; 4];
+        for (c, channel) in pixel.iter_mut().take(3).enumerate() {
+            let top = top_left[c] + (top_right[c] - top_left[c]) * u;
+            let bottom = bottom_left[c] + (bottom_right[c] - bottom_left[c]) * u;
+            *channel = ((top + (bottom - top) * v) * 255.0) as u8;
+        }
+        Rgba(pixel)
+    });
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Pattern of tile `tile` at `(u, v)` in the tile, from 0 to 1.
+/// Patterns take turns so neighbours with close hues still differ.
+fn pattern(tile: usize, u: f32, v: f32) -> f32 {
+    let (du, dv) = (u - 0.5, v - 0.5);
+    let stripes = 3.0 + (tile / 5 % 3) as f32;
+    match tile % 5 {
+        0 => ((u + v) * stripes * 2.0).fract().round(),
+        1 => ((u * stripes).floor() + (v * stripes).floor()) % 2.0,
+        2 => ((du * du + dv * dv).sqrt() * stripes * 2.0).fract().round(),
+        3 => (v * stripes * 2.0).fract().round(),
+        _ => {
+            let (cu, cv) = ((u * stripes).fract() - 0.5, (v * stripes).fract() - 0.5);
+            (cu * cu + cv * cv < 0.1) as u8 as f32
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Attract mode, a demo started after a while without input. It scrambles
+//! and solves a board of its own, going through the images, and leaves the
+//! boards of the player untouched, their random generator too.
+
+use std::time::{Duration, Instant};
+
+use nannou::rand::rand::{rngs::StdRng, SeedableRng};
+use sliding_puzzle::GridSize;
+
+use crate::packs;
+use crate::puzzle::{self, Puzzle, Stage};
+
+/// Time between two moves of the solve.
+static MOVE_INTERVAL: Duration = Duration::from_millis(120);
+
+/// How long the solved image stays before the next scramble.
+static SOLVED_PAUSE: Duration = Duration::from_secs(2);
+
+pub struct Attract {
+    pub puzzle: Puzzle,   // The demo board
+    solution: Vec<usize>, // Moves left to solve it, the next one last
+    last_step: Instant,   // Time of the last move or of the solve
+    rng: StdRng,          // Scrambles of the demo, apart from the seeded ones of the game
+}
+
+impl Attract {
+    /// Start the demo with the images of `collection`, on a board of
+    /// `grid_size` with images `img_size` pixels on the longest side.
+    pub fn new(
+        grid_size: GridSize,
+        collection: &packs::Collection,
+        collection_current: usize,
+        img_size: u32,
+    ) -> Self {
+        Attract {
+            puzzle: Puzzle::new(grid_size, collection, collection_current, img_size),
+            solution: vec![],
+            last_step: Instant::now(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Move the demo on: scramble, solve one move at a time, then show
+    /// the solved image for a while before going to the next image.
+    pub fn step(&mut self) {
+        if self.puzzle.stage == Stage::Scrambling {
+            self.puzzle.scramble_for(puzzle::SCRAMBLE_INTERVAL);
+            return;
+        }
+        if self.puzzle.is_solved() {
+            if self.last_step.elapsed() > SOLVED_PAUSE {
+                self.puzzle.next_image();
+                // animated, the demo solves it by playing the moves back
+                self.puzzle.start_scramble(true, &mut self.rng);
+            }
+            return;
+        }
+        if self.last_step.elapsed() < MOVE_INTERVAL {
+            return;
+        }
+        if self.solution.is_empty() {
+            self.solution = self.puzzle.solution();
+            self.solution.reverse();
+        }
+        if let Some(piece) = self.solution.pop() {
+            let (ix, iy) = self.puzzle.index_of(piece);
+            self.puzzle.try_move(ix, iy);
+        }
+        self.last_step = Instant::now();
+    }
+}
@@ -0,0 +1,91 @@
+//! Auto-solve, started with `A`: the shortest solution of the board is
+//! searched in the background, see `sliding_puzzle::solver`, then played
+//! one move per frame. Pressing `A` again stops it.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use sliding_puzzle::{solver, Board};
+
+/// What the auto-solve does next.
+pub enum Step {
+    Searching(usize), // Still searching, for a solution of at least that many moves
+    Move(usize),      // Piece to slide into the empty space
+    Done(usize),      // Solution played, that many moves
+    Failed,           // No solution, or the board changed meanwhile
+}
+
+pub struct AutoSolve {
+    pub board: usize,                       // Index of the puzzle being solved
+    start: Board,                           // Board the solution is searched for
+    abort: Arc<AtomicBool>,                 // Set to stop the search
+    bound: Arc<AtomicUsize>,                // Moves the solution has at least, so far
+    receiver: Receiver<Option<Vec<usize>>>, // Solution, once found
+    moves: Option<VecDeque<usize>>,         // Pieces left to move, once found
+    length: usize,                          // Moves of the solution
+}
+
+impl AutoSolve {
+    /// Start searching a solution of `start`, the board of puzzle `board`.
+    pub fn new(board: usize, start: &Board) -> Self {
+        let abort = Arc::new(AtomicBool::new(false));
+        let bound = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel();
+        let (search_board, search_abort, search_bound) =
+            (start.clone(), abort.clone(), bound.clone());
+        thread::spawn(move || {
+            let solution = solver::solve(&search_board, &search_abort, |moves| {
+                search_bound.store(moves, Ordering::Relaxed);
+            });
+            let _ = sender.send(solution);
+        });
+        AutoSolve {
+            board,
+            start: start.clone(),
+            abort,
+            bound,
+            receiver,
+            moves: None,
+            length: 0,
+        }
+    }
+
+    /// Next step of the auto-solve of `current`, the board as it is now.
+    pub fn step(&mut self, current: &Board) -> Step {
+        if self.moves.is_none() {
+            match self.receiver.try_recv() {
+                Ok(Some(solution)) if *current == self.start => {
+                    self.length = solution.len();
+                    self.moves = Some(solution.into());
+                }
+                Ok(_) => return Step::Failed,
+                Err(mpsc::TryRecvError::Empty) => {
+                    return Step::Searching(self.bound.load(Ordering::Relaxed))
+                }
+                Err(mpsc::TryRecvError::Disconnected) => return Step::Failed,
+            }
+        }
+        let moves = self.moves.as_mut().unwrap();
+        match moves.pop_front() {
+            // the player may have moved a piece meanwhile
+            Some(piece) => {
+                let (ix, iy) = current.index_of(piece);
+                match current.is_move_valid(ix, iy) {
+                    true => Step::Move(piece),
+                    false => Step::Failed,
+                }
+            }
+            None => Step::Done(self.length),
+        }
+    }
+}
+
+impl Drop for AutoSolve {
+    /// Stop the search, if it is still going.
+    fn drop(&mut self) {
+        self.abort.store(true, Ordering::Relaxed);
+    }
+}
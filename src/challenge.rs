@@ -0,0 +1,91 @@
+//! Challenge codes, ie, a grid size and a scramble seed written as a short
+//! text such as `4x4-9F3A27C1` or `5x3-9F3A27C1`. Two players starting the same challenge get
+//! exactly the same scrambled board.
+
+use std::fmt;
+
+use qrcode::{Color, QrCode};
+use sliding_puzzle::GridSize;
+
+use crate::MAX_GRID_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Challenge {
+    pub size: GridSize,
+    pub seed: u64,
+}
+
+impl Challenge {
+    /// Parse a challenge code, ignoring case and surrounding spaces. The
+    /// sides must be from 2 to `MAX_GRID_SIZE`, as for `--size`.
+    pub fn decode(code: &str) -> Option<Self> {
+        let (size, seed) = code.trim().split_once('-')?;
+        if !size.to_lowercase().contains('x') {
+            return None;
+        }
+        let size: GridSize = size.parse().ok()?;
+        let range = 2..=MAX_GRID_SIZE;
+        if !range.contains(&size.width) || !range.contains(&size.height) {
+            return None;
+        }
+        let seed = u64::from_str_radix(seed, 16).ok()?;
+        Some(Challenge { size, seed })
+    }
+
+    /// Modules of the QR code of this challenge, as the width of the
+    /// square code and a row-major list of dark modules.
+    pub fn qr_modules(&self) -> Option<(usize, Vec<bool>)> {
+        let code = QrCode::new(self.to_string()).ok()?;
+        let dark = code.to_colors().iter().map(|c| *c == Color::Dark).collect();
+        Some((code.width(), dark))
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{:X}", self.size, self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_round_trip() {
+        for challenge in [
+            Challenge {
+                size: GridSize::square(4),
+                seed: 0x9F3A27C1,
+            },
+            Challenge {
+                size: GridSize {
+                    width: 5,
+                    height: 3,
+                },
+                seed: 0,
+            },
+        ] {
+            assert_eq!(Challenge::decode(&challenge.to_string()), Some(challenge));
+        }
+        let challenge = Challenge::decode(" 4X4-9f3a27c1 ").unwrap();
+        assert_eq!(challenge.to_string(), "4x4-9F3A27C1");
+    }
+
+    #[test]
+    fn decode_rejects_bad_codes() {
+        for code in ["", "4x4", "4-9F3A27C1", "4x4-XYZ", "4x4-9F3A27C1-1"] {
+            assert_eq!(Challenge::decode(code), None, "'{code}' decoded");
+        }
+        // sides out of the range of --size
+        for code in [
+            "1x4-9F3A27C1",
+            "4x0-9F3A27C1",
+            "21x4-9F3A27C1",
+            "4x100-9F3A27C1",
+        ] {
+            assert_eq!(Challenge::decode(code), None, "'{code}' decoded");
+        }
+        assert!(Challenge::decode(&format!("{MAX_GRID_SIZE}x2-1")).is_some());
+    }
+}
@@ -0,0 +1,180 @@
+//! Command line of the game, see `sliding_puzzle --help`. Values which
+//! can't be used stop the game with a message, rather than being guessed.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use clap::Parser;
+use nannou::image;
+use sliding_puzzle::{Board, GridSize};
+
+use crate::challenge::Challenge;
+use crate::config::ResizeFilter;
+use crate::MAX_GRID_SIZE;
+
+/// The command line, read once.
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+/// The command line. Read the first time, exiting with the help or the
+/// mistakes made if that's what it asks for.
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(Args::parse)
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Sliding puzzle with your own pictures")]
+pub struct Args {
+    /// Grid size, as `--size`
+    #[arg(value_name = "SIZE", value_parser = parse_grid_size, conflicts_with = "size")]
+    pub size_positional: Option<GridSize>,
+
+    /// Grid size, `5x3` or `4` for a square grid [default: 4]
+    #[arg(long, value_parser = parse_grid_size)]
+    pub size: Option<GridSize>,
+
+    /// Folder of the images to play with, its subfolders included, as
+    /// `images_dir` in the config [default: `images`]
+    #[arg(long, value_name = "DIR")]
+    pub images_dir: Option<PathBuf>,
+
+    /// Image to start with, from the images folder or anywhere else
+    #[arg(long, value_name = "FILE", value_parser = parse_image)]
+    pub image: Option<PathBuf>,
+
+    /// Image to start with, downloaded from the web
+    #[cfg(feature = "url")]
+    #[arg(long, value_name = "URL", value_parser = parse_url, conflicts_with = "image")]
+    pub url: Option<String>,
+
+    /// Seed of the scrambles, players with the same seed get the same boards
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Random moves of an animated scramble, as `scramble_moves` in the
+    /// config [default: 100 on a 4x4 board, as many per cell on others]
+    #[arg(long, value_name = "MOVES", value_parser = parse_at_least_one)]
+    pub scramble_moves: Option<usize>,
+
+    /// Milliseconds between two moves of an animated scramble, as
+    /// `scramble_interval_ms` in the config [default: 15]
+    #[arg(long, value_name = "MS", value_parser = parse_at_least_one)]
+    pub scramble_interval_ms: Option<usize>,
+
+    /// Empty spaces of the board, more than one for an easier variant, as
+    /// `blanks` in the config [default: 1]
+    #[arg(long, value_name = "COUNT", value_parser = parse_at_least_one)]
+    pub blanks: Option<usize>,
+
+    /// Filter resizing the images to the boards, as `resize_filter` in the
+    /// config [default: catmull-rom]
+    #[arg(long, value_name = "FILTER")]
+    pub resize_filter: Option<ResizeFilter>,
+
+    /// Play the images of the images folder in random order, as
+    /// `shuffle_images` in the config
+    #[arg(long)]
+    pub shuffle_images: bool,
+
+    /// Start with the numbers on the pieces hidden
+    #[arg(long)]
+    pub no_numbers: bool,
+
+    /// Window without a frame
+    #[arg(long)]
+    pub borderless: bool,
+
+    /// Play a challenge shared by someone else, eg, `4x4-9F3A27C1`
+    #[arg(long, value_name = "CODE", value_parser = parse_challenge,
+          conflicts_with_all = ["size", "size_positional"])]
+    pub challenge: Option<Challenge>,
+
+    /// Import a puzzle pack into the profile before starting
+    #[arg(long, value_name = "ZIP")]
+    pub import_pack: Option<PathBuf>,
+
+    /// Start from a position, eg, `3x3:4,1,3/7,2,5/8,0,6`, rows from the top
+    /// and 0 for the empty space
+    #[arg(long, value_name = "POSITION", value_parser = parse_position,
+          conflicts_with_all = ["size", "size_positional", "challenge"])]
+    pub position: Option<Board>,
+
+    /// Play back a replay saved by a solve, eg, one shared by someone else
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+}
+
+impl Args {
+    /// Grid size to start with, from the challenge or the size given.
+    pub fn grid_size(&self) -> GridSize {
+        if let Some(board) = &self.position {
+            return board.size();
+        }
+        match self.challenge {
+            Some(challenge) => challenge.size,
+            None => self
+                .size
+                .or(self.size_positional)
+                .unwrap_or(GridSize::square(4)),
+        }
+    }
+}
+
+/// Grid size with sides from 2 to `MAX_GRID_SIZE`.
+fn parse_grid_size(text: &str) -> Result<GridSize, String> {
+    let size: GridSize = text.parse()?;
+    let range = 2..=MAX_GRID_SIZE;
+    if !range.contains(&size.width) || !range.contains(&size.height) {
+        return Err(format!("sides must be from 2 to {MAX_GRID_SIZE}"));
+    }
+    Ok(size)
+}
+
+/// Path of an image file the game can read.
+fn parse_image(text: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(text);
+    if !path.is_file() {
+        return Err(String::from("no such file"));
+    }
+    match image::ImageFormat::from_path(&path) {
+        Ok(format) if format.can_read() => Ok(path),
+        _ => Err(String::from("not an image format the game reads")),
+    }
+}
+
+/// URL of an image, checked to be one once downloaded.
+#[cfg(feature = "url")]
+fn parse_url(text: &str) -> Result<String, String> {
+    match crate::web::is_url(text) {
+        true => Ok(text.trim().to_string()),
+        false => Err(String::from("not an http:// or https:// URL")),
+    }
+}
+
+/// Number of scramble moves, milliseconds or empty spaces, at least one.
+fn parse_at_least_one(text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(0) => Err(String::from("must be at least 1")),
+        Ok(moves) => Ok(moves),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Position in the notation of `Board`, on a grid the game can show and
+/// solvable.
+pub fn parse_position(text: &str) -> Result<Board, String> {
+    let board: Board = text.parse()?;
+    let range = 2..=MAX_GRID_SIZE;
+    let size = board.size();
+    if !range.contains(&size.width) || !range.contains(&size.height) {
+        return Err(format!("sides must be from 2 to {MAX_GRID_SIZE}"));
+    }
+    if !board.is_solvable() {
+        return Err(String::from("the position can't be solved"));
+    }
+    Ok(board)
+}
+
+/// Challenge code, see `Challenge::decode`.
+fn parse_challenge(code: &str) -> Result<Challenge, String> {
+    Challenge::decode(code).ok_or_else(|| String::from("expected a code like 4x4-9F3A27C1"))
+}
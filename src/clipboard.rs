@@ -0,0 +1,83 @@
+//! Text on the system clipboard, through the clipboard tools of the
+//! platform: `pbcopy` on macOS, PowerShell on Windows, and `wl-copy`,
+//! `xclip` or `xsel` on Linux, the first one installed.
+
+use std::io::{self, Error, ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+/// Commands putting their input on the clipboard, tried in order.
+static COPY_COMMANDS: &[&[&str]] = if cfg!(target_os = "macos") {
+    &[&["pbcopy"]]
+} else if cfg!(target_os = "windows") {
+    &[&["clip"]]
+} else {
+    &[
+        &["wl-copy"],
+        &["xclip", "-selection", "clipboard"],
+        &["xsel", "--clipboard", "--input"],
+    ]
+};
+
+/// Commands writing the clipboard out, tried in order.
+static PASTE_COMMANDS: &[&[&str]] = if cfg!(target_os = "macos") {
+    &[&["pbpaste"]]
+} else if cfg!(target_os = "windows") {
+    &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]]
+} else {
+    &[
+        &["wl-paste", "--no-newline"],
+        &["xclip", "-selection", "clipboard", "-out"],
+        &["xsel", "--clipboard", "--output"],
+    ]
+};
+
+/// Put `text` on the clipboard.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut last_error = no_tool();
+    for command in COPY_COMMANDS {
+        let child = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        match child.wait()? {
+            status if status.success() => return Ok(()),
+            status => last_error = Error::other(format!("{} {status}", command[0])),
+        }
+    }
+    Err(last_error)
+}
+
+/// Text on the clipboard.
+pub fn paste() -> io::Result<String> {
+    let mut last_error = no_tool();
+    for command in PASTE_COMMANDS {
+        match Command::new(command[0])
+            .args(&command[1..])
+            .stderr(Stdio::null())
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            }
+            Ok(output) => last_error = Error::other(format!("{} {}", command[0], output.status)),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn no_tool() -> Error {
+    Error::new(ErrorKind::NotFound, "no clipboard tool found")
+}
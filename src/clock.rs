@@ -0,0 +1,73 @@
+//! Wall clock time and how it is written in file names, records and the HUD.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Timezone-independent timestamp `YYYYMMDD-HHMMSS-mmm` (UTC) used in file
+/// names. The milliseconds keep files saved within a second, eg, quick
+/// screenshots, from overwriting each other.
+pub fn timestamp_millis() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let (year, month, day, h, m, s) = civil(now.as_secs());
+    let millis = now.subsec_millis();
+    format!("{year:04}{month:02}{day:02}-{h:02}{m:02}{s:02}-{millis:03}")
+}
+
+/// Today's date `YYYY-MM-DD` (UTC).
+#[cfg(feature = "daily")]
+pub fn date() -> String {
+    let (year, month, day, ..) = civil(unix_now());
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Human readable date `YYYY-MM-DD HH:MM` (UTC) of a Unix time.
+pub fn format_date(secs: u64) -> String {
+    let (year, month, day, h, m, _) = civil(secs);
+    format!("{year:04}-{month:02}-{day:02} {h:02}:{m:02}")
+}
+
+/// Duration as `M:SS.S`, or `H:MM:SS` for the really long solves.
+pub fn format_duration(secs: f64) -> String {
+    let whole = secs as u64;
+    if whole >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            whole / 3600,
+            whole % 3600 / 60,
+            whole % 60
+        )
+    } else {
+        format!("{}:{:04.1}", whole / 60, secs % 60.0)
+    }
+}
+
+/// Whole seconds as `M:SS`, for clocks ticking once a second.
+pub fn format_clock(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Year, month, day, hours, minutes and seconds of a Unix time.
+fn civil(secs: u64) -> (i64, i64, i64, u64, u64, u64) {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since epoch, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
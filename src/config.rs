@@ -0,0 +1,178 @@
+//! User settings, read from `config.toml` in the profile.
+//!
+//! The file is created with the default values the first time the game
+//! runs, missing entries take their default value.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use clap::ValueEnum;
+use nannou::image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+
+use crate::profile;
+
+/// Name of the config file in the profile.
+static CONFIG_FILE: &str = "config.toml";
+
+/// Filters resizing the images to the boards, from the fastest to the
+/// smoothest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeFilter {
+    Nearest,  // Blocky, but fast
+    Triangle, // Smooth, a bit blurry when shrinking a lot
+    #[default]
+    CatmullRom, // Sharp and smooth
+    Lanczos3, // Sharpest, and slowest
+}
+
+impl ResizeFilter {
+    pub fn filter_type(self) -> FilterType {
+        match self {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::CatmullRom => FilterType::CatmullRom,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Folder of the images to play with, its subfolders included, eg,
+    /// the whole photo library. Empty for `images` in the working folder,
+    /// or in the profile if there is none.
+    pub images_dir: String,
+    /// Play the images of the images folder in random order, rather than
+    /// sorted by name.
+    pub shuffle_images: bool,
+    /// Window title while solving. `{time}`, `{moves}`, `{size}` and
+    /// `{image}` are replaced by the current values.
+    pub title_format: String,
+    /// Keep the window above other windows, toggle with `K`.
+    pub always_on_top: bool,
+    /// Open the control panel window at start, or later with `F2`.
+    pub control_panel: bool,
+    /// Seconds without input before the demo starts, 0 to never start it.
+    pub attract_after: u64,
+    /// Pause the timer while the window is not focused.
+    pub auto_pause: bool,
+    /// Dim the boards while paused, so they can't be studied for free.
+    pub dim_when_paused: bool,
+    /// Tone down the animations, fewer particles fly around.
+    pub reduced_motion: bool,
+    /// Throw confetti over a board when it is solved.
+    pub confetti: bool,
+    /// Cut the pieces with interlocking jigsaw edges rather than straight
+    /// ones, toggle with `Shift+J`.
+    pub jigsaw_edges: bool,
+    /// Play with solid color tiles of a gradient rather than the images,
+    /// toggle with `Shift+C`.
+    pub colors: bool,
+    /// Where to fetch the image of the day from, `{date}` is replaced by
+    /// today's date. Empty to not fetch it. Needs the `daily` feature.
+    pub daily_image_url: String,
+    /// Camera the snapshots are taken from with `C`, as `ffmpeg` names it,
+    /// eg, `/dev/video1`. Empty for the first one, except on Windows where
+    /// it must be set. Needs the `webcam` feature.
+    pub webcam_device: String,
+    /// Where `Shift+S` gets a surprise image from, `{width}` and `{height}`
+    /// are replaced by the size of the board in pixels. Needs the `url`
+    /// feature.
+    pub surprise_url: String,
+    /// Clicking a piece in line with the empty space slides all the pieces
+    /// up to it. Count that as one move per piece, rather than one move.
+    pub count_each_piece: bool,
+    /// Scramble with moves played one by one, rather than at once. Hints
+    /// and solutions are only known for animated scrambles.
+    pub animated_scramble: bool,
+    /// Moves per frame of the animated scramble and of auto-solve.
+    pub animation_speed: usize,
+    /// Random moves of an animated scramble, 0 for 100 on a 4x4 board and
+    /// as many per cell on other sizes. `--scramble-moves` overrides it.
+    pub scramble_moves: usize,
+    /// Milliseconds between two moves of an animated scramble, divided by
+    /// the animation speed. `--scramble-interval-ms` overrides it.
+    pub scramble_interval_ms: usize,
+    /// Least distance of a scramble from the solved board, from 0 to 1. The
+    /// scramble goes on until the pieces are this share of the distance of
+    /// a random board away from their place, 0 for any board.
+    pub scramble_difficulty: f32,
+    /// Empty spaces of the board, more than one for an easier variant where
+    /// the pieces have more room. `--blanks` overrides it.
+    pub blanks: usize,
+    /// Filter resizing the images to the boards: `nearest`, `triangle`,
+    /// `catmull_rom` or `lanczos3`, from the fastest to the smoothest.
+    /// The images are resized once the window stops being resized.
+    /// `--resize-filter` overrides it.
+    pub resize_filter: ResizeFilter,
+    /// Peeks at the solved image allowed in each challenge, 0 for no limit.
+    pub peek_limit: usize,
+    /// Volume of the sound effects, from 0 to 1. Needs the `sound` feature,
+    /// `Shift+M` turns them off and on.
+    pub volume: f32,
+    /// Volume of the music played from the `music` folder, from 0 to 1.
+    /// `Ctrl+N` skips to the next track, `Ctrl+M` turns it off and on.
+    pub music_volume: f32,
+    /// Keys of the actions, replacing their default keys, eg,
+    /// `scramble = ["Space"]`. See `keys.rs` for the names.
+    pub keys: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            images_dir: String::new(),
+            shuffle_images: false,
+            title_format: String::from("Sliding Puzzle - {time} - {moves} moves"),
+            always_on_top: false,
+            control_panel: false,
+            attract_after: 300,
+            auto_pause: true,
+            dim_when_paused: true,
+            reduced_motion: false,
+            confetti: true,
+            jigsaw_edges: false,
+            colors: false,
+            daily_image_url: String::from("https://picsum.photos/seed/{date}/1200/1200"),
+            webcam_device: String::new(),
+            surprise_url: String::from("https://picsum.photos/{width}/{height}"),
+            count_each_piece: false,
+            animated_scramble: false,
+            animation_speed: 1,
+            scramble_moves: 0,
+            scramble_interval_ms: 15,
+            scramble_difficulty: 0.5,
+            blanks: 1,
+            resize_filter: ResizeFilter::default(),
+            peek_limit: 0,
+            volume: 0.5,
+            music_volume: 0.3,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config from the profile, writing the defaults if there is none.
+    pub fn load() -> Self {
+        let path = profile::profile_path(CONFIG_FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                println!("Error reading {}, using defaults: {e}", path.display());
+                Config::default()
+            }),
+            Err(_) => {
+                let config = Config::default();
+                if let Ok(text) = toml::to_string_pretty(&config) {
+                    if let Err(e) = fs::write(&path, text) {
+                        println!("Error writing {}: {e}", path.display());
+                    }
+                }
+                config
+            }
+        }
+    }
+}
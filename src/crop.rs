@@ -0,0 +1,184 @@
+//! Crop screen, opened with `Ctrl+I` before a solve: the whole image with
+//! the region played on the board framed over it, as the middle of the
+//! image is often not the part worth playing, eg, it cuts faces off.
+//!
+//! Dragging moves the frame, the mouse wheel and `+`/`-` resize it and the
+//! arrows nudge it. `Enter` plays the region, `R` goes back to the middle
+//! and `Esc` leaves the image as it was.
+
+use nannou::image::imageops::FilterType;
+use nannou::image::{DynamicImage, GenericImageView};
+use nannou::prelude::*;
+use sliding_puzzle::GridSize;
+
+use crate::PAD_HEIGHT_FACTOR;
+
+/// Longest side of the image shown, in pixels, big photos are shrunk.
+static PREVIEW_SIZE: u32 = 2048;
+
+/// Smallest frame, as a share of the biggest one.
+static MIN_FRAME: f32 = 0.1;
+
+/// Share of the frame it grows or shrinks by with each step.
+static RESIZE_STEP: f32 = 0.1;
+
+/// Share of the image the arrows nudge the frame by.
+static NUDGE_STEP: f32 = 0.02;
+
+pub struct Cropper {
+    pub board: usize,                      // Index of the board the image is cropped for
+    texture: wgpu::Texture,                // Image shown, maybe shrunk
+    image_size: Vec2,                      // Size of the original image, in pixels
+    aspect: f32,                           // Width over height of the board
+    frame: Rect,                           // Region played, in pixels of the original, y going up
+    pub dragged: Option<(Point2, Point2)>, // Where a drag started on screen, and the frame centre then
+}
+
+impl Cropper {
+    /// Crop screen of `image` for board `board` of size `grid_size`, with
+    /// the frame on `region`, `[x, y, width, height]` from the top left,
+    /// or in the middle if `None`.
+    pub fn new(
+        app: &App,
+        board: usize,
+        image: &DynamicImage,
+        grid_size: GridSize,
+        region: Option<[u32; 4]>,
+    ) -> Self {
+        let (width, height) = image.dimensions();
+        let preview = match width.max(height) > PREVIEW_SIZE {
+            true => image.resize(PREVIEW_SIZE, PREVIEW_SIZE, FilterType::Triangle),
+            false => image.clone(),
+        };
+        let mut cropper = Cropper {
+            board,
+            texture: wgpu::Texture::from_image(app, &preview),
+            image_size: vec2(width as f32, height as f32),
+            aspect: grid_size.width as f32 / grid_size.height as f32,
+            frame: Rect::from_w_h(0.0, 0.0),
+            dragged: None,
+        };
+        cropper.frame = match region {
+            Some([x, y, w, h]) => Rect::from_x_y_w_h(
+                x as f32 + w as f32 / 2.0,
+                height as f32 - y as f32 - h as f32 / 2.0,
+                w as f32,
+                h as f32,
+            ),
+            None => cropper.biggest_frame(),
+        };
+        cropper.fit();
+        cropper
+    }
+
+    /// Region framed, `[x, y, width, height]` in pixels from the top left
+    /// of the original image.
+    pub fn region(&self) -> [u32; 4] {
+        let left = self.frame.left().round().max(0.0) as u32;
+        let top = (self.image_size.y - self.frame.top()).round().max(0.0) as u32;
+        [
+            left,
+            top,
+            (self.frame.w().round() as u32).max(1),
+            (self.frame.h().round() as u32).max(1),
+        ]
+    }
+
+    /// Frame back in the middle, as big as it goes.
+    pub fn reset(&mut self) {
+        self.frame = self.biggest_frame();
+    }
+
+    /// Grow the frame by `steps` of `RESIZE_STEP`, or shrink it if negative,
+    /// around its centre.
+    pub fn resize(&mut self, steps: f32) {
+        let wh = self.frame.wh() * (1.0 + RESIZE_STEP).powf(steps);
+        self.frame = Rect::from_xy_wh(self.frame.xy(), wh);
+        self.fit();
+    }
+
+    /// Move the frame by `(dx, dy)` steps of `NUDGE_STEP`.
+    pub fn nudge(&mut self, dx: f32, dy: f32) {
+        let by = vec2(dx, dy) * self.image_size.max_element() * NUDGE_STEP;
+        self.frame = self.frame.shift(by);
+        self.fit();
+    }
+
+    /// Move the frame with the drag, the mouse or finger being at `point`.
+    pub fn drag_to(&mut self, win: Rect, point: Point2) {
+        let Some((start, centre)) = self.dragged else {
+            return;
+        };
+        let scale = self.scale(win);
+        self.frame = Rect::from_xy_wh(centre + (point - start) / scale, self.frame.wh());
+        self.fit();
+    }
+
+    /// Start a drag at `point`, on the screen.
+    pub fn start_drag(&mut self, point: Point2) {
+        self.dragged = Some((point, self.frame.xy()));
+    }
+
+    /// Draw the image and the frame over the whole of `win`.
+    pub fn draw(&self, draw: &Draw, win: Rect) {
+        draw.rect().xy(win.xy()).wh(win.wh()).color(BLACK);
+        let scale = self.scale(win);
+        let image = Rect::from_xy_wh(win.xy(), self.image_size * scale);
+        draw.texture(&self.texture).xy(image.xy()).wh(image.wh());
+        let frame = Rect::from_xy_wh(
+            image.bottom_left() + self.frame.xy() * scale,
+            self.frame.wh() * scale,
+        );
+        // dim what is left out of the frame
+        let shade = rgba(0.0, 0.0, 0.0, 0.6);
+        let bands = [
+            Rect::from_corners(image.top_left(), pt2(image.right(), frame.top())),
+            Rect::from_corners(pt2(image.left(), frame.bottom()), image.bottom_right()),
+            Rect::from_corners(pt2(image.left(), frame.top()), frame.bottom_left()),
+            Rect::from_corners(frame.top_right(), pt2(image.right(), frame.bottom())),
+        ];
+        for band in bands.iter().filter(|band| band.w() > 0.0 && band.h() > 0.0) {
+            draw.rect().xy(band.xy()).wh(band.wh()).color(shade);
+        }
+        draw.rect()
+            .xy(frame.xy())
+            .wh(frame.wh())
+            .no_fill()
+            .stroke(WHITE)
+            .stroke_weight(2.0);
+        let pad = win.h() * PAD_HEIGHT_FACTOR;
+        let help = "Drag the frame over the part to play, wheel or +/- to resize. Enter play, R middle, Esc cancel";
+        draw.text(help)
+            .font_size((pad / 5.0).max(6.0) as u32)
+            .x_y(win.x(), win.bottom() + pad / 2.0)
+            .w(win.w())
+            .center_justify()
+            .color(GRAY);
+    }
+
+    /// Points on screen per pixel of the image, fitting it in `win` with
+    /// room for the help below.
+    fn scale(&self, win: Rect) -> f32 {
+        let pad = win.h() * PAD_HEIGHT_FACTOR;
+        let room = vec2(win.w(), win.h() - 2.0 * pad);
+        (room / self.image_size).min_element().max(f32::EPSILON)
+    }
+
+    /// Biggest frame of the aspect of the board, in the middle.
+    fn biggest_frame(&self) -> Rect {
+        let width = self.image_size.x.min(self.image_size.y * self.aspect);
+        let wh = vec2(width, width / self.aspect);
+        Rect::from_xy_wh(self.image_size / 2.0, wh)
+    }
+
+    /// Keep the frame of the aspect of the board, between its smallest and
+    /// biggest size, and within the image.
+    fn fit(&mut self) {
+        let biggest = self.biggest_frame().w();
+        let width = self.frame.w().clamp(biggest * MIN_FRAME, biggest);
+        let wh = vec2(width, width / self.aspect);
+        let half = wh / 2.0;
+        let xy = self.frame.xy().max(half).min(self.image_size - half);
+        self.frame = Rect::from_xy_wh(xy, wh);
+    }
+}
@@ -0,0 +1,28 @@
+//! Image of the day, fetched from `daily_image_url` in the config and
+//! kept in the profile so it is downloaded once a day at most.
+
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+
+use nannou::app::Proxy;
+
+use crate::{clock, profile, web};
+
+/// Folder in the profile where the images of the day are kept.
+static DAILY_DIR: &str = "daily";
+
+/// Get today's image in the background, from the profile if it was already
+/// fetched. `{date}` in `url` is replaced by the date, eg, to seed the
+/// provider. The receiver gets the path of the image, `proxy` wakes the
+/// app up once it is there.
+pub fn fetch(url: &str, proxy: Proxy) -> Receiver<Result<PathBuf, String>> {
+    let date = clock::date();
+    let url = url.replace("{date}", &date);
+    web::in_background(proxy, move || {
+        let path = profile::profile_path(DAILY_DIR).join(format!("{date}.png"));
+        if !path.exists() {
+            web::download(&url, &path)?;
+        }
+        Ok(path)
+    })
+}
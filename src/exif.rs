@@ -0,0 +1,87 @@
+//! Orientation of photos, read from their EXIF metadata. Phones and
+//! cameras save photos the way the sensor was held and note how to turn
+//! them for viewing, without which they'd be played sideways.
+//!
+//! Only the orientation of JPEG photos is read, other formats are taken
+//! the way they are saved.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use nannou::image::DynamicImage;
+
+/// Tag of the orientation in the EXIF metadata.
+static ORIENTATION_TAG: u16 = 0x0112;
+
+/// Orientation of the photo at `path`, from 1 for the right way up to 8,
+/// as in the EXIF standard. `None` if it isn't a JPEG with an orientation.
+pub fn orientation(path: &Path) -> Option<u16> {
+    let mut file = BufReader::new(File::open(path).ok()?);
+    let mut marker = [0; 2];
+    file.read_exact(&mut marker).ok()?;
+    if marker != [0xFF, 0xD8] {
+        return None;
+    }
+    // the metadata is in one of the segments before the picture data
+    loop {
+        let mut header = [0; 4];
+        file.read_exact(&mut header).ok()?;
+        let [0xFF, kind, high, low] = header else {
+            return None;
+        };
+        let length = usize::from(u16::from_be_bytes([high, low])).checked_sub(2)?;
+        if kind == 0xDA || kind == 0xD9 {
+            return None;
+        }
+        let mut segment = vec![0; length];
+        file.read_exact(&mut segment).ok()?;
+        if kind == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return tiff_orientation(&segment[6..]);
+        }
+    }
+}
+
+/// Orientation in the first folder of EXIF metadata in `tiff`, which is
+/// laid out as a TIFF file.
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| {
+        let bytes = [*tiff.get(at)?, *tiff.get(at + 1)?];
+        Some(match little_endian {
+            true => u16::from_le_bytes(bytes),
+            false => u16::from_be_bytes(bytes),
+        })
+    };
+    let u32_at = |at: usize| {
+        let bytes = tiff.get(at..at + 4)?.try_into().ok()?;
+        Some(match little_endian {
+            true => u32::from_le_bytes(bytes),
+            false => u32::from_be_bytes(bytes),
+        })
+    };
+    let folder = u32_at(4)? as usize;
+    // entries of 12 bytes after their count: tag, type, count and value
+    (0..u16_at(folder)? as usize)
+        .map(|i| folder + 2 + i * 12)
+        .find(|&entry| u16_at(entry) == Some(ORIENTATION_TAG))
+        .and_then(|entry| u16_at(entry + 8))
+}
+
+/// `image` turned and flipped the right way up, as noted by `orientation`.
+pub fn upright(image: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
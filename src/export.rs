@@ -0,0 +1,123 @@
+//! Export of the solve history as a self-contained static HTML page,
+//! to browse or publish progress outside the game.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sliding_puzzle::GridSize;
+
+use crate::clock;
+use crate::history::Solve;
+
+/// Size of the charts in the page, in pixels.
+static CHART_WIDTH: f64 = 600.0;
+static CHART_HEIGHT: f64 = 200.0;
+
+static STYLE: &str = "body { font-family: sans-serif; margin: 2em auto; max-width: 50em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { padding: 0.2em 0.8em; text-align: right; border-bottom: 1px solid #ddd; }
+th:first-child, td:first-child { text-align: left; }
+svg { background: #fafafa; border: 1px solid #ddd; margin-bottom: 2em; }";
+
+/// Write `index.html` with the history of `solves` into `dir`
+/// and return its path.
+pub fn export_html(solves: &[Solve], dir: &Path) -> io::Result<PathBuf> {
+    let mut by_size: BTreeMap<GridSize, Vec<&Solve>> = BTreeMap::new();
+    for solve in solves {
+        by_size.entry(solve.grid_size()).or_default().push(solve);
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Sliding Puzzle - My solves</title>\n");
+    let _ = writeln!(html, "<style>\n{STYLE}\n</style>\n</head>\n<body>");
+    html.push_str("<h1>My solves</h1>\n");
+    let _ = writeln!(
+        html,
+        "<p>{} solves, exported {}.</p>",
+        solves.len(),
+        clock::format_date(clock::unix_now())
+    );
+
+    // Summary and progress chart per grid size
+    for (size, solves) in &by_size {
+        let count = solves.len() as f64;
+        let best_time = solves.iter().map(|s| s.seconds).fold(f64::MAX, f64::min);
+        let mean_time = solves.iter().map(|s| s.seconds).sum::<f64>() / count;
+        let best_moves = solves.iter().map(|s| s.moves).min().unwrap_or(0);
+        let mean_moves = solves.iter().map(|s| s.moves).sum::<usize>() as f64 / count;
+        let _ = writeln!(html, "<h2>{size}</h2>");
+        html.push_str("<table>\n<tr><th>Solves</th><th>Best time</th><th>Average time</th>");
+        html.push_str("<th>Fewest moves</th><th>Average moves</th></tr>\n");
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td></tr>\n</table>",
+            solves.len(),
+            clock::format_duration(best_time),
+            clock::format_duration(mean_time),
+            best_moves,
+            mean_moves
+        );
+        html.push_str(&time_chart(solves));
+    }
+
+    // Every solve, most recent first
+    html.push_str("<h2>History</h2>\n<table>\n");
+    html.push_str("<tr><th>Date</th><th>Image</th><th>Size</th><th>Moves</th>");
+    html.push_str("<th>Time</th><th>Seed</th><th>Hints</th></tr>\n");
+    for solve in solves.iter().rev() {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            clock::format_date(solve.timestamp),
+            escape(&solve.image),
+            solve.grid_size(),
+            solve.moves,
+            clock::format_duration(solve.seconds),
+            solve.seed.map(|s| format!("{s:X}")).unwrap_or_default(),
+            solve.hint_cost
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join("index.html");
+    fs::write(&path, html)?;
+    Ok(path)
+}
+
+/// Inline SVG line chart of the solve times, oldest solve on the left.
+fn time_chart(solves: &[&Solve]) -> String {
+    let max = solves.iter().map(|s| s.seconds).fold(1.0, f64::max);
+    let step = CHART_WIDTH / (solves.len().max(2) - 1) as f64;
+    let points: Vec<String> = solves
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let x = i as f64 * step;
+            let y = CHART_HEIGHT - s.seconds / max * (CHART_HEIGHT - 10.0);
+            format!("{x:.1},{y:.1}")
+        })
+        .collect();
+    format!(
+        "<svg width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" \
+         viewBox=\"-5 -5 {} {}\">\n\
+         <text x=\"0\" y=\"10\" font-size=\"12\">time, up to {}</text>\n\
+         <polyline fill=\"none\" stroke=\"#3465a4\" stroke-width=\"2\" points=\"{}\"/>\n</svg>\n",
+        CHART_WIDTH + 10.0,
+        CHART_HEIGHT + 10.0,
+        clock::format_duration(max),
+        points.join(" ")
+    )
+}
+
+/// Escape text for HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,209 @@
+//! Gallery of the images of the focused puzzle, opened with `G`. Shows a
+//! thumbnail of each image with the best time on it for the grid size,
+//! clicking one starts a puzzle with it. The arrow keys move the selection
+//! and `Enter` starts a puzzle with the selected image.
+
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use nannou::app::Proxy;
+use nannou::image::{imageops::FilterType, RgbaImage};
+use nannou::prelude::*;
+
+use crate::clock;
+use crate::history::Solve;
+use crate::puzzle::{self, Puzzle};
+
+/// Size of the thumbnails, in pixels.
+static THUMBNAIL_SIZE: u32 = 160;
+
+/// Smallest width of a column of the gallery, in points.
+static COLUMN_WIDTH: f32 = 150.0;
+
+/// Height of the captions under the thumbnails, in points.
+static CAPTION_HEIGHT: f32 = 32.0;
+
+pub struct Gallery {
+    pub scroll: f32,                        // How far the thumbnails are scrolled up
+    names: Vec<String>,                     // File name of each image
+    best: Vec<Option<f64>>,                 // Best time of each image, in seconds
+    current: usize,                         // Index of the image of the puzzle
+    pub selected: usize,                    // Index of the image picked with the keys or the mouse
+    thumbnails: Vec<Option<wgpu::Texture>>, // Thumbnails made so far
+    receiver: Receiver<(usize, RgbaImage)>, // Thumbnails made in the background
+}
+
+impl Gallery {
+    /// Gallery of the images of `puzzle`, with the best times of `solves`
+    /// at its grid size. Thumbnails are made in the background, `proxy`
+    /// wakes the app up for each of them.
+    pub fn new(puzzle: &Puzzle, solves: &[Solve], proxy: Proxy) -> Self {
+        let names: Vec<String> = puzzle
+            .image_list
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let best = names
+            .iter()
+            .map(|name| {
+                solves
+                    .iter()
+                    .filter(|s| &s.image == name && s.grid_size() == puzzle.grid_size)
+                    .map(|s| s.seconds)
+                    .reduce(f64::min)
+            })
+            .collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let images: Vec<_> = puzzle
+            .image_list
+            .iter()
+            .cloned()
+            .zip(puzzle.image_info.iter().cloned())
+            .collect();
+        thread::spawn(move || {
+            for (i, (path, info)) in images.into_iter().enumerate() {
+                // images which can't be opened are left without thumbnail
+                let Ok(image) = puzzle::open_image(&path, info.as_ref()) else {
+                    continue;
+                };
+                let thumbnail =
+                    image.resize_to_fill(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle);
+                if sender.send((i, thumbnail.to_rgba8())).is_err() {
+                    return;
+                }
+                let _ = proxy.wakeup();
+            }
+        });
+
+        Gallery {
+            scroll: 0.0,
+            thumbnails: names.iter().map(|_| None).collect(),
+            names,
+            best,
+            current: puzzle.image_index_current,
+            selected: puzzle.image_index_current,
+            receiver,
+        }
+    }
+
+    /// Make textures of the thumbnails done since the last call.
+    pub fn poll(&mut self, app: &App) {
+        while let Ok((i, thumbnail)) = self.receiver.try_recv() {
+            let image = nannou::image::DynamicImage::ImageRgba8(thumbnail);
+            self.thumbnails[i] = Some(wgpu::Texture::from_image(app, &image));
+        }
+    }
+
+    /// Number of columns and size of a thumbnail cell in `win`.
+    fn layout(&self, win: Rect) -> (usize, f32) {
+        let columns = ((win.w() / COLUMN_WIDTH) as usize).max(1);
+        (columns, win.w() / columns as f32)
+    }
+
+    /// Centre of the thumbnail of image `i`.
+    fn centre(&self, win: Rect, i: usize) -> Point2 {
+        let (columns, cell) = self.layout(win);
+        let (col, row) = (i % columns, i / columns);
+        let x = win.left() + (col as f32 + 0.5) * cell;
+        let y = win.top() + self.scroll - row as f32 * (cell + CAPTION_HEIGHT) - cell / 2.0;
+        pt2(x, y)
+    }
+
+    /// Index of the image whose thumbnail is at `point`, if any.
+    pub fn hit(&self, win: Rect, point: Point2) -> Option<usize> {
+        let (_columns, cell) = self.layout(win);
+        (0..self.names.len()).find(|&i| {
+            let centre = self.centre(win, i);
+            (point.x - centre.x).abs() < cell / 2.0 && (point.y - centre.y).abs() < cell / 2.0
+        })
+    }
+
+    /// Move the selection by `dx` columns and `dy` rows, staying on the
+    /// images, and scroll it into view.
+    pub fn move_selection(&mut self, dx: isize, dy: isize, win: Rect) {
+        if self.names.is_empty() {
+            return;
+        }
+        let (columns, cell) = self.layout(win);
+        let index = self.selected as isize + dx + dy * columns as isize;
+        self.selected = index.clamp(0, self.names.len() as isize - 1) as usize;
+        // scroll just enough for the thumbnail and its caption to show
+        let centre = self.centre(win, self.selected);
+        let above = centre.y + cell / 2.0 - win.top();
+        let below = win.bottom() - (centre.y - cell / 2.0 - CAPTION_HEIGHT);
+        if above > 0.0 {
+            self.scroll_by(above, win);
+        } else if below > 0.0 {
+            self.scroll_by(-below, win);
+        }
+    }
+
+    /// Scroll by `dy` points, keeping the thumbnails in view.
+    pub fn scroll_by(&mut self, dy: f32, win: Rect) {
+        let (columns, cell) = self.layout(win);
+        let rows = self.names.len().div_ceil(columns);
+        let max = (rows as f32 * (cell + CAPTION_HEIGHT) - win.h()).max(0.0);
+        self.scroll = (self.scroll - dy).clamp(0.0, max);
+    }
+
+    pub fn draw(&self, draw: &Draw, win: Rect) {
+        draw.rect().xy(win.xy()).wh(win.wh()).color(BLACK);
+        if self.names.is_empty() {
+            draw.text("No images, add some to the images folder")
+                .xy(win.xy())
+                .w(win.w())
+                .center_justify()
+                .color(WHITE);
+            return;
+        }
+        let (_columns, cell) = self.layout(win);
+        let size = cell - 10.0;
+        for (i, name) in self.names.iter().enumerate() {
+            let centre = self.centre(win, i);
+            if centre.y - cell > win.top() || centre.y + cell < win.bottom() {
+                continue;
+            }
+            match &self.thumbnails[i] {
+                Some(texture) => {
+                    draw.texture(texture).xy(centre).w_h(size, size);
+                }
+                None => {
+                    draw.rect().xy(centre).w_h(size, size).color(DARKSLATEGREY);
+                }
+            }
+            if i == self.selected {
+                draw.rect()
+                    .xy(centre)
+                    .w_h(cell, cell)
+                    .no_fill()
+                    .stroke(WHITE)
+                    .stroke_weight(2.0);
+            }
+            if i == self.current {
+                draw.rect()
+                    .xy(centre)
+                    .w_h(size, size)
+                    .no_fill()
+                    .stroke(YELLOW)
+                    .stroke_weight(3.0);
+            }
+            let best = match self.best[i] {
+                Some(seconds) => format!("Best {}", clock::format_duration(seconds)),
+                None => String::from("Not solved yet"),
+            };
+            draw.text(&format!("{name}\n{best}"))
+                .font_size(11)
+                .x_y(centre.x, centre.y - cell / 2.0 - CAPTION_HEIGHT / 2.0)
+                .w_h(cell, CAPTION_HEIGHT)
+                .center_justify()
+                .align_text_middle_y()
+                .color(WHITE);
+        }
+    }
+}
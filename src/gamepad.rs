@@ -0,0 +1,92 @@
+//! Game controllers. The d-pad and the left stick slide the pieces into
+//! the empty space, the face buttons scramble, reset and toggle the
+//! numbers, and the shoulder buttons go through the images.
+//!
+//! Buttons run the same actions as the keys, see `palette::Action`.
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use crate::palette::Action;
+
+/// Tilt of the stick past which it slides a piece.
+static STICK_PRESSED: f32 = 0.6;
+
+/// Tilt of the stick under which it is back in the middle, ready to slide
+/// the next piece.
+static STICK_RELEASED: f32 = 0.3;
+
+pub struct Gamepads {
+    gilrs: Gilrs,
+    stick: (f32, f32), // Tilt of the left stick, right and up
+    stick_held: bool,  // Whether the stick slid a piece and was not let back since
+}
+
+impl Gamepads {
+    pub fn new() -> Result<Self, String> {
+        let gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+        Ok(Gamepads {
+            gilrs,
+            stick: (0.0, 0.0),
+            stick_held: false,
+        })
+    }
+
+    /// Returns `true` if a controller is plugged in, its input is polled
+    /// every frame.
+    pub fn connected(&self) -> bool {
+        self.gilrs.gamepads().next().is_some()
+    }
+
+    /// Actions of the buttons pressed and the stick moved since the last call.
+    pub fn poll(&mut self) -> Vec<Action> {
+        let mut actions = vec![];
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => actions.extend(button_action(button)),
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => self.stick.0 = value,
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => self.stick.1 = value,
+                _ => continue,
+            }
+            actions.extend(self.stick_action());
+        }
+        actions
+    }
+
+    /// Slide the stick is tilted for, once until it is let back.
+    fn stick_action(&mut self) -> Option<Action> {
+        let (x, y) = self.stick;
+        if x.abs().max(y.abs()) < STICK_RELEASED {
+            self.stick_held = false;
+            return None;
+        }
+        if self.stick_held || x.abs().max(y.abs()) < STICK_PRESSED {
+            return None;
+        }
+        self.stick_held = true;
+        Some(match x.abs() > y.abs() {
+            true if x > 0.0 => Action::SlideRight,
+            true => Action::SlideLeft,
+            false if y > 0.0 => Action::SlideUp,
+            false => Action::SlideDown,
+        })
+    }
+}
+
+/// Action of `button`, if it has one.
+fn button_action(button: Button) -> Option<Action> {
+    match button {
+        Button::DPadUp => Some(Action::SlideUp),
+        Button::DPadDown => Some(Action::SlideDown),
+        Button::DPadLeft => Some(Action::SlideLeft),
+        Button::DPadRight => Some(Action::SlideRight),
+        Button::South => Some(Action::Scramble),
+        Button::East => Some(Action::Reset),
+        Button::West => Some(Action::ToggleNumbers),
+        Button::North => Some(Action::Hint),
+        Button::LeftTrigger => Some(Action::PreviousImage),
+        Button::RightTrigger => Some(Action::NextImage),
+        Button::Start => Some(Action::Menu),
+        Button::Select => Some(Action::Undo),
+        _ => None,
+    }
+}
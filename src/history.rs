@@ -0,0 +1,61 @@
+//! History of all the completed solves, kept in the profile as one
+//! JSON record per line so that appending never rewrites the file.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sliding_puzzle::GridSize;
+
+use crate::profile;
+
+/// File in the profile with the solve history.
+static HISTORY_FILE: &str = "solves.jsonl";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Solve {
+    pub timestamp: u64, // Unix time the puzzle was solved
+    pub image: String,  // File name of the image, empty for the blank image
+    pub size: usize,    // Grid size, the width of rectangular grids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<usize>, // Height of rectangular grids, `None` for square ones
+    pub moves: usize,   // Number of moves taken
+    pub seconds: f64,   // Time from the first move to the solution
+    #[serde(default)]
+    pub seed: Option<u64>, // Scramble seed, for seeded scrambles and challenges
+    #[serde(default)]
+    pub hint_cost: usize, // Cost of the hints taken, 0 for a solve without help
+}
+
+impl Solve {
+    /// Size of the grid the solve was made on.
+    pub fn grid_size(&self) -> GridSize {
+        GridSize {
+            width: self.size,
+            height: self.height.unwrap_or(self.size),
+        }
+    }
+}
+
+/// Append a solve to the history.
+pub fn record(solve: &Solve) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(profile::profile_path(HISTORY_FILE))?;
+    writeln!(file, "{}", serde_json::to_string(solve)?)?;
+    debug!("Recorded solve {solve:?}");
+    Ok(())
+}
+
+/// All the solves in the history, oldest first.
+/// Lines which can't be read, eg, from a newer version, are skipped.
+pub fn load() -> Vec<Solve> {
+    let Ok(text) = fs::read_to_string(profile::profile_dir().join(HISTORY_FILE)) else {
+        return vec![];
+    };
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
@@ -0,0 +1,159 @@
+//! The images folder: where it is, the images in it and its subfolders,
+//! and a watch on it so images added while the game runs can be played
+//! without restarting.
+//!
+//! Images are sorted the way people sort names, `photo 2` before
+//! `photo 10`, or shuffled with `--shuffle-images`.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use nannou::app::Proxy;
+use nannou::image;
+use nannou::rand::rand::{seq::SliceRandom, thread_rng};
+
+use crate::profile;
+
+/// Folder of the images when neither the command line nor the config
+/// name one, in the working folder or else in the profile.
+static IMAGES_DIR: &str = "images";
+
+/// Time between two looks at the images folder for changes.
+static WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Folder of the images: `arg` from the command line, or else `config`
+/// from the config, or else `images` in the working folder if there is
+/// one, or else in the profile, so the game finds its images wherever it
+/// is started from.
+pub fn images_dir(arg: Option<&PathBuf>, config: &str) -> PathBuf {
+    if let Some(dir) = arg {
+        return dir.clone();
+    }
+    if !config.is_empty() {
+        return PathBuf::from(config);
+    }
+    match Path::new(IMAGES_DIR).is_dir() {
+        true => PathBuf::from(IMAGES_DIR),
+        false => profile::profile_path(IMAGES_DIR),
+    }
+}
+
+/// Get the list of images from the images folder `dir` and its
+/// subfolders, hidden ones aside, sorted by name or shuffled if `shuffle`.
+/// Images in any format the image crate can read are accepted, those
+/// which turn out not to open are skipped when shown.
+/// If no images are found, an empty vector is returned.
+pub fn get_images(dir: &Path, shuffle: bool) -> Vec<PathBuf> {
+    let mut images = scan(dir, |folder, e| {
+        println!("Error reading images folder {}: {e}", folder.display())
+    });
+    if shuffle {
+        images.shuffle(&mut thread_rng());
+    }
+    images
+}
+
+/// Look at `dir` every `WATCH_INTERVAL` in the background. The receiver
+/// gets the images in it each time they change, shuffled if `shuffle`,
+/// `proxy` wakes the app up when they do.
+pub fn watch(dir: PathBuf, shuffle: bool, proxy: Proxy) -> Receiver<Vec<PathBuf>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // errors were reported when the images were first read
+        let mut images = scan(&dir, |_, _| ());
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let now = scan(&dir, |_, _| ());
+            if now == images {
+                continue;
+            }
+            images = now;
+            let mut sent = images.clone();
+            if shuffle {
+                sent.shuffle(&mut thread_rng());
+            }
+            if sender.send(sent).is_err() {
+                break;
+            }
+            let _ = proxy.wakeup();
+        }
+    });
+    receiver
+}
+
+/// Images of `dir` and its subfolders, in natural order, `on_error` being
+/// called for the folders which can't be read.
+fn scan(dir: &Path, mut on_error: impl FnMut(&Path, io::Error)) -> Vec<PathBuf> {
+    let mut images = vec![];
+    let mut folders = vec![dir.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(e) => {
+                on_error(&folder, e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            // links to folders are not followed, they could loop
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                if !hidden {
+                    folders.push(path);
+                }
+                continue;
+            }
+            // any format the image crate reads, eg, `photo.JPG`
+            let readable = image::ImageFormat::from_path(&path).is_ok_and(|f| f.can_read());
+            if readable && !hidden {
+                images.push(path);
+            }
+        }
+    }
+    images.sort_by(|a, b| natural_cmp(a, b).then_with(|| a.cmp(b)));
+    images
+}
+
+/// Order of `a` and `b` as people sort names: ignoring case, and with
+/// runs of digits compared as numbers, eg, `photo 2` before `photo 10`.
+fn natural_cmp(a: &Path, b: &Path) -> Ordering {
+    let (a, b) = (
+        a.to_string_lossy().to_lowercase(),
+        b.to_string_lossy().to_lowercase(),
+    );
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                // without leading zeros, the longer number is the bigger
+                (x.len(), x).cmp(&(y.len(), y))
+            }
+            (Some(_), Some(_)) => a.next().cmp(&b.next()),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Run of digits at the start of `chars`, taken out of it.
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+        digits.push(digit);
+    }
+    digits
+}
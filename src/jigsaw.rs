@@ -0,0 +1,119 @@
+//! Jigsaw edges for the pieces, toggled with `Shift+J`. Each edge between
+//! two pieces of the solved image gets a round tab on one side and the
+//! matching notch on the other, so the pieces interlock like a jigsaw.
+//!
+//! The pieces are cut once into images a bit larger than a cell, with the
+//! tabs reaching over the neighbouring cells and transparent notches.
+
+use nannou::image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use sliding_puzzle::GridSize;
+
+/// How far the centre of a tab is past the edge, in cells.
+static TAB_OFFSET: f32 = 0.1;
+
+/// Radius of the tabs and notches, in cells.
+static TAB_RADIUS: f32 = 0.18;
+
+/// Width of the dark outline of the pieces, in pixels.
+static OUTLINE: f32 = 1.5;
+
+/// Pixels a cut piece reaches past its cell on each side, for cells of
+/// `cell_size` pixels.
+pub fn margin(cell_size: u32) -> u32 {
+    ((TAB_OFFSET + TAB_RADIUS) * cell_size as f32).ceil() as u32
+}
+
+/// Cut the pieces of `solved` for a board of `grid_size`, indexed by piece
+/// number. Piece 0, the empty space, is an empty image. The piece of the
+/// last cell is cut too, for the Loopover variant.
+pub fn cut(solved: &DynamicImage, grid_size: GridSize) -> Vec<RgbaImage> {
+    let (width, height) = solved.dimensions();
+    let cell_size = width / grid_size.width as u32;
+    let margin = margin(cell_size);
+    let side = cell_size + 2 * margin;
+    let mut pieces = vec![RgbaImage::new(0, 0)];
+    for piece in 1..=grid_size.cells() {
+        let (col, row) = ((piece - 1) % grid_size.width, (piece - 1) / grid_size.width);
+        let shape = Shape::new(grid_size, col, row);
+        let x0 = (col as u32 * cell_size) as i64 - margin as i64;
+        let y0 = (row as u32 * cell_size) as i64 - margin as i64;
+        let inside = |x: f32, y: f32| {
+            let u = (x - margin as f32) / cell_size as f32;
+            let v = (y - margin as f32) / cell_size as f32;
+            shape.contains(u, v)
+        };
+        pieces.push(RgbaImage::from_fn(side, side, |x, y| {
+            let (sx, sy) = (x0 + x as i64, y0 + y as i64);
+            let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+            if !inside(fx, fy) || sx < 0 || sy < 0 || sx >= width as i64 || sy >= height as i64 {
+                return Rgba([0, 0, 0, 0]);
+            }
+            let mut pixel = solved.get_pixel(sx as u32, sy as u32);
+            let edge = [
+                (-OUTLINE, 0.0),
+                (OUTLINE, 0.0),
+                (0.0, -OUTLINE),
+                (0.0, OUTLINE),
+            ]
+            .iter()
+            .any(|(dx, dy)| !inside(fx + dx, fy + dy));
+            if edge {
+                for channel in &mut pixel.0[..3] {
+                    *channel /= 3;
+                }
+            }
+            pixel
+        }));
+    }
+    pieces
+}
+
+/// Tabs and notches of one piece. Sides are left, right, top and bottom,
+/// `Some(true)` for a tab, `Some(false)` for a notch and `None` for the
+/// straight sides on the border of the board.
+struct Shape {
+    sides: [Option<bool>; 4],
+}
+
+impl Shape {
+    /// Shape of the piece at `(col, row)` of the solved image, row 0 being
+    /// the top one.
+    fn new(grid_size: GridSize, col: usize, row: usize) -> Self {
+        Shape {
+            sides: [
+                (col > 0).then(|| !tab_first(col - 1, row, true)),
+                (col < grid_size.width - 1).then(|| tab_first(col, row, true)),
+                (row > 0).then(|| !tab_first(col, row - 1, false)),
+                (row < grid_size.height - 1).then(|| tab_first(col, row, false)),
+            ],
+        }
+    }
+
+    /// Returns `true` if `(u, v)`, in cells from the top left corner of
+    /// the piece, is part of it.
+    fn contains(&self, u: f32, v: f32) -> bool {
+        let mut inside = (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v);
+        let outward = [(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)];
+        for (side, (nx, ny)) in self.sides.iter().zip(outward) {
+            let Some(tab) = side else {
+                continue;
+            };
+            // the tab bulges out of the middle of the side, the notch is
+            // where the tab of the neighbour goes
+            let direction = if *tab { TAB_OFFSET } else { -TAB_OFFSET };
+            let cx = 0.5 + nx * (0.5 + direction);
+            let cy = 0.5 + ny * (0.5 + direction);
+            if (u - cx).powi(2) + (v - cy).powi(2) < TAB_RADIUS * TAB_RADIUS {
+                inside = *tab;
+            }
+        }
+        inside
+    }
+}
+
+/// Returns `true` if the tab of the edge after the piece at `(col, row)`
+/// belongs to that piece rather than to the next one, to the right if
+/// `vertical` or below otherwise. Mixed up so the pieces differ.
+fn tab_first(col: usize, row: usize, vertical: bool) -> bool {
+    !(col * 7 + row * 13 + vertical as usize * 5).is_multiple_of(3)
+}
@@ -0,0 +1,338 @@
+//! Keys running the actions of the game, see `palette::Action`. Each action
+//! has default keys, which can be replaced in the `[keys]` table of the
+//! config for keyboards where they are awkward to reach, eg,
+//!
+//! ```toml
+//! [keys]
+//! scramble = ["Space"]
+//! undo = ["Backspace", "Ctrl+Z"]
+//! next_image = ["PageDown"]
+//! ```
+//!
+//! Actions are named in snake case after `palette::Action`. Keys are a key
+//! name, as in `KEY_NAMES`, after any of `Ctrl+`, `Shift+` and `Alt+`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use nannou::event::{Key, ModifiersState};
+
+use crate::palette::Action;
+
+/// Names of the keys which can be bound, the first name of a key is the
+/// one shown in the palette. Names are matched ignoring case.
+static KEY_NAMES: &[(&str, Key)] = &[
+    ("A", Key::A),
+    ("B", Key::B),
+    ("C", Key::C),
+    ("D", Key::D),
+    ("E", Key::E),
+    ("F", Key::F),
+    ("G", Key::G),
+    ("H", Key::H),
+    ("I", Key::I),
+    ("J", Key::J),
+    ("K", Key::K),
+    ("L", Key::L),
+    ("M", Key::M),
+    ("N", Key::N),
+    ("O", Key::O),
+    ("P", Key::P),
+    ("Q", Key::Q),
+    ("R", Key::R),
+    ("S", Key::S),
+    ("T", Key::T),
+    ("U", Key::U),
+    ("V", Key::V),
+    ("W", Key::W),
+    ("X", Key::X),
+    ("Y", Key::Y),
+    ("Z", Key::Z),
+    ("0", Key::Key0),
+    ("1", Key::Key1),
+    ("2", Key::Key2),
+    ("3", Key::Key3),
+    ("4", Key::Key4),
+    ("5", Key::Key5),
+    ("6", Key::Key6),
+    ("7", Key::Key7),
+    ("8", Key::Key8),
+    ("9", Key::Key9),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+    ("Up", Key::Up),
+    ("Down", Key::Down),
+    ("Left", Key::Left),
+    ("Right", Key::Right),
+    ("Esc", Key::Escape),
+    ("Escape", Key::Escape),
+    ("Tab", Key::Tab),
+    ("Space", Key::Space),
+    ("Enter", Key::Return),
+    ("Backspace", Key::Back),
+    ("Insert", Key::Insert),
+    ("Delete", Key::Delete),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    (".", Key::Period),
+    (",", Key::Comma),
+    ("+", Key::Plus),
+    ("=", Key::Equals),
+    ("-", Key::Minus),
+    ("/", Key::Slash),
+    ("\\", Key::Backslash),
+    (";", Key::Semicolon),
+    ("'", Key::Apostrophe),
+    ("[", Key::LBracket),
+    ("]", Key::RBracket),
+    ("`", Key::Grave),
+    ("Numpad0", Key::Numpad0),
+    ("Numpad1", Key::Numpad1),
+    ("Numpad2", Key::Numpad2),
+    ("Numpad3", Key::Numpad3),
+    ("Numpad4", Key::Numpad4),
+    ("Numpad5", Key::Numpad5),
+    ("Numpad6", Key::Numpad6),
+    ("Numpad7", Key::Numpad7),
+    ("Numpad8", Key::Numpad8),
+    ("Numpad9", Key::Numpad9),
+    ("NumpadAdd", Key::NumpadAdd),
+    ("NumpadSubtract", Key::NumpadSubtract),
+    ("NumpadEnter", Key::NumpadEnter),
+];
+
+/// Keys of `action` unless the config says otherwise.
+fn default_keys(action: Action) -> &'static [&'static str] {
+    match action {
+        Action::Reset => &["R"],
+        Action::Scramble => &["S"],
+        Action::SlideUp => &["Up"],
+        Action::SlideDown => &["Down"],
+        Action::SlideLeft => &["Left"],
+        Action::SlideRight => &["Right"],
+        Action::ToggleNumbers => &["N"],
+        Action::CycleNumbering => &["Shift+N"],
+        Action::ToggleOutline => &["F"],
+        Action::ToggleThumbnail => &["T"],
+        Action::ToggleGenerated => &["J"],
+        Action::ToggleColors => &["Shift+C"],
+        Action::NewPicture => &["Shift+G"],
+        Action::ToggleJigsaw => &["Shift+J"],
+        Action::ToggleRotation => &["Shift+Q"],
+        Action::ToggleLoopover => &["Shift+L"],
+        Action::Klotski => &["Ctrl+K"],
+        Action::RotatePiece => &["Q"],
+        Action::NextImage => &["."],
+        Action::PreviousImage => &[","],
+        Action::RandomImage => &["/"],
+        Action::CropImage => &["Ctrl+I"],
+        Action::NextCollection => &["Tab"],
+        Action::NextLayout => &["L"],
+        Action::GrowGrid => &["+", "=", "NumpadAdd"],
+        Action::ShrinkGrid => &["-", "NumpadSubtract"],
+        Action::ExportHistory => &["E"],
+        Action::ExportPicture => &["I"],
+        Action::ExportSheet => &["W"],
+        Action::Minimize => &["M"],
+        Action::ToggleBorderless => &["D"],
+        Action::ToggleAlwaysOnTop => &["K"],
+        Action::Challenge => &["X"],
+        Action::ControlPanel => &["F2"],
+        Action::Fullscreen => &["F11"],
+        Action::Screenshot => &["F12"],
+        Action::ResetZoom => &["0"],
+        Action::Gallery => &["G"],
+        Action::Stats => &["F3"],
+        Action::Hint => &["H"],
+        Action::Peek => &["P"],
+        Action::Bookmark => &["B"],
+        Action::RestoreBookmark => &["Shift+B"],
+        Action::Undo => &["U", "Ctrl+Z"],
+        Action::Redo => &["Ctrl+Y"],
+        Action::AutoSolve => &["A"],
+        Action::Pause => &["Space"],
+        Action::SaveGame => &["Ctrl+S"],
+        Action::LoadGame => &["Ctrl+L"],
+        Action::Editor => &["Ctrl+E"],
+        Action::CopyPosition => &["Ctrl+C"],
+        Action::PastePosition => &["Ctrl+V"],
+        Action::Replay => &["Ctrl+R"],
+        Action::ExportReplay => &["Ctrl+G"],
+        Action::Menu => &["Home"],
+        #[cfg(feature = "settings")]
+        Action::Settings => &["O"],
+        #[cfg(feature = "sound")]
+        Action::ToggleSound => &["Shift+M"],
+        #[cfg(feature = "sound")]
+        Action::NextTrack => &["Ctrl+N"],
+        #[cfg(feature = "sound")]
+        Action::ToggleMusic => &["Ctrl+M"],
+        #[cfg(feature = "record")]
+        Action::Record => &["F9"],
+        #[cfg(feature = "url")]
+        Action::PasteUrl => &["Ctrl+U"],
+        #[cfg(feature = "url")]
+        Action::Surprise => &["Shift+S"],
+        #[cfg(feature = "webcam")]
+        Action::Webcam => &["C"],
+        #[cfg(feature = "twitch")]
+        Action::TwitchMode => &["V"],
+        Action::Quit => &["Esc"],
+    }
+}
+
+/// A key with the modifiers to hold down with it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Binding {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Binding {
+    /// Parse `Ctrl+Shift+Z` and the like, see `KEY_NAMES`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut binding = Binding {
+            key: Key::Escape,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        };
+        let mut rest = text.trim();
+        // `+` alone or after a modifier is the key, not a separator
+        while let Some((modifier, key)) = rest.split_once('+').filter(|(m, _)| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => return None,
+            }
+            rest = key;
+        }
+        binding.key = KEY_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(rest))?
+            .1;
+        Some(binding)
+    }
+
+    /// Number of modifiers of the binding, if they are all held down in
+    /// `mods`. Other modifiers may be held too.
+    fn modifiers_held(&self, mods: ModifiersState) -> Option<usize> {
+        let wanted = [
+            (self.ctrl, mods.ctrl()),
+            (self.shift, mods.shift()),
+            (self.alt, mods.alt()),
+        ];
+        match wanted.iter().all(|&(wanted, held)| !wanted || held) {
+            true => Some(wanted.iter().filter(|(wanted, _)| *wanted).count()),
+            false => None,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+        ] {
+            if held {
+                write!(f, "{name}")?;
+            }
+        }
+        let name = KEY_NAMES.iter().find(|(_, key)| *key == self.key);
+        write!(f, "{}", name.map_or("?", |(name, _)| name))
+    }
+}
+
+/// Keys of every action, the defaults with the changes from the config.
+pub struct KeyMap {
+    bindings: Vec<(Binding, Action)>, // Keys and their action, the ones from the config first
+}
+
+impl KeyMap {
+    /// Key map with the keys of `config`, the `[keys]` table of the config,
+    /// replacing the default keys of their action. Entries which can't be
+    /// read are left out with a message.
+    pub fn new(config: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut bindings = vec![];
+        let mut defaults = vec![];
+        for &action in Action::ALL {
+            let name = config_name(action);
+            let (keys, target): (Vec<&str>, _) = match config.get(&name) {
+                Some(keys) => (keys.iter().map(String::as_str).collect(), &mut bindings),
+                None => (default_keys(action).to_vec(), &mut defaults),
+            };
+            for text in keys {
+                match Binding::parse(text) {
+                    Some(binding) => target.push((binding, action)),
+                    None => println!("Unknown key '{text}' for {name}"),
+                }
+            }
+        }
+        for name in config.keys() {
+            if !Action::ALL
+                .iter()
+                .any(|&action| config_name(action) == *name)
+            {
+                println!("Unknown action '{name}' in the keys of the config");
+            }
+        }
+        bindings.extend(defaults);
+        KeyMap { bindings }
+    }
+
+    /// Action of `key` pressed with `mods` held down. Of the bindings of
+    /// the key, the one with the most modifiers held wins, so `Shift+N`
+    /// goes before `N`.
+    pub fn action(&self, key: Key, mods: ModifiersState) -> Option<Action> {
+        let mut best: Option<(usize, Action)> = None;
+        for (binding, action) in &self.bindings {
+            if binding.key != key {
+                continue;
+            }
+            if let Some(held) = binding.modifiers_held(mods) {
+                if best.is_none_or(|(most, _)| held > most) {
+                    best = Some((held, *action));
+                }
+            }
+        }
+        best.map(|(_, action)| action)
+    }
+
+    /// First key of `action`, as shown in the palette. Empty if it has none.
+    pub fn key_name(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .find(|(_, a)| *a == action)
+            .map(|(binding, _)| binding.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Name of `action` in the config, eg, `slide_up` for `Action::SlideUp`.
+pub fn config_name(action: Action) -> String {
+    let mut name = String::new();
+    for (i, c) in format!("{action:?}").chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
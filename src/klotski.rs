@@ -0,0 +1,190 @@
+//! Klotski, the sliding block puzzle: blocks of one, two or four cells on
+//! a board with two empty cells, slid one cell at a time until the big
+//! block reaches the exit. Blocks span several cells, so unlike `Board`
+//! the board is a list of blocks, plus a grid of the block covering each
+//! cell to check the moves against.
+//!
+//! Layouts are written as rows of letters from the top, one letter per
+//! block and `.` for an empty cell, see `LAYOUTS`.
+
+use crate::GridSize;
+
+/// A block and where it is. Cells are indexed `(ix, iy)` as on `Board`,
+/// row 0 being the bottom one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub x: usize,      // Column of its left cells
+    pub y: usize,      // Row of its bottom cells
+    pub width: usize,  // Columns it spans
+    pub height: usize, // Rows it spans
+}
+
+impl Block {
+    /// Number of cells the block covers.
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// Starting position of a Klotski board.
+pub struct Layout {
+    pub name: &'static str,
+    pub rows: &'static [&'static str], // Rows from the top, a letter per block and `.` for empty
+    pub exit: (usize, usize),          // Cell the bottom left of the big block must reach
+}
+
+/// Classic layouts, the hardest first. All are 4 by 5 with the exit in
+/// the middle of the bottom edge.
+pub static LAYOUTS: &[Layout] = &[
+    Layout {
+        name: "Forget-me-not",
+        rows: &["ABBC", "ABBC", "DEEF", "DGHF", "I..J"],
+        exit: (1, 0),
+    },
+    Layout {
+        name: "Line up",
+        rows: &["ABBC", "ABBC", "DEFG", "HIIJ", "H..J"],
+        exit: (1, 0),
+    },
+    Layout {
+        name: "Side door",
+        rows: &["BBAC", "BBAC", "DEFG", "DEHI", "J..K"],
+        exit: (1, 0),
+    },
+    Layout {
+        name: "Four generals",
+        rows: &["ABBC", "DBBE", "FGGH", "IJKL", "M..N"],
+        exit: (1, 0),
+    },
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Klotski {
+    size: GridSize,
+    blocks: Vec<Block>,             // Blocks, the big one first
+    cells: Vec<Vec<Option<usize>>>, // Index of the block covering each cell, by row from the bottom
+    exit: (usize, usize),           // Cell the bottom left of the big block must reach
+}
+
+impl Klotski {
+    /// Board set up as `layout`. Each letter must cover a rectangle of
+    /// cells, and the rows must be of the same width.
+    pub fn from_layout(layout: &Layout) -> Result<Self, String> {
+        let width = layout.rows.first().map_or(0, |row| row.len());
+        let height = layout.rows.len();
+        if width == 0 || layout.rows.iter().any(|row| row.len() != width) {
+            return Err(String::from("Rows of different widths"));
+        }
+        let mut letters: Vec<(char, Vec<(usize, usize)>)> = vec![];
+        for (row, line) in layout.rows.iter().enumerate() {
+            for (ix, letter) in line.chars().enumerate().filter(|&(_, c)| c != '.') {
+                let cell = (ix, height - 1 - row);
+                match letters.iter_mut().find(|(l, _)| *l == letter) {
+                    Some((_, cells)) => cells.push(cell),
+                    None => letters.push((letter, vec![cell])),
+                }
+            }
+        }
+        let mut blocks = vec![];
+        for (letter, cells) in letters {
+            let x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+            let y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+            let block = Block {
+                x,
+                y,
+                width: cells.iter().map(|c| c.0).max().unwrap_or(0) + 1 - x,
+                height: cells.iter().map(|c| c.1).max().unwrap_or(0) + 1 - y,
+            };
+            if block.area() != cells.len() {
+                return Err(format!("Block {letter} isn't a rectangle"));
+            }
+            blocks.push(block);
+        }
+        // the first of the biggest blocks is the one to get out
+        let big = (0..blocks.len())
+            .max_by_key(|&i| (blocks[i].area(), std::cmp::Reverse(i)))
+            .ok_or_else(|| String::from("No blocks"))?;
+        blocks.swap(0, big);
+        let mut klotski = Klotski {
+            size: GridSize { width, height },
+            blocks,
+            cells: vec![vec![None; width]; height],
+            exit: layout.exit,
+        };
+        klotski.fill();
+        Ok(klotski)
+    }
+
+    pub fn size(&self) -> GridSize {
+        self.size
+    }
+
+    /// Blocks, the big one first.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Cell the bottom left of the big block must reach.
+    pub fn exit(&self) -> (usize, usize) {
+        self.exit
+    }
+
+    /// Index of the block covering `(ix, iy)`, `None` for an empty cell.
+    pub fn block_at(&self, ix: usize, iy: usize) -> Option<usize> {
+        *self.cells.get(iy)?.get(ix)?
+    }
+
+    /// Returns `true` if `block` can slide one cell by `(dx, dy)`: the
+    /// cells it would cover are on the board and empty, or its own.
+    pub fn can_move(&self, block: usize, dx: isize, dy: isize) -> bool {
+        let Some(&Block {
+            x,
+            y,
+            width,
+            height,
+        }) = self.blocks.get(block)
+        else {
+            return false;
+        };
+        let (Some(x), Some(y)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+            return false;
+        };
+        if x + width > self.size.width || y + height > self.size.height {
+            return false;
+        }
+        (y..y + height)
+            .all(|iy| (x..x + width).all(|ix| self.cells[iy][ix].is_none_or(|b| b == block)))
+    }
+
+    /// Slide `block` one cell by `(dx, dy)`. Returns `false` if it can't
+    /// move that way.
+    pub fn try_move(&mut self, block: usize, dx: isize, dy: isize) -> bool {
+        if !self.can_move(block, dx, dy) {
+            return false;
+        }
+        let moved = &mut self.blocks[block];
+        moved.x = moved.x.wrapping_add_signed(dx);
+        moved.y = moved.y.wrapping_add_signed(dy);
+        self.fill();
+        true
+    }
+
+    /// Returns `true` once the big block is at the exit.
+    pub fn is_solved(&self) -> bool {
+        (self.blocks[0].x, self.blocks[0].y) == self.exit
+    }
+
+    /// Work the grid of cells out from the blocks.
+    fn fill(&mut self) {
+        for row in self.cells.iter_mut() {
+            row.fill(None);
+        }
+        for (i, block) in self.blocks.iter().enumerate() {
+            for row in &mut self.cells[block.y..block.y + block.height] {
+                for cell in &mut row[block.x..block.x + block.width] {
+                    *cell = Some(i);
+                }
+            }
+        }
+    }
+}
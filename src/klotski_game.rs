@@ -0,0 +1,182 @@
+//! Klotski mode, opened with `Ctrl+K`: the classic block puzzle in place
+//! of the boards, see `sliding_puzzle::klotski`. Clicking a block picks
+//! it, then the arrows or a click on an empty cell next to it slide it.
+//! Drags and swipes slide the block they start on. `Tab` goes to the next
+//! layout, `R` starts it over and `Esc` leaves.
+
+use nannou::prelude::*;
+use sliding_puzzle::klotski::{Klotski, LAYOUTS};
+
+use crate::{board_rect, PAD_HEIGHT_FACTOR};
+
+/// Color of the big block, the others are grey.
+static BIG_BLOCK_COLOR: (f32, f32, f32) = (0.8, 0.2, 0.2);
+
+pub struct KlotskiGame {
+    layout: usize,               // Index of the layout in `LAYOUTS`
+    board: Klotski,              // Blocks where they are now
+    selected: Option<usize>,     // Block picked, the arrows slide it
+    moves: usize,                // Blocks slid by one cell so far
+    pub pressed: Option<Point2>, // Where the mouse button or the finger went down
+}
+
+impl KlotskiGame {
+    /// Game of layout `layout` of `LAYOUTS`, from the start.
+    pub fn new(layout: usize) -> Self {
+        let layout = layout % LAYOUTS.len();
+        KlotskiGame {
+            layout,
+            board: Klotski::from_layout(&LAYOUTS[layout]).expect("layouts are valid"),
+            selected: None,
+            moves: 0,
+            pressed: None,
+        }
+    }
+
+    /// Start the next layout, back to the first after the last.
+    pub fn next_layout(&mut self) {
+        *self = KlotskiGame::new(self.layout + 1);
+    }
+
+    /// Start the layout over.
+    pub fn restart(&mut self) {
+        *self = KlotskiGame::new(self.layout);
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.board.is_solved()
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// Slide the picked block one cell by `(dx, dy)`. Returns `true` if
+    /// it moved. Nothing moves once solved.
+    pub fn slide_selected(&mut self, dx: isize, dy: isize) -> bool {
+        let Some(block) = self.selected.filter(|_| !self.is_solved()) else {
+            return false;
+        };
+        let moved = self.board.try_move(block, dx, dy);
+        if moved {
+            self.moves += 1;
+        }
+        moved
+    }
+
+    /// Pick the block at `cell`, or slide the picked one into `cell` if it
+    /// is an empty cell right next to it. Returns `true` if a block moved.
+    pub fn click(&mut self, (ix, iy): (usize, usize)) -> bool {
+        if let Some(block) = self.board.block_at(ix, iy) {
+            self.selected = Some(block);
+            return false;
+        }
+        let Some(block) = self.selected.map(|b| self.board.blocks()[b]) else {
+            return false;
+        };
+        let side = |i: usize, start: usize, length: usize| match i {
+            i if i + 1 == start => Some(-1),
+            i if i == start + length => Some(1),
+            i if (start..start + length).contains(&i) => Some(0),
+            _ => None,
+        };
+        match (
+            side(ix, block.x, block.width),
+            side(iy, block.y, block.height),
+        ) {
+            (Some(dx), Some(dy)) if (dx == 0) != (dy == 0) => self.slide_selected(dx, dy),
+            _ => false,
+        }
+    }
+
+    /// Pick the block at `cell` and slide it one cell by `(dx, dy)`, eg,
+    /// for a swipe starting on it. Returns `true` if it moved.
+    pub fn push(&mut self, (ix, iy): (usize, usize), dx: isize, dy: isize) -> bool {
+        let Some(block) = self.board.block_at(ix, iy) else {
+            return false;
+        };
+        self.selected = Some(block);
+        self.slide_selected(dx, dy)
+    }
+
+    /// Cell at `point` of the board drawn in `win`, if it is on it.
+    pub fn cell_at(&self, win: Rect, point: Point2) -> Option<(usize, usize)> {
+        let size = self.board.size();
+        let board = board_rect(win, size);
+        if !board.contains(point) {
+            return None;
+        }
+        let cell_size = board.w() / size.width as f32;
+        let ix = (((point.x - board.left()) / cell_size) as usize).min(size.width - 1);
+        let iy = (((point.y - board.bottom()) / cell_size) as usize).min(size.height - 1);
+        Some((ix, iy))
+    }
+
+    /// Draw the game over the whole of `win`.
+    pub fn draw(&self, draw: &Draw, win: Rect) {
+        draw.rect().xy(win.xy()).wh(win.wh()).color(BLACK);
+        let size = self.board.size();
+        let board = board_rect(win, size);
+        let cell_size = board.w() / size.width as f32;
+        draw.rect()
+            .xy(board.xy())
+            .wh(board.wh())
+            .color(rgb(0.15, 0.15, 0.15));
+        // the exit is a gap in the frame, as wide as the big block
+        let big = self.board.blocks()[0];
+        let (exit_x, exit_y) = self.board.exit();
+        let exit = Rect::from_x_y_w_h(
+            board.left() + (exit_x as f32 + big.width as f32 / 2.0) * cell_size,
+            board.bottom() + exit_y as f32 * cell_size,
+            big.width as f32 * cell_size,
+            cell_size * 0.1,
+        );
+        draw.rect()
+            .xy(board.xy())
+            .wh(board.wh())
+            .no_fill()
+            .stroke(GRAY)
+            .stroke_weight(cell_size * 0.1);
+        draw.rect().xy(exit.xy()).wh(exit.wh()).color(BLACK);
+        let (r, g, b) = BIG_BLOCK_COLOR;
+        for (i, block) in self.board.blocks().iter().enumerate() {
+            let wh = vec2(block.width as f32, block.height as f32) * cell_size;
+            let xy =
+                board.bottom_left() + vec2(block.x as f32, block.y as f32) * cell_size + wh / 2.0;
+            let color = match i {
+                0 => rgb(r, g, b),
+                _ => rgb(0.6, 0.6, 0.6),
+            };
+            draw.rect().xy(xy).wh(wh - cell_size * 0.08).color(color);
+            if self.selected == Some(i) {
+                draw.rect()
+                    .xy(xy)
+                    .wh(wh - cell_size * 0.08)
+                    .no_fill()
+                    .stroke(YELLOW)
+                    .stroke_weight((cell_size * 0.06).max(2.0));
+            }
+        }
+        let pad = win.h() * PAD_HEIGHT_FACTOR;
+        let status = match self.is_solved() {
+            true => format!("Solved in {} moves! Tab for the next layout", self.moves),
+            false => format!(
+                "Klotski: {} - {} moves",
+                LAYOUTS[self.layout].name, self.moves
+            ),
+        };
+        draw.text(&status)
+            .font_size((pad / 3.0).max(8.0) as u32)
+            .x_y(win.x(), board.top() + pad / 2.0)
+            .w(win.w())
+            .center_justify()
+            .color(WHITE);
+        let help = "Get the red block out at the bottom. Click a block, then arrows or a click to slide it. Tab next layout, R restart, Esc leave";
+        draw.text(help)
+            .font_size((pad / 5.0).max(6.0) as u32)
+            .x_y(win.x(), board.bottom() - pad / 2.0)
+            .w(win.w())
+            .center_justify()
+            .color(GRAY);
+    }
+}
@@ -0,0 +1,638 @@
+//! Logic of the sliding puzzle, free of any frontend: the board, the moves
+//! and the scramble. The nannou game in `main.rs` is one frontend, a
+//! terminal or web one, or a solver, can use the same `Board`.
+
+use std::fmt;
+use std::ops::Index;
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub mod klotski;
+pub mod solver;
+
+/// Size of a board, in cells. Written `5x3`, width first, or just `4` for
+/// a square board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridSize {
+    pub width: usize,  // Number of columns
+    pub height: usize, // Number of rows
+}
+
+impl GridSize {
+    /// Square grid of `size` by `size`.
+    pub fn square(size: usize) -> Self {
+        GridSize {
+            width: size,
+            height: size,
+        }
+    }
+
+    /// Number of cells, the empty space included.
+    pub fn cells(self) -> usize {
+        self.width * self.height
+    }
+
+    /// Number of cells on the longest side.
+    pub fn longest(self) -> usize {
+        self.width.max(self.height)
+    }
+
+    /// Average `Board::manhattan_distance` of the positions of a board of
+    /// this size, each piece is on average `(n² - 1) / 3n` cells away from
+    /// its place along a side of `n` cells.
+    pub fn average_distance(self) -> f32 {
+        let along = |n: usize| (n * n - 1) as f32 / (3 * n) as f32;
+        (self.cells() - 1) as f32 * (along(self.width) + along(self.height))
+    }
+}
+
+impl fmt::Display for GridSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl FromStr for GridSize {
+    type Err = String;
+
+    /// Parse `5x3`, ignoring case, or `4` for a square grid.
+    fn from_str(text: &str) -> Result<Self, String> {
+        let text = text.trim().to_lowercase();
+        let parse = |side: &str| {
+            side.parse::<usize>()
+                .map_err(|_| format!("Invalid grid size '{text}'"))
+        };
+        match text.split_once('x') {
+            Some((width, height)) => Ok(GridSize {
+                width: parse(width)?,
+                height: parse(height)?,
+            }),
+            None => Ok(GridSize::square(parse(&text)?)),
+        }
+    }
+}
+
+/// Rules of the moves of a board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    #[default]
+    Sliding, // Pieces slide into the empty space next to them
+    Loopover, // No empty space, whole rows and columns shift, wrapping around the edges
+}
+
+/// Shift of a whole row or column of a board by some cells in the
+/// Loopover variant, the pieces pushed off one edge coming back on the
+/// other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shift {
+    Row(usize, isize),    // Row `iy` shifted right, left if negative
+    Column(usize, isize), // Column `ix` shifted up, down if negative
+}
+
+impl Shift {
+    /// Shift taking this one back.
+    pub fn inverse(self) -> Self {
+        match self {
+            Shift::Row(iy, by) => Shift::Row(iy, -by),
+            Shift::Column(ix, by) => Shift::Column(ix, -by),
+        }
+    }
+}
+
+/// Board of pieces numbered from 1, with 0 for the empty space. Cells are
+/// indexed `(ix, iy)`, column then row, row 0 being the bottom one. Rows
+/// can be read with `board[iy]`.
+///
+/// A board may have several empty spaces, all 0, for an easier variant:
+/// a piece next to any of them can slide into it. A board with no empty
+/// space is played with the rules of Loopover, see `Variant`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board {
+    cells: Vec<Vec<usize>>, // Pieces by row, from the bottom row
+}
+
+impl Board {
+    /// Solved board of `size`: pieces in order from the top left corner,
+    /// row by row, and the empty space in the bottom right corner.
+    pub fn solved(size: GridSize) -> Self {
+        Board::solved_with_blanks(size, 1)
+    }
+
+    /// Solved board of `size` with `blanks` empty spaces, in the last cells
+    /// of the bottom row and up from there. There is at least one piece,
+    /// and no empty space for the Loopover variant.
+    pub fn solved_with_blanks(size: GridSize, blanks: usize) -> Self {
+        let GridSize { width, height } = size;
+        let pieces = size.cells() - blanks.min(size.cells() - 1);
+        let mut cells = vec![vec![0; width]; height];
+        for (row, line) in cells.iter_mut().enumerate() {
+            for (col, cell) in line.iter_mut().enumerate() {
+                let piece = (height - row - 1) * width + col + 1;
+                *cell = if piece <= pieces { piece } else { 0 };
+            }
+        }
+        Board { cells }
+    }
+
+    /// Board of `rows` of pieces, from the bottom row. Each piece from 1 up
+    /// must be there once, the other cells empty, on rows of the same width.
+    /// With no empty cell the board is one of the Loopover variant.
+    pub fn from_rows(rows: Vec<Vec<usize>>) -> Result<Self, String> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return Err(String::from("Rows of different widths"));
+        }
+        let mut pieces: Vec<usize> = rows.iter().flatten().copied().filter(|&p| p != 0).collect();
+        pieces.sort_unstable();
+        if pieces.iter().enumerate().any(|(i, &piece)| i + 1 != piece) {
+            return Err(String::from("Pieces missing or repeated"));
+        }
+        Ok(Board { cells: rows })
+    }
+
+    /// Number of columns and rows.
+    pub fn size(&self) -> GridSize {
+        GridSize {
+            width: self.cells[0].len(),
+            height: self.cells.len(),
+        }
+    }
+
+    /// Rows of the board, from the bottom one.
+    pub fn rows(&self) -> impl Iterator<Item = &[usize]> {
+        self.cells.iter().map(|row| row.as_slice())
+    }
+
+    /// Returns `true` if every piece is in its place.
+    pub fn is_solved(&self) -> bool {
+        *self == Board::solved_with_blanks(self.size(), self.blanks())
+    }
+
+    /// Rules the board is played with, Loopover if it has no empty space.
+    pub fn variant(&self) -> Variant {
+        match self.blanks() {
+            0 => Variant::Loopover,
+            _ => Variant::Sliding,
+        }
+    }
+
+    /// Number of empty spaces, usually one.
+    pub fn blanks(&self) -> usize {
+        self.cells.iter().flatten().filter(|&&p| p == 0).count()
+    }
+
+    /// Indices of the empty spaces, from the bottom row.
+    pub fn empties(&self) -> Vec<(usize, usize)> {
+        let mut empties = vec![];
+        for (iy, row) in self.cells.iter().enumerate() {
+            for (ix, &piece) in row.iter().enumerate() {
+                if piece == 0 {
+                    empties.push((ix, iy));
+                }
+            }
+        }
+        empties
+    }
+
+    /// Empty space next to `(ix, iy)`, if any, the first one found when
+    /// there are several.
+    pub fn empty_next_to(&self, ix: usize, iy: usize) -> Option<(usize, usize)> {
+        let GridSize { width, height } = self.size();
+        let neighbours = [
+            (ix + 1 < width).then(|| (ix + 1, iy)),
+            ix.checked_sub(1).map(|x| (x, iy)),
+            (iy + 1 < height).then(|| (ix, iy + 1)),
+            iy.checked_sub(1).map(|y| (ix, y)),
+        ];
+        neighbours
+            .into_iter()
+            .flatten()
+            .find(|&(x, y)| self.cells[y][x] == 0)
+    }
+
+    /// Returns `true` if the piece at `(ix, iy)` is in its place. The empty
+    /// space is never in place, it is no piece.
+    pub fn is_piece_correct(&self, ix: usize, iy: usize) -> bool {
+        let GridSize { width, height } = self.size();
+        let piece = self.cells[iy][ix];
+        piece != 0 && piece == (height - iy - 1) * width + ix + 1
+    }
+
+    /// Returns the indices of the empty space, the first one from the
+    /// bottom row when there are several.
+    pub fn index_empty(&self) -> (usize, usize) {
+        self.index_of(0)
+    }
+
+    /// Returns the indices of `piece`.
+    pub fn index_of(&self, piece: usize) -> (usize, usize) {
+        let iy = self.cells.iter().position(|r| r.contains(&piece)).unwrap();
+        let ix = self.cells[iy].iter().position(|&x| x == piece).unwrap();
+        (ix, iy)
+    }
+
+    /// Sum of the Manhattan distances of the pieces to their place, a lower
+    /// bound of the moves left to solve the board.
+    pub fn manhattan_distance(&self) -> usize {
+        let GridSize { width, height } = self.size();
+        let mut distance = 0;
+        for (iy, row) in self.rows().enumerate() {
+            for (ix, &piece) in row.iter().enumerate().filter(|(_, &p)| p != 0) {
+                let goal_x = (piece - 1) % width;
+                let goal_y = height - 1 - (piece - 1) / width;
+                distance += ix.abs_diff(goal_x) + iy.abs_diff(goal_y);
+            }
+        }
+        distance
+    }
+
+    /// Returns `true` if the piece at `(ix, iy)` is next to the empty space
+    /// and can slide into it.
+    pub fn is_move_valid(&self, ix: usize, iy: usize) -> bool {
+        self.cells[iy][ix] != 0 && self.empty_next_to(ix, iy).is_some()
+    }
+
+    /// Slide the piece at `(ix, iy)` into the empty space. Returns the piece
+    /// moved, or `None` if the move is not valid.
+    pub fn try_move(&mut self, ix: usize, iy: usize) -> Option<usize> {
+        let to = self.empty_next_to(ix, iy)?;
+        self.try_move_to((ix, iy), to)
+    }
+
+    /// Slide the piece at `from` into the empty space at `to`, which must be
+    /// next to it. Returns the piece moved, or `None` if the move is not
+    /// valid.
+    pub fn try_move_to(&mut self, from: (usize, usize), to: (usize, usize)) -> Option<usize> {
+        let ((ix, iy), (empty_x, empty_y)) = (from, to);
+        let piece = self.cells[iy][ix];
+        if piece == 0
+            || self.cells[empty_y][empty_x] != 0
+            || ix.abs_diff(empty_x) + iy.abs_diff(empty_y) != 1
+        {
+            return None;
+        }
+        self.cells[empty_y][empty_x] = piece;
+        self.cells[iy][ix] = 0;
+        Some(piece)
+    }
+
+    /// Swap the pieces of cells `a` and `b`, `(ix, iy)`, whatever the rule
+    /// of sliding, eg, to set a position up. The board may not be solvable
+    /// after, see `is_solvable`.
+    pub fn swap(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let piece = self.cells[a.1][a.0];
+        self.cells[a.1][a.0] = self.cells[b.1][b.0];
+        self.cells[b.1][b.0] = piece;
+    }
+
+    /// Shift a row or a column of the board, see `Shift`. Shifts of cells
+    /// off the board are ignored.
+    pub fn shift(&mut self, shift: Shift) {
+        let GridSize { width, height } = self.size();
+        match shift {
+            Shift::Row(iy, by) if iy < height => {
+                self.cells[iy].rotate_right(by.rem_euclid(width as isize) as usize);
+            }
+            Shift::Column(ix, by) if ix < width => {
+                let mut column: Vec<usize> = self.cells.iter().map(|row| row[ix]).collect();
+                // rows go up from the bottom, so up is to the right
+                column.rotate_right(by.rem_euclid(height as isize) as usize);
+                for (row, piece) in self.cells.iter_mut().zip(column) {
+                    row[ix] = piece;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Make one random shift of a row or column by one cell with `rng`, in
+    /// the Loopover variant. Returns the shift made.
+    pub fn random_shift(&mut self, rng: &mut impl Rng) -> Shift {
+        let GridSize { width, height } = self.size();
+        let by = if rng.gen() { 1 } else { -1 };
+        let shift = match rng.gen_range(0..width + height) {
+            iy if iy < height => Shift::Row(iy, by),
+            i => Shift::Column(i - height, by),
+        };
+        self.shift(shift);
+        shift
+    }
+
+    /// Make one random valid move with `rng`, returns the piece moved. The
+    /// board must have an empty space.
+    pub fn random_move(&mut self, rng: &mut impl Rng) -> usize {
+        // pick cells at random until one can move
+        loop {
+            let ix = rng.gen_range(0..self.size().width);
+            let iy = rng.gen_range(0..self.size().height);
+            if let Some(piece) = self.try_move(ix, iy) {
+                return piece;
+            }
+        }
+    }
+
+    /// Scramble the board with `moves` random moves, returns the pieces
+    /// moved in order.
+    pub fn scramble(&mut self, moves: usize, rng: &mut impl Rng) -> Vec<usize> {
+        (0..moves).map(|_| self.random_move(rng)).collect()
+    }
+
+    /// Scramble the board at once into a uniformly random solvable
+    /// position, other than the solved one. Boards of a single row or
+    /// column can't be scrambled, their pieces can't pass each other.
+    pub fn scramble_random(&mut self, rng: &mut impl Rng) {
+        let size = self.size();
+        if size.width < 2 || size.height < 2 {
+            return;
+        }
+        let blanks = self.blanks();
+        let mut pieces: Vec<usize> = (1..=size.cells() - blanks).collect();
+        pieces.resize(size.cells(), 0);
+        loop {
+            pieces.shuffle(rng);
+            self.fill(&pieces);
+            if !self.is_solvable() {
+                // swapping two pieces flips the parity of the permutation,
+                // and the solvable positions are as likely as before
+                match pieces.iter().position(|&p| p == 0) {
+                    Some(0 | 1) => pieces.swap(2, 3),
+                    _ => pieces.swap(0, 1),
+                }
+                self.fill(&pieces);
+            }
+            if !self.is_solved() {
+                return;
+            }
+        }
+    }
+
+    /// Number of inversions of the board: pairs of pieces in the wrong
+    /// order, reading the board row by row from the top left and leaving
+    /// the empty space out. Sliding a piece sideways never changes it.
+    pub fn inversions(&self) -> usize {
+        let pieces: Vec<usize> = self
+            .cells
+            .iter()
+            .rev()
+            .flatten()
+            .copied()
+            .filter(|&p| p != 0)
+            .collect();
+        (0..pieces.len())
+            .map(|i| pieces[i + 1..].iter().filter(|&&p| p < pieces[i]).count())
+            .sum()
+    }
+
+    /// Returns `true` if the board can be solved with moves. Half of the
+    /// positions can't, depending on the parity of the inversions. With an
+    /// odd width, sliding a piece up or down moves it past an even number
+    /// of pieces, so the parity never changes. With an even width it
+    /// changes with each row the empty space moves, so the row of the empty
+    /// space counts too. The height plays no part, this holds for
+    /// rectangular boards as for square ones. With several empty spaces any
+    /// position can be solved, two of them let any two pieces swap.
+    ///
+    /// Without an empty space, in the Loopover variant, shifting a row of
+    /// even width by one is an odd permutation, so any position can be
+    /// solved if a side is even. With both sides odd every shift is an even
+    /// permutation and only half of the positions can be solved.
+    pub fn is_solvable(&self) -> bool {
+        let GridSize { width, height } = self.size();
+        match self.blanks() {
+            0 if width.is_multiple_of(2) || height.is_multiple_of(2) => return true,
+            0 => return self.inversions().is_multiple_of(2),
+            1 => (),
+            _ => return true,
+        }
+        let inversions = self.inversions();
+        if !width.is_multiple_of(2) {
+            return inversions.is_multiple_of(2);
+        }
+        let (_ix, iy) = self.index_empty();
+        // rows counted from 1 at the bottom
+        !(inversions + iy + 1).is_multiple_of(2)
+    }
+
+    /// Lay `pieces` out on the board row by row from the top left.
+    fn fill(&mut self, pieces: &[usize]) {
+        let GridSize { width, height } = self.size();
+        for (i, &piece) in pieces.iter().enumerate() {
+            self.cells[height - 1 - i / width][i % width] = piece;
+        }
+    }
+}
+
+/// Board written as its size and its rows from the top, eg,
+/// `3x3:4,1,3/7,2,5/8,0,6`, pieces numbered from 1 and 0 for the empty
+/// space.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.size())?;
+        for (i, row) in self.cells.iter().rev().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            let pieces: Vec<String> = row.iter().map(usize::to_string).collect();
+            write!(f, "{}", pieces.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Board {
+    type Err = String;
+
+    /// Parse a board as written by `Display`. The size in front may be left
+    /// out, it is then taken from the rows.
+    fn from_str(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (size, rows) = match text.split_once(':') {
+            Some((size, rows)) => (Some(size.parse::<GridSize>()?), rows),
+            None => (None, text),
+        };
+        let mut cells = rows
+            .split('/')
+            .map(|row| {
+                row.split(',')
+                    .map(|piece| {
+                        piece
+                            .trim()
+                            .parse::<usize>()
+                            .map_err(|_| format!("Invalid piece '{}'", piece.trim()))
+                    })
+                    .collect::<Result<Vec<usize>, String>>()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        cells.reverse();
+        let board = Board::from_rows(cells)?;
+        match size {
+            Some(size) if size != board.size() => {
+                Err(format!("Rows make a {} board, not {size}", board.size()))
+            }
+            _ => Ok(board),
+        }
+    }
+}
+
+impl Index<usize> for Board {
+    type Output = [usize];
+
+    /// Row `iy` of the board, row 0 being the bottom one.
+    fn index(&self, iy: usize) -> &[usize] {
+        &self.cells[iy]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Board of `rows` written from the top row, as it is drawn.
+    fn board(rows: &[&[usize]]) -> Board {
+        Board {
+            cells: rows.iter().rev().map(|row| row.to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn solved_board() {
+        let solved = Board::solved(GridSize::square(3));
+        assert_eq!(solved, board(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 0]]));
+        assert!(solved.is_solved());
+        assert_eq!(solved.index_empty(), (2, 0));
+        assert_eq!(solved.index_of(1), (0, 2));
+        assert!(solved.is_piece_correct(0, 0));
+        assert!(!solved.is_piece_correct(2, 0));
+    }
+
+    #[test]
+    fn only_pieces_next_to_the_empty_space_move() {
+        let mut board = Board::solved(GridSize::square(3));
+        assert_eq!(board.try_move(0, 0), None);
+        assert_eq!(board.try_move(1, 1), None);
+        assert_eq!(board.try_move(2, 0), None);
+        assert_eq!(board.try_move(2, 1), Some(6));
+        assert_eq!(board.index_empty(), (2, 1));
+        assert!(!board.is_solved());
+        assert_eq!(board.try_move(2, 0), Some(6));
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn grid_size_from_str() {
+        let size = |width, height| GridSize { width, height };
+        assert_eq!("5x3".parse(), Ok(size(5, 3)));
+        assert_eq!(" 4X6 ".parse(), Ok(size(4, 6)));
+        assert_eq!("4".parse(), Ok(GridSize::square(4)));
+        for text in ["", "x", "4x", "x4", "4x4x4", "-3", "four", "3 x 3"] {
+            assert!(text.parse::<GridSize>().is_err(), "'{text}' parsed");
+        }
+    }
+
+    #[test]
+    fn from_rows_errors() {
+        assert!(Board::from_rows(vec![]).is_err());
+        assert!(Board::from_rows(vec![vec![]]).is_err());
+        assert!(Board::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+        // missing, repeated and out of range pieces
+        assert!(Board::from_rows(vec![vec![1, 0], vec![0, 3]]).is_err());
+        assert!(Board::from_rows(vec![vec![1, 1], vec![2, 0]]).is_err());
+        assert!(Board::from_rows(vec![vec![1, 2], vec![5, 0]]).is_err());
+        assert!(Board::from_rows(vec![vec![3, 0], vec![1, 2]]).is_ok());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for text in [
+            "3x3:4,1,3/7,2,5/8,0,6",
+            "4x2:1,2,3,4/5,6,7,0",
+            "2x3:1,2/3,0/4,0",
+            "3x2:2,3,1/4,5,6",
+        ] {
+            assert_eq!(text.parse::<Board>().unwrap().to_string(), text);
+        }
+        let solved: Board = "1,2/3,0".parse().unwrap();
+        assert_eq!(solved.to_string(), "2x2:1,2/3,0");
+        assert_eq!(solved, Board::solved(GridSize::square(2)));
+        assert!("3x3:1,2/3,0".parse::<Board>().is_err());
+        assert!("2x2:1,2/3,x".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn inversions_leave_the_empty_space_out() {
+        assert_eq!(Board::solved(GridSize::square(4)).inversions(), 0);
+        assert_eq!(board(&[&[2, 1, 3], &[4, 5, 6], &[7, 0, 8]]).inversions(), 1);
+        assert_eq!(board(&[&[0, 3, 2], &[1, 5, 4]]).inversions(), 4);
+    }
+
+    #[test]
+    fn is_solvable_odd_width() {
+        assert!(board(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 0]]).is_solvable());
+        assert!(board(&[&[1, 2, 3], &[4, 5, 6], &[7, 0, 8]]).is_solvable());
+        assert!(board(&[&[4, 1, 3], &[7, 2, 5], &[8, 0, 6]]).is_solvable());
+        assert!(!board(&[&[1, 2, 3], &[4, 5, 6], &[8, 7, 0]]).is_solvable());
+        assert!(!board(&[&[2, 1, 3], &[4, 5, 6], &[7, 0, 8]]).is_solvable());
+        assert!(board(&[&[1, 2, 3], &[4, 0, 5]]).is_solvable());
+        assert!(!board(&[&[2, 1, 3], &[4, 0, 5]]).is_solvable());
+    }
+
+    #[test]
+    fn is_solvable_even_width() {
+        let top = [1, 2, 3, 4];
+        let middle = [5, 6, 7, 8];
+        assert!(board(&[&top, &middle, &[9, 10, 11, 12], &[13, 14, 15, 0]]).is_solvable());
+        assert!(board(&[&top, &middle, &[9, 10, 11, 0], &[13, 14, 15, 12]]).is_solvable());
+        assert!(!board(&[&top, &middle, &[9, 10, 11, 12], &[13, 15, 14, 0]]).is_solvable());
+        assert!(!board(&[&top, &middle, &[9, 10, 11, 0], &[13, 15, 14, 12]]).is_solvable());
+        // the pieces in order with the empty space a row up
+        assert!(!board(&[&top, &middle, &[9, 10, 11, 0], &[12, 13, 14, 15]]).is_solvable());
+        assert!(board(&[&top, &[5, 6, 7, 0]]).is_solvable());
+        assert!(!board(&[&top, &[5, 7, 6, 0]]).is_solvable());
+    }
+
+    #[test]
+    fn moves_keep_the_board_solvable() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut board = Board::solved(GridSize::square(4));
+        for _ in 0..1000 {
+            board.random_move(&mut rng);
+            assert!(board.is_solvable(), "{board:?}");
+        }
+    }
+
+    #[test]
+    fn scramble_random_is_solvable_and_not_solved() {
+        for size in ["2", "3", "4", "5", "3x2", "2x4", "5x3"] {
+            let size: GridSize = size.parse().unwrap();
+            for seed in 0..50 {
+                let mut board = Board::solved(size);
+                board.scramble_random(&mut StdRng::seed_from_u64(seed));
+                assert!(board.is_solvable(), "{board:?}");
+                assert!(!board.is_solved(), "{board:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn several_empty_spaces() {
+        let solved = Board::solved_with_blanks(GridSize::square(3), 2);
+        assert_eq!(solved, board(&[&[1, 2, 3], &[4, 5, 6], &[7, 0, 0]]));
+        assert!(solved.is_solved());
+        assert_eq!(solved.blanks(), 2);
+        // swapping two pieces with an empty space to spare can be undone
+        assert!(board(&[&[2, 1, 3], &[4, 5, 6], &[7, 0, 0]]).is_solvable());
+    }
+
+    #[test]
+    fn is_solvable_without_an_empty_space() {
+        assert!(Board::solved_with_blanks(GridSize::square(3), 0).is_solved());
+        // shifting a line of even length is an odd permutation
+        assert!(board(&[&[2, 1], &[3, 4]]).is_solvable());
+        assert!(board(&[&[1, 2, 3], &[4, 5, 6], &[9, 7, 8]]).is_solvable());
+        assert!(!board(&[&[1, 2, 3], &[4, 5, 6], &[7, 9, 8]]).is_solvable());
+    }
+}
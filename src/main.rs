@@ -1,12 +1,74 @@
-use nannou::image::GenericImage;
-use nannou::image::{self, GenericImageView};
+use nannou::image;
 use nannou::prelude::*;
 use nannou::prelude::{wgpu, App, Frame, Key, LoopMode, MousePressed, Update, WindowEvent};
 
-use std::{env, fs, path::PathBuf, thread, time};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Instant;
+use std::{fs, thread, time};
 
 use env_logger::Builder;
 use log::debug;
+use nannou::rand::rand::{rngs::StdRng, SeedableRng};
+use nannou::winit::window::{CursorIcon, Fullscreen};
+use numbering::Numbering;
+use palette::Action;
+use puzzle::{Puzzle, Stage};
+use sliding_puzzle::{GridSize, Shift, Variant};
+use state::{Editor, GameState, Menu, Row};
+use window_state::WindowState;
+
+mod artwork;
+mod attract;
+mod autosolve;
+mod challenge;
+mod cli;
+mod clipboard;
+mod clock;
+mod config;
+mod crop;
+#[cfg(feature = "daily")]
+mod daily;
+mod exif;
+mod export;
+mod gallery;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod history;
+mod images;
+mod jigsaw;
+mod keys;
+mod klotski_game;
+mod numbering;
+mod packs;
+mod palette;
+mod panel;
+mod pieces;
+mod procedural;
+mod profile;
+mod puzzle;
+#[cfg(feature = "record")]
+mod recorder;
+mod records;
+mod render;
+mod replay;
+mod save;
+#[cfg(feature = "settings")]
+mod settings;
+mod sheet;
+#[cfg(feature = "sound")]
+mod sound;
+mod state;
+mod stats;
+#[cfg(feature = "tray")]
+mod tray;
+#[cfg(feature = "twitch")]
+mod twitch;
+#[cfg(any(feature = "daily", feature = "url"))]
+mod web;
+#[cfg(feature = "webcam")]
+mod webcam;
+mod window_state;
 
 /// Initial window size, window is square.
 /// User can resize to non-square size, in which
@@ -17,146 +79,970 @@ static START_WINDOW_SIZE: u32 = 300;
 /// of the window height.
 static PAD_HEIGHT_FACTOR: f32 = 0.1;
 
-/// Build a solved board with numbers up to height * width - 1
-fn solved_board(size: usize) -> Vec<Vec<usize>> {
-    let mut board = vec![vec![0; size]; size];
-    for row in 0..size {
-        for col in 0..size {
-            board[row][col] = (size - row - 1) * size + col + 1;
-        }
-    }
-    board[0][size - 1] = 0;
-    board
-}
+/// Number of puzzles shown side by side, cycled with `L`.
+static LAYOUTS: [usize; 3] = [1, 2, 4];
+
+/// Zoom factor of one step of the mouse wheel with `Ctrl`.
+static ZOOM_STEP: f32 = 1.15;
+
+/// Largest zoom of the boards.
+static MAX_ZOOM: f32 = 8.0;
+
+/// Largest grid size, with `+` or on the command line.
+static MAX_GRID_SIZE: usize = 20;
+
+/// Folder in the profile where screenshots taken with `F12` are saved.
+static SCREENSHOTS_DIR: &str = "screenshots";
+
+/// Folder in the profile where the solve history is exported with `E`.
+static EXPORT_DIR: &str = "export";
+
+/// Folder in the profile where the scrambled pictures are saved with `I`.
+static PICTURES_DIR: &str = "pictures";
+
+/// Folder in the profile where the printable sheets are saved with `W`.
+static SHEETS_DIR: &str = "sheets";
+
+/// Collection of the image files dropped on the window.
+static DROPPED_COLLECTION: &str = "Dropped files";
+
+/// Collection of the snapshots of the webcam taken with `C`.
+#[cfg(feature = "webcam")]
+static WEBCAM_COLLECTION: &str = "Webcam";
+
+/// Collection of the images opened from a URL.
+#[cfg(feature = "url")]
+static DOWNLOADS_COLLECTION: &str = "Downloads";
+
+/// Folder in the profile where the images opened from a URL are kept.
+#[cfg(feature = "url")]
+static DOWNLOADS_DIR: &str = "downloads";
+
+/// Size of the thumbnail of the target image, as a fraction of the board.
+static THUMBNAIL_FACTOR: f32 = 0.2;
+
+/// Time the window must stay the same size before the images are resized
+/// to it, the pieces are stretched until then.
+static RESIZE_SETTLE: time::Duration = time::Duration::from_millis(300);
+
+/// Distance the mouse moves before a click becomes a drag, in points.
+static DRAG_THRESHOLD: f32 = 4.0;
+
+/// Distance a finger moves for a swipe rather than a tap, in points.
+static SWIPE_DISTANCE: f32 = 30.0;
+
+/// Window title when not solving.
+static TITLE: &str = "Sliding Puzzle";
+
+/// Size of the window icon made from the puzzle image.
+static ICON_SIZE: u32 = 64;
+
+/// How long a toast message stays on screen.
+static TOAST_DURATION: time::Duration = time::Duration::from_millis(1500);
 
 struct Model {
-    grid_size: usize,                    // Size of the square grid of the board
-    flag_scramble: bool,                 // Flag to indicate if the board is being scrambled
-    flag_show_numbers: bool,             // Flag to indicate if the numbers should be shown
-    scramble_count: usize,               // Number of times the board has been scrambled
-    board: Vec<Vec<usize>>,              // The board itself
-    image_list: Vec<PathBuf>,            // List of images to use
-    image_index_current: usize,          // Index of the current image
-    image_original: image::DynamicImage, // Original image
-    image_solved: image::DynamicImage,   // Resized image and cut square
-    image: image::DynamicImage,          // Game display, ie, scrambled image
-    texture: wgpu::Texture,              // Texture to display the image
+    flag_show_numbers: bool, // Flag to indicate if the numbers should be shown
+    puzzles: Vec<Puzzle>,    // Puzzles shown side by side, see `LAYOUTS`
+    focused: usize,          // Index of the puzzle played with the keyboard
+
+    toast: Option<(String, Instant)>, // Short message shown on top of the board
+    screenshot_pending: Option<(PathBuf, u64)>, // Path and frame of a pending screenshot
+    collections: Vec<packs::Collection>, // Images folder followed by the imported packs
+    window_id: window::Id,            // The puzzle window, not necessarily the focused one
+    qr_window: Option<window::Id>,    // Popup showing the challenge as a QR code
+    icon_image: Option<PathBuf>,      // Image the window icon was made from
+    config: config::Config,           // User settings
+    title: String,                    // Current window title
+    flag_always_on_top: bool,         // Flag to indicate the window stays above the others
+    flag_borderless: bool,            // Flag to indicate the window has no frame
+    window_state: WindowState,        // Monitor and fullscreen mode, saved across runs
+    scale_factor: f32,                // Pixels per point the images were sized for
+    resize_at: Option<Instant>, // Time to resize the images, once the window stops being resized
+    last_input: Instant,        // Time of the last key press or mouse input
+    attract: Option<attract::Attract>, // Demo shown after a while without input
+    panel: Option<panel::Panel>, // Control panel window, opened with `F2`
+    palette: Option<palette::Palette>, // Command palette, opened with `Ctrl+P`
+    numbering: Numbering,       // Style of the labels on the pieces
+    flag_outline: bool,         // Flag to indicate the pieces are outlined in green or red
+    flag_thumbnail: bool,       // Flag to indicate the target image is shown in a corner
+    flag_focus_paused: bool,    // Flag to indicate the timers wait for the focus back
+    particles: render::Particles, // Confetti and dust flying over the boards
+    gallery: Option<gallery::Gallery>, // Thumbnails of the images, opened with `G`
+    stats: Option<stats::Stats>, // Stats of the solve history, opened with `F3`
+    gif_export: Option<mpsc::Receiver<std::io::Result<PathBuf>>>, // GIF of a replay being encoded
+    zoom: f32,                  // Zoom of the boards, 1 to fit the window
+    view_offset: Vec2,          // Pan of the zoomed boards, in points
+    pan_from: Option<(Point2, Vec2)>, // Mouse position and pan when the middle button went down
+    state: GameState,           // Menu, scrambling, playing or just solved
+    peek: Option<render::Peek>, // Solved image shown while `P` is held
+    drag: Option<render::Drag>, // Piece being dragged with the mouse
+    touch: Option<(u64, Point2)>, // Finger on the screen and where it went down
+    rejected: Option<(usize, (usize, usize), Instant)>, // Board, cell and time of the last move which couldn't be made
+    autosolve: Option<autosolve::AutoSolve>,            // Solution searched or played with `A`
+    rng: StdRng,        // Generator of the scrambles, seeded with `--seed`
+    keys: keys::KeyMap, // Keys of the actions, from the config
+    #[cfg(feature = "twitch")]
+    twitch: Option<twitch::Chat>, // Chat connection in Twitch-plays mode
+    #[cfg(feature = "record")]
+    recorder: Option<recorder::Recorder>, // Recording in progress
+    #[cfg(feature = "record")]
+    encoding: Option<mpsc::Receiver<std::io::Result<PathBuf>>>, // Recording being encoded
+    #[cfg(feature = "tray")]
+    tray: Option<tray::Tray>, // System tray icon, made when first minimized
+    #[cfg(feature = "daily")]
+    daily: Option<mpsc::Receiver<Result<PathBuf, String>>>, // Image of the day being fetched
+    images_watch: mpsc::Receiver<Vec<PathBuf>>, // Images of the images folder, when they change
+    #[cfg(feature = "webcam")]
+    webcam: Option<mpsc::Receiver<Result<PathBuf, String>>>, // Snapshot of the webcam being taken
+    #[cfg(feature = "url")]
+    download: Option<(mpsc::Receiver<Result<PathBuf, String>>, bool)>, // Image being downloaded from a URL, and whether it's a surprise
+    #[cfg(feature = "settings")]
+    settings: settings::Settings, // Settings overlay, opened with `O`
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<gamepad::Gamepads>, // Game controllers, if they can be read
+    #[cfg(feature = "sound")]
+    sound: Option<sound::Sound>, // Sound effects, if there is an output device
 }
 
 impl Model {
-    /// Reset board
-    fn reset(&mut self) {
-        self.board = solved_board(self.grid_size);
+    /// The puzzle played with the keyboard.
+    fn puzzle(&self) -> &Puzzle {
+        &self.puzzles[self.focused]
     }
 
-    /// Returns the indices of the empty space.
-    fn index_empty(&self) -> (usize, usize) {
-        let iy = self.board.iter().position(|r| r.contains(&0)).unwrap();
-        let ix = self.board[iy].iter().position(|&x| x == 0).unwrap();
-        (ix, iy)
+    fn puzzle_mut(&mut self) -> &mut Puzzle {
+        &mut self.puzzles[self.focused]
     }
 
-    /// When the user clicks on a piece, this function checks
-    /// if that piece can be moved and returns `true` if the piece
-    // can be moved, and `false` otherwise.
-    fn is_move_valid(&self, ix: usize, iy: usize) -> bool {
-        let (empty_x, empty_y) = self.index_empty();
-        ix.abs_diff(empty_x) + iy.abs_diff(empty_y) == 1
+    /// Scramble the puzzle played with the keyboard, animated or at once
+    /// as set in the config.
+    fn scramble(&mut self) {
+        let animated = self.config.animated_scramble;
+        self.puzzles[self.focused].start_scramble(animated, &mut self.rng);
     }
 
-    /// Move the piece at `(ix, iy)` to the empty space.
-    /// Check if the move is valid.
-    fn try_move(&mut self, ix: usize, iy: usize) {
-        debug!("Trying to move piece at index {ix}, {iy}");
-        match self.is_move_valid(ix, iy) {
-            true => {
-                debug!("Move is valid");
-                let (empty_x, empty_y) = self.index_empty();
-                self.board[empty_y][empty_x] = self.board[iy][ix];
-                self.board[iy][ix] = 0;
+    /// Save the focused puzzle, over the game saved before.
+    fn save_game(&mut self) {
+        match save::save(&self.puzzle().saved_game()) {
+            Ok(_) => self.show_toast(String::from("Game saved")),
+            Err(e) => {
+                println!("Error saving the game: {e}");
+                self.show_toast(String::from("Saving failed"));
+            }
+        }
+    }
+
+    /// Load the saved game into the focused puzzle, with its image if it
+    /// is still in one of the collections.
+    fn load_game(&mut self) {
+        let game = match save::load() {
+            Ok(game) => game,
+            Err(e) => {
+                println!("Error loading the game: {e}");
+                self.show_toast(String::from("No saved game"));
+                return;
+            }
+        };
+        self.autosolve = None;
+        self.show_image_file(game.image.as_deref());
+        match self.puzzles[self.focused].restore_game(game) {
+            Ok(()) => self.show_toast(String::from("Game loaded")),
+            Err(e) => {
+                println!("Error loading the game: {e}");
+                self.show_toast(String::from("Saved game is damaged"));
+            }
+        }
+    }
+
+    /// Show image `path` on the focused puzzle, if it is in one of the
+    /// collections. Otherwise the image is left as it is.
+    fn show_image_file(&mut self, path: Option<&Path>) {
+        let image = path.and_then(|path| {
+            self.collections
+                .iter()
+                .enumerate()
+                .find_map(|(c, collection)| {
+                    let index = collection.images.iter().position(|p| p == path)?;
+                    Some((c, index))
+                })
+        });
+        let focused = self.focused;
+        let puzzle = &mut self.puzzles[focused];
+        if let Some((collection, index)) = image {
+            if collection != puzzle.collection_current {
+                puzzle.set_collection(&self.collections[collection], collection);
+            }
+            puzzle.set_image(index);
+        }
+    }
+
+    /// Copy the position of the focused puzzle to the clipboard, in the
+    /// notation of `Board`.
+    fn copy_position(&mut self) {
+        let position = self.puzzle().board.to_string();
+        match clipboard::copy(&position) {
+            Ok(()) => self.show_toast(format!("Copied {position}")),
+            Err(e) => {
+                println!("Error copying the position: {e}");
+                self.show_toast(String::from("Copying failed"));
+            }
+        }
+    }
+
+    /// Start a solve of the focused puzzle from the position on the
+    /// clipboard, in the notation of `Board`.
+    fn paste_position(&mut self) {
+        let board = clipboard::paste()
+            .map_err(|e| e.to_string())
+            .and_then(|text| cli::parse_position(&text));
+        match board {
+            Ok(board) => {
+                self.autosolve = None;
+                self.puzzle_mut().start_from_position(board);
+                self.show_toast(String::from("Position pasted"));
             }
-            false => {
-                debug!("Move is invalid");
-                ()
+            Err(e) => {
+                println!("Error pasting the position: {e}");
+                self.show_toast(format!("No position pasted: {e}"));
             }
         }
     }
 
-    /// Randomly clicking everywhere until a valid move is found
-    fn do_one_random_move(&mut self) {
-        loop {
-            let ix = random_range(0, self.grid_size);
-            let iy = random_range(0, self.grid_size);
-            if self.is_move_valid(ix, iy) {
-                self.try_move(ix, iy);
+    /// Play `replay` back on the focused puzzle, from its scrambled board.
+    fn start_playback(&mut self, replay: replay::Replay) {
+        self.autosolve = None;
+        self.show_image_file(replay.image.as_deref());
+        if let Err(e) = self.puzzles[self.focused].start_replay(&replay) {
+            println!("Error playing the replay back: {e}");
+            self.show_toast(String::from("Replay is damaged"));
+            return;
+        }
+        self.state = GameState::Replaying(replay::Playback::new(self.focused, replay));
+    }
+
+    /// Save `replay` as an animated GIF, encoded in the background, and
+    /// play it back on the focused puzzle meanwhile.
+    fn export_replay_gif(&mut self, replay: replay::Replay) {
+        self.autosolve = None;
+        self.show_image_file(replay.image.as_deref());
+        let numbers = self.flag_show_numbers;
+        let puzzle = &mut self.puzzles[self.focused];
+        let frames = match puzzle.replay_frames(&replay, replay::GIF_SIZE, numbers) {
+            Ok(frames) => frames,
+            Err(e) => {
+                println!("Error playing the replay back: {e}");
+                self.show_toast(String::from("Replay is damaged"));
                 return;
             }
+        };
+        let path = replay::Replay::gif_path();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(replay::encode_gif(frames, &path).map(|()| path));
+        });
+        self.gif_export = Some(receiver);
+        self.show_toast(String::from("Making the GIF..."));
+        self.state = GameState::Replaying(replay::Playback::new(self.focused, replay));
+    }
+
+    /// Start solving the focused puzzle automatically, or stop if it is
+    /// already being solved.
+    fn toggle_autosolve(&mut self) {
+        if self.autosolve.take().is_some() {
+            self.show_toast(String::from("Auto-solve stopped"));
+            return;
+        }
+        if self.puzzle().stage == Stage::Scrambling {
+            self.show_toast(String::from("Wait for the scramble to end"));
+            return;
+        }
+        if self.puzzle().board.is_solved() {
+            self.show_toast(String::from("Already solved"));
+            return;
+        }
+        let board = &self.puzzle().board;
+        self.autosolve = Some(autosolve::AutoSolve::new(self.focused, board));
+        self.show_toast(String::from("Solving..."));
+    }
+
+    /// Move auto-solve on by a step: let the player know how the search
+    /// goes, or play the next move of the solution.
+    fn autosolve_step(&mut self) {
+        let Some(autosolve) = self.autosolve.as_mut() else {
+            return;
+        };
+        let index = autosolve.board;
+        // a board scrambled again is not the one being solved any more
+        let Some(puzzle) = self
+            .puzzles
+            .get_mut(index)
+            .filter(|puzzle| puzzle.stage != Stage::Scrambling)
+        else {
+            self.autosolve = None;
+            return;
+        };
+        match autosolve.step(&puzzle.board) {
+            autosolve::Step::Searching(bound) => {
+                let message = format!("Solving, at least {bound} moves... A to stop");
+                if self
+                    .toast
+                    .as_ref()
+                    .is_none_or(|(shown, _)| *shown != message)
+                {
+                    self.show_toast(message);
+                }
+            }
+            autosolve::Step::Move(piece) => {
+                // the solve is the computer's, it is not recorded
+                if puzzle.stage == Stage::Solving {
+                    puzzle.give_up();
+                }
+                let (ix, iy) = puzzle.index_of(piece);
+                puzzle.try_move(ix, iy);
+            }
+            autosolve::Step::Done(moves) => {
+                self.autosolve = None;
+                self.show_toast(format!("Solved automatically in {moves} moves"));
+            }
+            autosolve::Step::Failed => {
+                self.autosolve = None;
+                self.show_toast(String::from("Auto-solve found no solution"));
+            }
+        }
+    }
+
+    /// Move the piece at `(ix, iy)` of puzzle `index` and let the
+    /// player know if that solved it.
+    fn play_move(&mut self, index: usize, ix: usize, iy: usize) {
+        // moves made during the scramble would be mixed into it
+        if self.puzzles[index].stage == Stage::Scrambling {
+            return;
+        }
+        if !self.puzzles[index].is_move_valid(ix, iy) {
+            self.reject_move(index, ix, iy);
+            return;
+        }
+        #[cfg(feature = "sound")]
+        self.play_sound(sound::Effect::Slide);
+        if let Some(solve) = self.puzzles[index].play_move(ix, iy) {
+            self.solved(index, solve);
+        }
+    }
+
+    /// Celebrate `solve` of puzzle `index`.
+    fn solved(&mut self, index: usize, solve: history::Solve) {
+        if self.config.confetti {
+            self.particles.confetti(index);
+        }
+        #[cfg(feature = "sound")]
+        self.play_sound(sound::Effect::Solved);
+        let mut records = records::Records::load();
+        let outcome = records.update(&solve);
+        if outcome.faster || outcome.fewer_moves {
+            records.save();
+        }
+        self.state = GameState::Solved(render::Victory {
+            board: index,
+            moves: solve.moves,
+            seconds: solve.seconds,
+            outcome,
+            texture: None,
+        });
+        let mut message = format!(
+            "Solved in {} moves, {}",
+            solve.moves,
+            clock::format_duration(solve.seconds)
+        );
+        if solve.hint_cost > 0 {
+            message.push_str(&format!(", hints {}", solve.hint_cost));
+        }
+        self.show_toast(message);
+        if let Some(panel) = self.panel.as_mut() {
+            panel.solves.push(solve);
+        }
+    }
+
+    /// Zoom the boards in by `steps`, or out if negative, keeping the point
+    /// under `point` in place.
+    fn zoom_at(&mut self, point: Point2, steps: f32) {
+        let zoom = (self.zoom * ZOOM_STEP.powf(steps)).clamp(1.0, MAX_ZOOM);
+        let board_point = (point - self.view_offset) / self.zoom;
+        self.view_offset = match zoom == 1.0 {
+            true => Vec2::ZERO,
+            false => point - board_point * zoom,
+        };
+        self.zoom = zoom;
+    }
+
+    /// Slide all the pieces between the empty space and `(ix, iy)` of
+    /// puzzle `index`, if they are in the same row or column. They count
+    /// as one move unless `count_each_piece` is set.
+    fn play_line(&mut self, index: usize, ix: usize, iy: usize) {
+        let count_each = self.config.count_each_piece;
+        if self.puzzles[index].stage == Stage::Scrambling {
+            return;
+        }
+        if self.puzzles[index].line_to(ix, iy).is_none() {
+            self.reject_move(index, ix, iy);
+            return;
+        }
+        #[cfg(feature = "sound")]
+        self.play_sound(sound::Effect::Slide);
+        if let Some(solve) = self.puzzles[index].play_line(ix, iy, count_each) {
+            self.solved(index, solve);
+        }
+    }
+
+    /// Let the player know the piece at `(ix, iy)` of puzzle `index` can't
+    /// move: it flashes red for a moment. Clicks on the empty space are
+    /// let go without a word.
+    fn reject_move(&mut self, index: usize, ix: usize, iy: usize) {
+        if self.puzzles[index].board[iy][ix] == 0 {
+            return;
+        }
+        self.rejected = Some((index, (ix, iy), Instant::now()));
+        #[cfg(feature = "sound")]
+        self.play_sound(sound::Effect::Invalid);
+    }
+
+    /// Shift a row or column of puzzle `index`, in the Loopover variant.
+    fn play_shift(&mut self, index: usize, shift: Shift) {
+        if self.puzzles[index].stage == Stage::Scrambling {
+            return;
+        }
+        #[cfg(feature = "sound")]
+        self.play_sound(sound::Effect::Slide);
+        if let Some(solve) = self.puzzles[index].play_shift(shift) {
+            self.solved(index, solve);
+        }
+    }
+
+    /// Turn the piece at `(ix, iy)` of puzzle `index`, in the rotation variant.
+    fn play_rotation(&mut self, index: usize, ix: usize, iy: usize) {
+        if self.puzzles[index].stage == Stage::Scrambling {
+            return;
+        }
+        if let Some(solve) = self.puzzles[index].play_rotation(ix, iy) {
+            self.solved(index, solve);
+        }
+    }
+
+    /// Add the image of the day as a collection of its own, and show it
+    /// unless a solve is in progress.
+    #[cfg(feature = "daily")]
+    fn add_daily_image(&mut self, path: PathBuf) {
+        debug!("Image of the day in {}", path.display());
+        let index = self.collections.len();
+        let collection = packs::Collection::from_images("Image of the day", vec![path]);
+        self.collections.push(collection);
+        let puzzle = self.puzzle();
+        if puzzle.stage == Stage::Free {
+            self.puzzles[self.focused].set_collection(&self.collections[index], index);
+            self.show_toast(String::from("Image of the day"));
+        }
+    }
+
+    /// Play a new image on the focused board, added to the collection
+    /// named `name`, eg, a snapshot of the webcam.
+    #[cfg(any(feature = "webcam", feature = "url"))]
+    fn add_image(&mut self, name: &str, path: PathBuf) {
+        debug!("New image in {}", path.display());
+        let collection = match self.collections.iter().position(|c| c.name == name) {
+            Some(collection) => collection,
+            None => {
+                self.collections
+                    .push(packs::Collection::from_images(name, vec![]));
+                self.collections.len() - 1
+            }
+        };
+        let images = &mut self.collections[collection];
+        images.images.push(path);
+        images.info.push(None);
+        let index = images.images.len() - 1;
+        let focused = self.focused;
+        self.puzzles[focused].set_collection(&self.collections[collection], collection);
+        self.puzzles[focused].set_image(index);
+        self.puzzles[focused].reset();
+        if let GameState::Solved(_) = self.state {
+            self.state = GameState::Playing;
+        }
+    }
+
+    /// Scramble a random image of the collection of the focused board, or
+    /// a made-up one without images, when no surprise could be downloaded.
+    #[cfg(feature = "url")]
+    fn local_surprise(&mut self) {
+        let focused = self.focused;
+        match self.puzzle().image_list.is_empty() {
+            true => {
+                self.puzzles[focused].new_procedural();
+            }
+            false => self.puzzles[focused].random_image(),
+        }
+        if let GameState::Solved(_) = self.state {
+            self.state = GameState::Playing;
+        }
+        self.scramble();
+    }
+
+    /// Play the image file dropped on the window on the focused board, from
+    /// its collection if it is in one, otherwise added to the dropped files.
+    fn open_dropped(&mut self, path: &Path) {
+        debug!("Dropped {}", path.display());
+        if !image::ImageFormat::from_path(path).is_ok_and(|format| format.can_read()) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            self.show_toast(format!("Can't open {name}"));
+            return;
+        }
+        let (collection, index) = find_image(&self.collections, path).unwrap_or_else(|| {
+            let dropped = self
+                .collections
+                .iter()
+                .position(|c| c.name == DROPPED_COLLECTION);
+            let collection = dropped.unwrap_or_else(|| {
+                let collection = packs::Collection::from_images(DROPPED_COLLECTION, vec![]);
+                self.collections.push(collection);
+                self.collections.len() - 1
+            });
+            let images = &mut self.collections[collection];
+            images.images.push(path.to_path_buf());
+            images.info.push(None);
+            (collection, images.images.len() - 1)
+        });
+        let focused = self.focused;
+        self.puzzles[focused].set_collection(&self.collections[collection], collection);
+        self.puzzles[focused].set_image(index);
+        self.puzzles[focused].reset();
+        if let GameState::Solved(_) = self.state {
+            self.state = GameState::Playing;
+        }
+        self.show_toast(self.puzzle().image_name());
+    }
+
+    /// Pause the timers when the game loses focus and restart them when
+    /// it gets it back, if `auto_pause` is set.
+    fn focus_changed(&mut self, focused: bool) {
+        if !self.config.auto_pause || focused != self.flag_focus_paused {
+            return;
+        }
+        debug!("Focus changed, focused: {focused}");
+        self.flag_focus_paused = !focused;
+        match focused {
+            true => self.resume_timers(),
+            false => self.puzzles.iter_mut().for_each(Puzzle::pause),
+        }
+    }
+
+    /// Restart the timers of every puzzle, unless the game is paused.
+    fn resume_timers(&mut self) {
+        if let GameState::Paused = self.state {
+            return;
+        }
+        for puzzle in &mut self.puzzles {
+            puzzle.resume();
         }
     }
-    /// Update the image to show the current state of the board,
-    /// ie, cut the pieces from the solved image and paste them into the
-    /// image shown in the board according to the current state of the board.
-    fn update_image(&mut self) {
-        let (size, _h) = self.image_solved.dimensions();
-        let cell_size = size as usize / self.grid_size;
-
-        // Create a new image with the same size as the board
-        let mut new_image = image::DynamicImage::new_rgba8(size, size);
-
-        // Draw the pieces on the new image
-        for row in 0..self.grid_size {
-            for col in 0..self.grid_size {
-                let piece = self.board[row][col];
-                if piece != 0 {
-                    let x0 = ((piece - 1) % self.grid_size) as u32 * cell_size as u32;
-                    let y0 = ((piece - 1) / self.grid_size) as u32 * cell_size as u32;
-                    let little_square =
-                        self.image_solved
-                            .crop_imm(x0, y0, cell_size as u32, cell_size as u32);
-                    let x = (col * cell_size) as u32;
-                    let y = size - ((row + 1) * cell_size) as u32;
-                    debug!("Row {row}, Col {col}, piece: {piece:2} at x0: {x0:3}, y0: {y0:3} into x: {x:3}, y: {y:3}");
-                    new_image
-                        .copy_from(&little_square, x, y)
-                        .expect("Failed copying image");
-                } else {
-                    debug!("Row {row}, Col {col}, piece: {piece:2} - nothing to do");
+
+    /// Pause the game, or resume it. While paused the timers wait, the
+    /// boards are hidden so they can't be studied for free, and no moves
+    /// are taken.
+    fn toggle_pause(&mut self) {
+        match self.state {
+            GameState::Playing => {
+                self.state = GameState::Paused;
+                self.autosolve = None;
+                self.peek = None;
+                self.drag = None;
+                for puzzle in &mut self.puzzles {
+                    puzzle.pause();
+                }
+            }
+            GameState::Paused => {
+                self.state = GameState::Playing;
+                if !self.flag_focus_paused {
+                    self.resume_timers();
                 }
             }
+            _ => (),
+        }
+    }
+
+    /// Rect of the puzzle window, which is not necessarily the focused one.
+    fn window_rect(&self, app: &App) -> Rect {
+        app.window(self.window_id)
+            .expect("puzzle window is open")
+            .rect()
+    }
+
+    /// Show the next number of puzzles in `LAYOUTS`. New puzzles start
+    /// as a copy of the focused one, solved.
+    fn next_layout(&mut self, app: &App) {
+        let count = LAYOUTS
+            .iter()
+            .position(|&count| count == self.puzzles.len())
+            .map(|i| LAYOUTS[(i + 1) % LAYOUTS.len()])
+            .unwrap_or(1);
+        self.puzzles.truncate(count);
+        self.particles.truncate(count);
+        while self.puzzles.len() < count {
+            let puzzle = self.puzzle().duplicate();
+            self.puzzles.push(puzzle);
+        }
+        self.focused = self.focused.min(count - 1);
+        self.resize_puzzles(app);
+    }
+
+    /// Start the demo on a board filling the window, with the
+    /// images of the focused puzzle. The timers are paused meanwhile.
+    fn start_attract(&mut self, app: &App) {
+        debug!("Starting the attract mode");
+        for puzzle in &mut self.puzzles {
+            puzzle.pause();
+        }
+        let img_size = (board_size(self.window_rect(app)) * self.scale_factor) as u32;
+        let collection = self.puzzle().collection_current;
+        self.attract = Some(attract::Attract::new(
+            self.puzzle().grid_size,
+            &self.collections[collection],
+            collection,
+            img_size,
+        ));
+    }
+
+    /// Note some input from the player. Returns `true` if it ended the
+    /// demo, the input is then used for nothing else.
+    fn note_input(&mut self) -> bool {
+        self.last_input = Instant::now();
+        if self.attract.take().is_none() {
+            return false;
         }
-        self.image = new_image;
+        self.resume_timers();
+        true
     }
 
-    /// Increment the image index and calls `change_image()`.
+    /// Fit the images of every puzzle to its part of the window, in
+    /// physical pixels so they stay sharp on high resolution monitors.
+    fn resize_puzzles(&mut self, app: &App) {
+        self.resize_at = None;
+        self.scale_factor = app.window(self.window_id).unwrap().scale_factor();
+        let win = self.window_rect(app);
+        let rects = layout_rects(win, self.puzzles.len());
+        for (puzzle, rect) in self.puzzles.iter_mut().zip(rects) {
+            puzzle.resize((board_size(rect) * self.scale_factor) as u32);
+        }
+        if let Some(attract) = &mut self.attract {
+            attract
+                .puzzle
+                .resize((board_size(win) * self.scale_factor) as u32);
+        }
+    }
+
+    /// Change the grid size of the focused puzzle by `step`.
+    /// Both sides change, so rectangular grids keep their shape roughly.
+    fn change_grid_size(&mut self, step: isize) {
+        let GridSize { width, height } = self.puzzle().grid_size;
+        let grid_size = GridSize {
+            width: width.saturating_add_signed(step),
+            height: height.saturating_add_signed(step),
+        };
+        let range = 2..=MAX_GRID_SIZE;
+        if range.contains(&grid_size.width) && range.contains(&grid_size.height) {
+            self.puzzle_mut().set_grid_size(grid_size);
+            self.show_toast(grid_size.to_string());
+        }
+    }
+
+    /// Increment the image index of the focused puzzle.
     fn next_image(&mut self) {
-        self.image_index_current = (self.image_index_current + 1) % self.image_list.len();
-        self.change_image();
+        self.puzzle_mut().next_image();
+        self.show_recommended_sizes();
     }
-    /// Decrement the image index and calls `change_image()`.
+
+    /// Decrement the image index of the focused puzzle.
     fn previous_image(&mut self) {
-        if self.image_index_current == 0 {
-            self.image_index_current = self.image_list.len() - 1;
+        self.puzzle_mut().previous_image();
+        self.show_recommended_sizes();
+    }
+
+    /// Jump to another image of the focused puzzle, picked at random.
+    fn random_image(&mut self) {
+        self.puzzle_mut().random_image();
+        self.show_recommended_sizes();
+    }
+
+    /// Show the image at `index` on the focused puzzle.
+    fn select_image(&mut self, index: usize) {
+        self.puzzle_mut().set_image(index);
+        self.show_recommended_sizes();
+    }
+
+    /// Let the player know if the pack recommends other grid sizes
+    /// for the current image.
+    fn show_recommended_sizes(&mut self) {
+        if let Some(message) = self.puzzle().recommended_sizes() {
+            self.show_toast(message);
+        }
+    }
+
+    /// Take `images` as those of the images folder, the first collection,
+    /// eg, after files were dropped in it.
+    fn images_changed(&mut self, images: Vec<PathBuf>) {
+        let added = images
+            .len()
+            .saturating_sub(self.collections[0].images.len());
+        debug!("Images folder changed, {} images", images.len());
+        self.collections[0] = packs::Collection::from_images("images", images);
+        for puzzle in self
+            .puzzles
+            .iter_mut()
+            .filter(|p| p.collection_current == 0)
+        {
+            puzzle.refresh_collection(&self.collections[0]);
+        }
+        if added > 0 {
+            self.show_toast(format!("{added} new images"));
+        }
+    }
+
+    /// Switch the focused puzzle to the next collection which has images,
+    /// ie, the images folder or an imported pack, and show its first image.
+    fn next_collection(&mut self) {
+        let count = self.collections.len();
+        let current = self.puzzle().collection_current;
+        let Some(next) = (1..=count)
+            .map(|step| (current + step) % count)
+            .find(|&i| !self.collections[i].images.is_empty())
+        else {
+            return;
+        };
+        self.puzzles[self.focused].set_collection(&self.collections[next], next);
+        let name = self.collections[next].name.clone();
+        self.show_toast(name);
+    }
+
+    fn toggle_borderless(&mut self, app: &App) {
+        self.flag_borderless = !self.flag_borderless;
+        let window = app.window(self.window_id).unwrap();
+        window.set_decorations(!self.flag_borderless);
+    }
+
+    fn toggle_always_on_top(&mut self, app: &App) {
+        self.flag_always_on_top = !self.flag_always_on_top;
+        let window = app.window(self.window_id).unwrap();
+        window.set_always_on_top(self.flag_always_on_top);
+        self.show_toast(match self.flag_always_on_top {
+            true => String::from("Always on top"),
+            false => String::from("Normal window"),
+        });
+    }
+
+    /// Window title, with the live stats of the focused puzzle while solving.
+    fn live_title(&self) -> String {
+        let puzzle = self.puzzle();
+        if puzzle.stage != Stage::Solving {
+            return String::from(TITLE);
+        }
+        let seconds = puzzle.elapsed().as_secs();
+        self.config
+            .title_format
+            .replace("{time}", &clock::format_clock(seconds))
+            .replace("{moves}", &puzzle.move_count.to_string())
+            .replace("{size}", &puzzle.grid_size.to_string())
+            .replace("{image}", &puzzle.image_name())
+    }
+
+    /// Change the value of `row` of the start menu by `delta`, on the
+    /// focused puzzle so the choice shows right away. `Row::Play` plays.
+    fn menu_change(&mut self, row: Row, delta: isize) {
+        let GridSize { width, height } = self.puzzle().grid_size;
+        let side = |side: usize| side.saturating_add_signed(delta).clamp(2, MAX_GRID_SIZE);
+        match row {
+            Row::Width => self.puzzle_mut().set_grid_size(GridSize {
+                width: side(width),
+                height,
+            }),
+            Row::Height => self.puzzle_mut().set_grid_size(GridSize {
+                width,
+                height: side(height),
+            }),
+            Row::Image => {
+                let count = self.puzzle().image_list.len();
+                if count > 0 {
+                    let current = self.puzzle().image_index_current as isize;
+                    self.select_image((current + delta).rem_euclid(count as isize) as usize);
+                }
+            }
+            Row::Play => {
+                self.state = GameState::Playing;
+                self.scramble();
+            }
+        }
+    }
+
+    /// Slide a tile of the focused puzzle into the empty space, in the
+    /// direction `(dx, dy)`, so it is the one on the opposite side of the
+    /// empty space. Row 0 is the bottom row, up is `dy = 1`.
+    fn slide(&mut self, dx: isize, dy: isize) {
+        // nothing slides without an empty space
+        if self.puzzle().variant == Variant::Loopover {
+            #[cfg(feature = "sound")]
+            self.play_sound(sound::Effect::Invalid);
+            return;
+        }
+        let (empty_x, empty_y) = self.puzzle().index_empty();
+        let ix = empty_x.wrapping_add_signed(-dx);
+        let iy = empty_y.wrapping_add_signed(-dy);
+        let grid_size = self.puzzle().grid_size;
+        if ix < grid_size.width && iy < grid_size.height {
+            self.play_move(self.focused, ix, iy);
         } else {
-            self.image_index_current -= 1;
+            #[cfg(feature = "sound")]
+            self.play_sound(sound::Effect::Invalid);
         }
-        self.change_image();
     }
-    /// Change the image to the one at the current index.
-    fn change_image(&mut self) {
-        self.image_original = image::open(&self.image_list[self.image_index_current]).unwrap();
-        let (img_size, _h) = self.image_solved.dimensions();
-        self.image_solved = self.image_original.resize_to_fill(
-            img_size,
-            img_size,
-            image::imageops::FilterType::Nearest,
-        );
+
+    /// Show the solved image of the focused board until `key` is released,
+    /// or any key is pressed without one. Challenges allow `peek_limit`
+    /// peeks per solve, if set.
+    fn start_peek(&mut self, app: &App, key: Option<Key>) {
+        if self.peek.is_some() {
+            return;
+        }
+        let limit = self.config.peek_limit;
+        let puzzle = self.puzzle();
+        let counted = puzzle.stage == Stage::Solving && puzzle.challenge.is_some() && limit > 0;
+        if counted && puzzle.peeks >= limit {
+            self.show_toast(String::from("No peeks left"));
+            return;
+        }
+        self.peek = Some(render::Peek {
+            board: self.focused,
+            key,
+            texture: wgpu::Texture::from_image(app, &puzzle.image_solved),
+        });
+        if counted {
+            let puzzle = self.puzzle_mut();
+            puzzle.peeks += 1;
+            let left = limit - puzzle.peeks;
+            self.show_toast(format!("{left} peeks left"));
+        }
+    }
+
+    /// Play `effect`, if there is sound.
+    #[cfg(feature = "sound")]
+    fn play_sound(&self, effect: sound::Effect) {
+        if let Some(sound) = &self.sound {
+            sound.play(effect);
+        }
+    }
+
+    /// Skip to the next track of the music.
+    #[cfg(feature = "sound")]
+    fn next_track(&mut self) {
+        match self.sound.as_mut().is_some_and(sound::Sound::next_track) {
+            true => self.show_toast(String::from("Next track")),
+            false => self.show_toast(String::from("No music, add some to the music folder")),
+        }
+    }
+
+    /// Silence the music, or bring it back.
+    #[cfg(feature = "sound")]
+    fn toggle_music(&mut self) {
+        let Some(sound) = self.sound.as_mut() else {
+            self.show_toast(String::from("No sound output"));
+            return;
+        };
+        let muted = sound.toggle_music();
+        self.show_toast(match muted {
+            true => String::from("Music off"),
+            false => String::from("Music on"),
+        });
+    }
+
+    /// Silence the sound effects, or bring them back.
+    #[cfg(feature = "sound")]
+    fn toggle_sound(&mut self) {
+        let Some(sound) = self.sound.as_mut() else {
+            self.show_toast(String::from("No sound output"));
+            return;
+        };
+        sound.muted = !sound.muted;
+        let muted = sound.muted;
+        self.show_toast(match muted {
+            true => String::from("Sound off"),
+            false => String::from("Sound on"),
+        });
+    }
+
+    /// Apply a move commanded by Twitch chat to the focused puzzle, the
+    /// tile slides in the commanded direction. Moves are dropped unless
+    /// the boards are played, eg, while paused, in a replay or the demo.
+    #[cfg(feature = "twitch")]
+    fn chat_move(&mut self, command: twitch::Command) {
+        if !matches!(self.state, GameState::Playing) || self.attract.is_some() {
+            debug!("Chat move {command:?} dropped");
+            return;
+        }
+        match command {
+            twitch::Command::Up => self.slide(0, 1),
+            twitch::Command::Down => self.slide(0, -1),
+            twitch::Command::Left => self.slide(-1, 0),
+            twitch::Command::Right => self.slide(1, 0),
+        }
+    }
+
+    /// Show a short message on top of the board for `TOAST_DURATION`.
+    fn show_toast(&mut self, message: String) {
+        debug!("Toast: {message}");
+        self.toast = Some((message, Instant::now()));
     }
 }
 
+/// Split the window into one rect per puzzle, in reading order.
+/// Two puzzles go side by side, or one above the other in a tall window.
+fn layout_rects(win: Rect, count: usize) -> Vec<Rect> {
+    let (cols, rows) = match count {
+        1 => (1, 1),
+        2 if win.w() >= win.h() => (2, 1),
+        2 => (1, 2),
+        _ => (2, 2),
+    };
+    let (w, h) = (win.w() / cols as f32, win.h() / rows as f32);
+    (0..count)
+        .map(|i| {
+            let (col, row) = (i % cols, i / cols);
+            Rect::from_x_y_w_h(
+                win.left() + (col as f32 + 0.5) * w,
+                win.top() - (row as f32 + 0.5) * h,
+                w,
+                h,
+            )
+        })
+        .collect()
+}
+
+/// Longest side of the board drawn centred in `rect`, leaving
+/// some padding around it.
+fn board_size(rect: Rect) -> f32 {
+    let pad = rect.h() * PAD_HEIGHT_FACTOR;
+    (rect.w().min(rect.h()) - 2.0 * pad).max(1.0)
+}
+
+/// The board of `grid_size` drawn centred in `rect`, with square cells,
+/// its longest side being `board_size`.
+fn board_rect(rect: Rect, grid_size: GridSize) -> Rect {
+    let cell_size = board_size(rect) / grid_size.longest() as f32;
+    let (width, height) = (grid_size.width as f32, grid_size.height as f32);
+    Rect::from_xy_wh(rect.xy(), vec2(width, height) * cell_size)
+}
+
 fn main() {
     // for debugging, do `set PUZZLE_LOG=debug` in cmd
     Builder::from_env("PUZZLE_LOG").init();
     debug!("Logger initialized");
+    // read the command line before opening the window, so `--help` and
+    // mistakes are answered at once
+    cli::args();
 
     nannou::app(model)
         .update(update)
@@ -165,217 +1051,1884 @@ fn main() {
 }
 
 fn model(app: &App) -> Model {
-    let args: Vec<_> = env::args().collect();
+    // `Esc` also closes the command palette, see `event`
+    app.set_exit_on_escape(false);
+    let args = cli::args();
 
-    // Check if the user passed a size argument
-    // If not, use the default size of 4.
-    // Grid is always square.
-    let grid_size = match args.len() {
-        2 => {
-            let size = args[1].parse().unwrap();
-            size
+    // Import a puzzle pack, `sliding_puzzle --import-pack lighthouses.zip`
+    if let Some(zip_path) = &args.import_pack {
+        match packs::import(zip_path) {
+            Ok(name) => println!("Imported pack {name}"),
+            Err(e) => println!("Error importing pack {}: {e}", zip_path.display()),
         }
-        _ => 4,
+    }
+
+    // Same scrambles as another player, `sliding_puzzle --seed 42`
+    let rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
     };
+    let grid_size = args.grid_size();
+    let borderless = args.borderless;
+
+    let mut config = config::Config::load();
+    if let Some(interval) = args.scramble_interval_ms {
+        config.scramble_interval_ms = interval;
+    }
+    if let Some(filter) = args.resize_filter {
+        config.resize_filter = filter;
+    }
 
-    let _window = app
+    let window = app
         .new_window()
         .size(START_WINDOW_SIZE, START_WINDOW_SIZE)
-        .title("Sliding Puzzle")
+        .title(TITLE)
+        .always_on_top(config.always_on_top)
+        .decorations(!borderless)
         .view(view)
         .event(event)
-        .resized(window_resized)
-        .build()
-        .unwrap();
+        .resized(window_resized);
+    #[cfg(feature = "settings")]
+    let window = window.raw_event(settings::raw_window_event);
+    let window_id = window.build().unwrap();
 
-    let pad = (app.window_rect().h() * PAD_HEIGHT_FACTOR) as u32;
-    let img_size = START_WINDOW_SIZE - 2 * pad;
+    let window_state = WindowState::load();
+    restore_window_state(app, window_id, &window_state);
 
-    // Load a list of images from the images folder.
-    // Use the first image as current.
-    // If no images are found, use a blank image.
-    let mut image_original: image::DynamicImage;
-    let image_index_current = 0;
-    let image_list = get_images();
+    let pad = (START_WINDOW_SIZE as f32 * PAD_HEIGHT_FACTOR) as u32;
+    let scale_factor = app.window(window_id).unwrap().scale_factor();
+    let img_size = ((START_WINDOW_SIZE - 2 * pad) as f32 * scale_factor) as u32;
 
-    if image_list.is_empty() {
-        println!("No images found in the images folder");
-        image_original = image::DynamicImage::new_rgba8(img_size, img_size);
-        // Fill the image with white
-        for x in 0..img_size {
-            for y in 0..img_size {
-                image_original.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
-            }
-        }
+    // Load a list of images from the images folder, followed by the
+    // imported packs. Use the image given on the command line, or else
+    // the first image of the first collection with images, as current.
+    let images_dir = images::images_dir(args.images_dir.as_ref(), &config.images_dir);
+    debug!("Reading images from {}", images_dir.display());
+    let shuffle_images = args.shuffle_images || config.shuffle_images;
+    let images = images::get_images(&images_dir, shuffle_images);
+    let mut collections = vec![packs::Collection::from_images("images", images)];
+    collections.extend(packs::load_collections());
+    let start_image = args.image.as_ref().map(|path| {
+        find_image(&collections, path).unwrap_or_else(|| {
+            let collection = packs::Collection::from_images("Command line", vec![path.clone()]);
+            collections.push(collection);
+            (collections.len() - 1, 0)
+        })
+    });
+    let collection_current = match start_image {
+        Some((collection, _)) => collection,
+        None => collections
+            .iter()
+            .position(|c| !c.images.is_empty())
+            .unwrap_or(0),
+    };
+    if collections[collection_current].images.is_empty() {
+        println!("No images found in the images folder or in packs");
     } else {
-        debug!("Images found: {:?}", image_list);
-        image_original = image::open(&image_list[image_index_current]).unwrap();
+        debug!("Images found: {:?}", collections[collection_current].images);
     }
-
-    // Resize the original image to a square to fit the window,
-    // also make a working copy of it which will be used to display the pieces
-    let image_solved =
-        image_original.resize_to_fill(img_size, img_size, image::imageops::FilterType::Nearest);
-    let image = image_solved.clone();
-    let texture = wgpu::Texture::from_image(app, &image);
-
-    Model {
+    let mut puzzle = Puzzle::new(
         grid_size,
-        flag_scramble: false,
-        flag_show_numbers: true,
-        scramble_count: 0,
-        board: solved_board(grid_size),
-        image_list,
-        image_index_current,
-        image_original,
-        image_solved,
-        image,
-        texture,
-    }
-}
-
-/// Resize the image when the window is resized.
-fn window_resized(_app: &App, model: &mut Model, dim: Vec2) {
-    let pad = (dim.y * PAD_HEIGHT_FACTOR) as u32;
-    let img_size = dim.y.min(dim.x) as u32 - 2 * pad;
-    model.image_solved = model.image_original.resize_to_fill(
-        img_size,
+        &collections[collection_current],
+        collection_current,
         img_size,
-        image::imageops::FilterType::Nearest,
     );
-}
-
-/// Game loop
-/// This function is called every frame.
-/// It updates the image and the texture.
-/// It also scrambles the board if the flag is set.
-fn update(app: &App, model: &mut Model, _update: Update) {
-    // Do a number of random moves to scramble the board is the flag is set.
-    if model.flag_scramble {
-        model.do_one_random_move();
-        thread::sleep(time::Duration::from_millis(15));
-        model.scramble_count += 1;
-        if model.scramble_count > 100 {
-            model.scramble_count = 0;
-            model.flag_scramble = false;
-            app.set_loop_mode(LoopMode::Wait);
-        }
-    }
-    model.update_image();
-    model.texture = wgpu::Texture::from_image(app, &model.image);
-}
-
-/// Process a user mouse click.
-fn mouse_clicked(mouse_x: f32, mouse_y: f32, app: &App, model: &mut Model) {
-    // and move it if it can be moved.
-    let win = app.window_rect();
-    let pad = win.h() * PAD_HEIGHT_FACTOR;
-    let cell_size = (win.h().min(win.w()) - 2.0 * pad) / model.grid_size as f32;
-    let board_size = cell_size * model.grid_size as f32;
-    if mouse_x.abs().max(mouse_y.abs()) > board_size / 2.0 {
-        debug!("Clicked outside the board");
-        return;
+    if let Some((_, index)) = start_image.filter(|&(_, index)| index > 0) {
+        puzzle.set_image(index);
     }
-    let x_offset = (win.w() - 2.0 * pad - board_size) / 2.0;
-    let y_offset = (win.h() - 2.0 * pad - board_size) / 2.0;
-
-    let ix_clicked = (model.grid_size as f32 * (mouse_x + win.w() / 2.0 - pad - x_offset)
-        / (win.w() - 2.0 * pad - 2.0 * x_offset)) as usize;
-    let iy_clicked = (model.grid_size as f32 * (mouse_y + win.h() / 2.0 - pad - 2.0 * y_offset)
-        / (win.h() - 2.0 * pad - y_offset)) as usize;
-    debug!("Indices clicked: {}, {}", ix_clicked, iy_clicked);
-    model.try_move(ix_clicked, iy_clicked);
-}
-
-fn event(app: &App, model: &mut Model, event: WindowEvent) {
-    match event {
-        MousePressed(_button) => mouse_clicked(app.mouse.x, app.mouse.y, app, model),
-        KeyPressed(Key::R) => model.reset(),
-        KeyPressed(Key::N) => model.flag_show_numbers = !model.flag_show_numbers,
-        KeyPressed(Key::Period) => model.next_image(),
-        KeyPressed(Key::Comma) => model.previous_image(),
-        KeyPressed(Key::S) => {
-            app.set_loop_mode(LoopMode::RefreshSync);
-            model.flag_scramble = true;
-        }
-        _ => (),
+    puzzle.scramble_moves = args
+        .scramble_moves
+        .or((config.scramble_moves > 0).then_some(config.scramble_moves));
+    puzzle.scramble_difficulty = config.scramble_difficulty.clamp(0.0, 1.0);
+    puzzle.blanks = args.blanks.unwrap_or(config.blanks).max(1);
+    if config.resize_filter != puzzle.filter {
+        puzzle.set_filter(config.resize_filter);
+    }
+    if config.jigsaw_edges {
+        puzzle.toggle_jigsaw();
+    }
+    if config.colors {
+        puzzle.toggle_colors();
+    }
+    puzzle.reset();
+    if let Some(challenge) = args.challenge {
+        puzzle.challenge = Some(challenge);
+        puzzle.start_seeded_scramble(challenge.seed);
+    }
+    if let Some(board) = args.position.clone() {
+        puzzle.start_from_position(board);
     }
-}
-
-fn view(app: &App, model: &Model, frame: Frame) {
-    frame.clear(BLACK);
-
-    let draw = app.draw();
-
-    draw.texture(&model.texture).x_y(0.0, 0.0);
-
-    // draw the board
-    let win = app.window_rect();
-    let pad = win.h() * PAD_HEIGHT_FACTOR;
-    let cell_size = (win.w().min(win.h()) - 2.0 * pad) / model.grid_size as f32;
-
-    let font_size = (cell_size / 2.0) as u32;
-
-    let x_offset = (win.w() - 2.0 * pad - cell_size * model.grid_size as f32) / 2.0;
-    let y_offset = (win.h() - 2.0 * pad - cell_size * model.grid_size as f32) / 2.0;
 
-    // draw all the cells
-    for row in 0..model.grid_size {
-        let y = win.bottom() + y_offset + pad + row as f32 * cell_size + cell_size / 2.0;
+    // Twitch-plays mode is enabled by naming the channel to join,
+    // e.g. `set PUZZLE_TWITCH_CHANNEL=mychannel` in cmd.
+    #[cfg(feature = "twitch")]
+    let twitch =
+        std::env::var("PUZZLE_TWITCH_CHANNEL").ok().and_then(
+            |channel| match twitch::Chat::connect(&channel, app.create_proxy()) {
+                Ok(chat) => Some(chat),
+                Err(e) => {
+                    println!("Error connecting to Twitch chat: {e}");
+                    None
+                }
+            },
+        );
 
-        for col in 0..model.grid_size {
-            let x = win.left() + x_offset + pad + col as f32 * cell_size + cell_size / 2.0;
+    #[cfg(feature = "daily")]
+    let daily = match config.daily_image_url.is_empty() {
+        true => None,
+        false => Some(daily::fetch(&config.daily_image_url, app.create_proxy())),
+    };
 
-            let piece = model.board[row][col];
+    #[cfg(feature = "sound")]
+    let sound = sound::Sound::new(config.volume, config.music_volume)
+        .inspect_err(|e| println!("Error opening the sound output: {e}"))
+        .ok();
 
-            // draw the cell
-            draw.rect()
-                .x_y(x, y)
-                .w_h(cell_size, cell_size)
-                .no_fill()
-                .stroke(GREY)
-                .stroke_weight(2.0);
+    set_window_icon(app, window_id, &puzzle.image_original);
+    let icon_image = puzzle.image_list.get(puzzle.image_index_current).cloned();
 
-            // draw the number of the piece
-            if model.flag_show_numbers {
-                let text = match piece {
-                    0 => String::from(""),
-                    _ => piece.to_string(),
-                };
+    let mut model = Model {
+        flag_show_numbers: !args.no_numbers,
+        puzzles: vec![puzzle],
+        focused: 0,
+        toast: None,
+        screenshot_pending: None,
+        collections,
+        window_id,
+        qr_window: None,
+        icon_image,
+        flag_always_on_top: config.always_on_top,
+        flag_borderless: borderless,
+        window_state,
+        scale_factor,
+        resize_at: None,
+        last_input: Instant::now(),
+        attract: None,
+        panel: None,
+        palette: None,
+        numbering: Numbering::default(),
+        flag_outline: false,
+        flag_thumbnail: false,
+        flag_focus_paused: false,
+        particles: render::Particles::new(config.reduced_motion),
+        gallery: None,
+        stats: None,
+        gif_export: None,
+        zoom: 1.0,
+        view_offset: Vec2::ZERO,
+        pan_from: None,
+        state: match args.size.or(args.size_positional).is_some()
+            || args.image.is_some()
+            || args.challenge.is_some()
+            || args.position.is_some()
+        {
+            true => GameState::Playing,
+            false => GameState::Menu(Menu::default()),
+        },
+        autosolve: None,
+        peek: None,
+        drag: None,
+        touch: None,
+        rejected: None,
+        rng,
+        keys: keys::KeyMap::new(&config.keys),
+        config,
+        title: String::from(TITLE),
+        #[cfg(feature = "twitch")]
+        twitch,
+        #[cfg(feature = "record")]
+        recorder: None,
+        #[cfg(feature = "record")]
+        encoding: None,
+        #[cfg(feature = "tray")]
+        tray: None,
+        #[cfg(feature = "daily")]
+        daily,
+        images_watch: images::watch(images_dir, shuffle_images, app.create_proxy()),
+        #[cfg(feature = "webcam")]
+        webcam: None,
+        #[cfg(feature = "url")]
+        download: args
+            .url
+            .as_ref()
+            .map(|url| (download_image(url, app.create_proxy()), false)),
+        #[cfg(feature = "settings")]
+        settings: settings::Settings::new(&app.window(window_id).unwrap()),
+        #[cfg(feature = "gamepad")]
+        gamepads: gamepad::Gamepads::new()
+            .inspect_err(|e| println!("Error reading game controllers: {e}"))
+            .ok(),
+        #[cfg(feature = "sound")]
+        sound,
+    };
+    // Wake up once a second so the clock in the title keeps ticking.
+    let proxy = app.create_proxy();
+    thread::spawn(move || {
+        while proxy.wakeup().is_ok() {
+            thread::sleep(time::Duration::from_secs(1));
+        }
+    });
+    if model.config.control_panel {
+        panel::open(app, &mut model);
+    }
+    if let Some(path) = &args.replay {
+        match replay::Replay::load(path) {
+            Ok(replay) => model.start_playback(replay),
+            Err(e) => println!("Error loading the replay {}: {e}", path.display()),
+        }
+    }
+    model
+}
 
-                let text_area = geom::Rect::from_w_h(cell_size, cell_size).relative_to([-x, -y]);
+/// Put the window back on the monitor it was on last time, fullscreen
+/// if it was. Falls back to the default placement if that monitor is gone.
+fn restore_window_state(app: &App, window_id: window::Id, state: &WindowState) {
+    let window = app.window(window_id).unwrap();
+    let monitor = state.monitor.as_ref().and_then(|name| {
+        window
+            .winit_window()
+            .available_monitors()
+            .find(|monitor| monitor.name().as_ref() == Some(name))
+    });
+    if state.fullscreen {
+        window.set_fullscreen_with(Some(Fullscreen::Borderless(monitor)));
+    } else if let Some(monitor) = monitor {
+        // Centre the window on that monitor.
+        let (x, y) = (monitor.position().x, monitor.position().y);
+        let (w, h) = window.outer_size_pixels();
+        let x = x + (monitor.size().width as i32 - w as i32) / 2;
+        let y = y + (monitor.size().height as i32 - h as i32) / 2;
+        window.set_outer_position_pixels(x, y);
+    }
+}
 
-                draw.text(&text)
-                    .font_size(font_size)
-                    .xy(text_area.xy())
-                    .wh(text_area.wh())
-                    .align_text_middle_y()
-                    .center_justify()
-                    .color(BLACK);
-            }
-        }
+/// Remember the monitor and fullscreen mode of the window if they changed.
+fn save_window_state(app: &App, model: &mut Model) {
+    let window = app.window(model.window_id).unwrap();
+    let state = WindowState {
+        monitor: window.current_monitor().and_then(|monitor| monitor.name()),
+        fullscreen: window.is_fullscreen(),
+    };
+    if state != model.window_state {
+        debug!("Window state changed to {state:?}");
+        state.save();
+        model.window_state = state;
     }
+}
 
-    draw.to_frame(app, &frame).unwrap();
+/// Resize the images when the window is resized, eg, going fullscreen,
+/// once it stops for `RESIZE_SETTLE`. Resizing big images at each step of
+/// a drag of the window edge would stutter.
+fn window_resized(app: &App, model: &mut Model, _dim: Vec2) {
+    save_window_state(app, model);
+    model.resize_at = Some(Instant::now() + RESIZE_SETTLE);
 }
 
-/// Get the list of images from the images folder.
-/// Only PNG images are accepted.
-/// If no images are found, an empty vector is returned.
-fn get_images() -> Vec<PathBuf> {
-    let mut images = vec![];
-    match fs::read_dir("images") {
-        Ok(paths) => {
-            for path in paths {
-                let path = path.unwrap().path();
-                if path.extension().unwrap() == "png" {
-                    images.push(path);
-                }
-            }
+/// Game loop
+/// This function is called every frame.
+/// It updates the images and the textures.
+/// It also scrambles the boards which have the flag set.
+fn update(app: &App, model: &mut Model, _update: Update) {
+    // Play the random moves due of the boards being scrambled, faster with
+    // the animation speed.
+    let mut scrambling = false;
+    let interval = time::Duration::from_millis(model.config.scramble_interval_ms as u64)
+        / model.config.animation_speed.max(1) as u32;
+    for (i, puzzle) in model.puzzles.iter_mut().enumerate() {
+        if puzzle.stage == Stage::Scrambling {
+            puzzle.scramble_for(interval);
+            model.particles.dust(i);
+            scrambling = true;
+        }
+    }
+    model.particles.update();
+    // Input waits while a board is being scrambled.
+    if let GameState::Playing | GameState::Scrambling = model.state {
+        #[cfg(feature = "sound")]
+        if !scrambling && matches!(model.state, GameState::Scrambling) {
+            model.play_sound(sound::Effect::Scrambled);
+        }
+        model.state = match scrambling {
+            true => GameState::Scrambling,
+            false => GameState::Playing,
+        };
+    }
+    // Play the moves of the replay due by now.
+    if let GameState::Replaying(playback) = &mut model.state {
+        let steps = playback.due();
+        if let Some(puzzle) = model.puzzles.get_mut(playback.board) {
+            for step in steps {
+                puzzle.play_step(step);
+            }
+        }
+    }
+    // The victory screen shows the whole image, empty space included.
+    if let GameState::Solved(victory) = &mut model.state {
+        if victory.board >= model.puzzles.len() {
+            model.state = GameState::Playing;
+        } else if victory.texture.is_none() {
+            let image = &model.puzzles[victory.board].image_solved;
+            victory.texture = Some(wgpu::Texture::from_image(app, image));
+        }
+    }
+    // Search the solution of the board with auto-solve on, then play it.
+    for _ in 0..model.config.animation_speed.max(1) {
+        model.autosolve_step();
+    }
+    // Start the demo after a while without input, and move it on.
+    let idle = time::Duration::from_secs(model.config.attract_after);
+    if model.attract.is_none()
+        && model.config.attract_after > 0
+        && model.last_input.elapsed() > idle
+    {
+        model.start_attract(app);
+    }
+    if let Some(attract) = &mut model.attract {
+        attract.step();
+    }
+    // Apply the moves commanded by Twitch chat.
+    #[cfg(feature = "twitch")]
+    if let Some(command) = model.twitch.as_mut().and_then(twitch::Chat::poll) {
+        model.chat_move(command);
+    }
+    // Show the image of the day once it is there.
+    #[cfg(feature = "daily")]
+    if let Some(result) = model.daily.as_ref().and_then(|rx| rx.try_recv().ok()) {
+        model.daily = None;
+        match result {
+            Ok(path) => model.add_daily_image(path),
+            Err(e) => println!("Error fetching the image of the day: {e}"),
+        }
+    }
+    // Take the images added to or removed from the images folder.
+    if let Some(images) = model.images_watch.try_iter().last() {
+        model.images_changed(images);
+    }
+    // Play the snapshot of the webcam once it is taken.
+    #[cfg(feature = "webcam")]
+    if let Some(result) = model.webcam.as_ref().and_then(|rx| rx.try_recv().ok()) {
+        model.webcam = None;
+        match result {
+            Ok(path) => {
+                model.add_image(WEBCAM_COLLECTION, path);
+                model.show_toast(String::from("Snapshot of the webcam"));
+            }
+            Err(e) => {
+                println!("Error taking a snapshot of the webcam: {e}");
+                model.show_toast(e);
+            }
+        }
+    }
+    // Play the image downloaded from a URL once it is there.
+    #[cfg(feature = "url")]
+    if let Some((result, surprise)) = model
+        .download
+        .as_ref()
+        .and_then(|(rx, surprise)| Some((rx.try_recv().ok()?, *surprise)))
+    {
+        model.download = None;
+        match result {
+            Ok(path) if surprise => {
+                model.add_image(DOWNLOADS_COLLECTION, path);
+                model.scramble();
+                model.show_toast(String::from("Surprise!"));
+            }
+            Ok(path) => {
+                model.add_image(DOWNLOADS_COLLECTION, path);
+                model.show_toast(String::from("Downloaded image"));
+            }
+            Err(e) if surprise => {
+                println!("Error downloading a surprise image: {e}");
+                model.local_surprise();
+                model.show_toast(String::from("Offline, a surprise from your images"));
+            }
+            Err(e) => {
+                println!("Error downloading the image: {e}");
+                model.show_toast(format!("Download failed: {e}"));
+            }
+        }
+    }
+    if let Some(gallery) = model.gallery.as_mut() {
+        gallery.poll(app);
+    }
+    #[cfg(feature = "settings")]
+    settings::update(app, model);
+    // Confirm the screenshot once the captured frame has been drawn,
+    // so the toast itself does not end up in the picture.
+    if let Some((path, frame)) = &model.screenshot_pending {
+        if app.window(model.window_id).unwrap().elapsed_frames() > *frame {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let message = format!("Saved {name}");
+            model.screenshot_pending = None;
+            model.show_toast(message);
+        }
+    }
+    // Capture the frame for the recording, and let the player know
+    // when a finished recording has been encoded.
+    #[cfg(feature = "record")]
+    {
+        if let Some(recorder) = model.recorder.as_mut() {
+            recorder.capture(&app.window(model.window_id).unwrap());
+        }
+        let encoded = model.encoding.as_ref().and_then(|rx| rx.try_recv().ok());
+        if let Some(result) = encoded {
+            model.encoding = None;
+            match result {
+                Ok(path) => {
+                    println!("Recording saved to {}", path.display());
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    model.show_toast(format!("Saved {name}"));
+                }
+                Err(e) => {
+                    println!("Error encoding the recording: {e}");
+                    model.show_toast(String::from("Recording failed"));
+                }
+            }
+        }
+    }
+    // Let the player know when the GIF of a replay is saved.
+    let exported = model.gif_export.as_ref().and_then(|rx| rx.try_recv().ok());
+    if let Some(result) = exported {
+        model.gif_export = None;
+        match result {
+            Ok(path) => {
+                println!("Replay GIF saved to {}", path.display());
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                model.show_toast(format!("Saved {name}"));
+            }
+            Err(e) => {
+                println!("Error saving the replay GIF: {e}");
+                model.show_toast(String::from("Saving the GIF failed"));
+            }
+        }
+    }
+    // Handle the commands given from the tray.
+    #[cfg(feature = "tray")]
+    if let Some(command) = model.tray.as_ref().and_then(tray::Tray::poll) {
+        tray_command(app, model, command);
+    }
+    // Run the actions of the game controllers.
+    #[cfg(feature = "gamepad")]
+    for action in model
+        .gamepads
+        .as_mut()
+        .map(|g| g.poll())
+        .unwrap_or_default()
+    {
+        gamepad_action(app, model, action);
+    }
+    // Start the next track once one is over.
+    #[cfg(feature = "sound")]
+    if let Some(sound) = model.sound.as_mut() {
+        sound.poll();
+    }
+    // Stop flashing the piece which couldn't move.
+    if model
+        .rejected
+        .is_some_and(|(_, _, at)| at.elapsed() > render::REJECT_FLASH)
+    {
+        model.rejected = None;
+    }
+    // Hide the toast once it has been shown long enough.
+    if let Some((_, shown_at)) = &model.toast {
+        if shown_at.elapsed() > TOAST_DURATION {
+            model.toast = None;
+        }
+    }
+    // Keep drawing frames while something changes on screen,
+    // otherwise wait for the next event.
+    let busy = scrambling
+        || model.toast.is_some()
+        || model.screenshot_pending.is_some()
+        || model.attract.is_some()
+        || model.autosolve.is_some()
+        || model.rejected.is_some()
+        || model.resize_at.is_some()
+        || !model.particles.is_empty();
+    #[cfg(feature = "record")]
+    let busy = busy || model.recorder.is_some();
+    #[cfg(feature = "settings")]
+    let busy = busy || model.settings.open;
+    // controllers have no events, they are polled every frame
+    #[cfg(feature = "gamepad")]
+    let busy = busy
+        || model
+            .gamepads
+            .as_ref()
+            .is_some_and(gamepad::Gamepads::connected);
+    app.set_loop_mode(match busy {
+        true => LoopMode::RefreshSync,
+        false => LoopMode::Wait,
+    });
+    // Size the images again for a monitor with another scale factor, or
+    // once the window is no longer being resized.
+    let scale_factor = app.window(model.window_id).unwrap().scale_factor();
+    if model.resize_at.is_some_and(|at| Instant::now() >= at) {
+        model.resize_puzzles(app);
+    }
+    if scale_factor != model.scale_factor {
+        debug!("Scale factor changed to {scale_factor}");
+        model.resize_puzzles(app);
+    }
+    // Show the time and moves of the solve in the title.
+    let title = model.live_title();
+    if title != model.title {
+        app.window(model.window_id).unwrap().set_title(&title);
+        model.title = title;
+    }
+    // Keep the window icon in sync with the focused puzzle image.
+    let puzzle = model.puzzle();
+    let current_image = puzzle.image_list.get(puzzle.image_index_current);
+    if current_image != model.icon_image.as_ref() {
+        model.icon_image = current_image.cloned();
+        set_window_icon(app, model.window_id, &model.puzzle().image_original);
+    }
+    let demo = model.attract.as_mut().map(|attract| &mut attract.puzzle);
+    for puzzle in model.puzzles.iter_mut().chain(demo) {
+        if puzzle.textures.len() != puzzle.pieces.len() {
+            puzzle.textures = render::piece_textures(app, &puzzle.pieces);
+        }
+    }
+    // Make the thumbnail of the target image again after it changed.
+    let puzzle = &mut model.puzzles[model.focused];
+    if model.flag_thumbnail && puzzle.thumbnail.is_none() {
+        let size = (puzzle.img_size() as f32 * THUMBNAIL_FACTOR) as u32;
+        let image = puzzle.image_solved.thumbnail(size, size);
+        puzzle.thumbnail = Some(wgpu::Texture::from_image(app, &image));
+    }
+}
+
+/// Small version of `image` for icons.
+fn icon_image(image: &image::DynamicImage) -> image::RgbaImage {
+    image
+        .resize_to_fill(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Triangle)
+        .to_rgba8()
+}
+
+/// Use a small version of `image` as the window (and taskbar) icon.
+fn set_window_icon(app: &App, window_id: window::Id, image: &image::DynamicImage) {
+    let icon = icon_image(image);
+    match nannou::winit::window::Icon::from_rgba(icon.into_raw(), ICON_SIZE, ICON_SIZE) {
+        Ok(icon) => {
+            if let Some(window) = app.window(window_id) {
+                window.set_window_icon(Some(icon));
+            }
+        }
+        Err(e) => println!("Error making the window icon: {e}"),
+    }
+}
+
+/// Minimize the game, into the tray if there is one, and pause the timers.
+fn minimize(app: &App, model: &mut Model) {
+    for puzzle in &mut model.puzzles {
+        puzzle.pause();
+    }
+    let window = app.window(model.window_id).unwrap();
+    #[cfg(feature = "tray")]
+    {
+        if model.tray.is_none() {
+            match tray::Tray::new(app.create_proxy()) {
+                Ok(tray) => model.tray = Some(tray),
+                Err(e) => println!("Error making the tray icon: {e}"),
+            }
+        }
+        if let Some(tray) = &model.tray {
+            tray.show(&icon_image(&model.puzzle().image_original));
+            window.set_visible(false);
+            return;
+        }
+    }
+    window.set_minimized(true);
+}
+
+/// Run `action` of a game controller, as its key would in `event`.
+#[cfg(feature = "gamepad")]
+fn gamepad_action(app: &App, model: &mut Model, action: Action) {
+    if model.note_input() || model.palette.is_some() || model.gallery.is_some() {
+        return;
+    }
+    if model.stats.take().is_some() {
+        return;
+    }
+    match &mut model.state {
+        GameState::Menu(menu) => match action {
+            Action::SlideUp => menu.move_selection(-1),
+            Action::SlideDown => menu.move_selection(1),
+            Action::SlideLeft | Action::SlideRight => {
+                let row = menu.row();
+                let delta = if action == Action::SlideLeft { -1 } else { 1 };
+                model.menu_change(row, delta);
+            }
+            Action::Scramble => model.menu_change(Row::Play, 0),
+            Action::Menu => model.state = GameState::Playing,
+            _ => (),
+        },
+        GameState::Scrambling => (),
+        // any button resumes, none of them is needed while paused
+        GameState::Paused => model.toggle_pause(),
+        GameState::Replaying(_)
+        | GameState::Editing(_)
+        | GameState::Cropping(_)
+        | GameState::Klotski(_) => model.state = GameState::Playing,
+        GameState::Playing | GameState::Solved(_) => {
+            model.state = GameState::Playing;
+            run_action(app, model, action);
+        }
+    }
+}
+
+/// Back from the tray with a command picked in it.
+#[cfg(feature = "tray")]
+fn tray_command(app: &App, model: &mut Model, command: tray::Command) {
+    debug!("Tray command {command:?}");
+    if let Some(tray) = &model.tray {
+        tray.hide();
+    }
+    let window = app.window(model.window_id).unwrap();
+    window.set_visible(true);
+    window.winit_window().focus_window();
+    model.resume_timers();
+    match command {
+        tray::Command::Resume => (),
+        tray::Command::NewGame => model.scramble(),
+        tray::Command::Quit => app.quit(),
+    }
+}
+
+/// Save the next rendered frame, board and all, to a timestamped PNG
+/// in the screenshots folder of the profile.
+fn take_screenshot(app: &App, model: &mut Model) {
+    let path = profile::profile_path(SCREENSHOTS_DIR)
+        .join(format!("puzzle_{}.png", clock::timestamp_millis()));
+    debug!("Saving screenshot to {}", path.display());
+    let window = app.window(model.window_id).unwrap();
+    window.capture_frame(&path);
+    model.toast = None;
+    model.screenshot_pending = Some((path, window.elapsed_frames()));
+}
+
+/// Start recording the window, or stop and encode the recording.
+#[cfg(feature = "record")]
+fn toggle_recording(app: &App, model: &mut Model) {
+    let window = app.window(model.window_id).unwrap();
+    match model.recorder.take() {
+        Some(recorder) => {
+            model.encoding = Some(recorder.finish(&window));
+            model.show_toast(String::from("Encoding the recording"));
+        }
+        None => match recorder::Recorder::start() {
+            Ok(recorder) => model.recorder = Some(recorder),
+            Err(e) => println!("Error starting the recording: {e}"),
+        },
+    }
+}
+
+/// Download the image at `url` in the background, into the downloads
+/// folder of the profile.
+#[cfg(feature = "url")]
+fn download_image(url: &str, proxy: nannou::app::Proxy) -> mpsc::Receiver<Result<PathBuf, String>> {
+    let url = url.trim().to_string();
+    let path = profile::profile_path(DOWNLOADS_DIR)
+        .join(format!("download_{}.png", clock::timestamp_millis()));
+    web::in_background(proxy, move || web::download(&url, &path).map(|()| path))
+}
+
+/// Save the scrambled picture of the focused puzzle to a timestamped PNG
+/// in the profile, with the numbers if they are shown.
+fn export_picture(model: &mut Model) {
+    let path = profile::profile_path(PICTURES_DIR)
+        .join(format!("scrambled_{}.png", clock::timestamp_millis()));
+    let picture = model
+        .puzzle()
+        .picture(model.puzzle().img_size(), model.flag_show_numbers);
+    let saved = match path.parent().map(fs::create_dir_all) {
+        Some(Err(e)) => Err(e.into()),
+        _ => picture.save(&path),
+    };
+    match saved {
+        Ok(()) => {
+            println!("Picture saved to {}", path.display());
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            model.show_toast(format!("Saved {name}"));
+        }
+        Err(e) => {
+            println!("Error saving picture {}: {e}", path.display());
+            model.show_toast(String::from("Saving the picture failed"));
+        }
+    }
+}
+
+/// Save a printable sheet of the focused puzzle to a timestamped PDF
+/// in the profile.
+fn export_sheet(model: &mut Model) {
+    let path =
+        profile::profile_path(SHEETS_DIR).join(format!("sheet_{}.pdf", clock::timestamp_millis()));
+    match sheet::write_sheet(model.puzzle(), &path) {
+        Ok(()) => {
+            println!("Sheet saved to {}", path.display());
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            model.show_toast(format!("Saved {name}"));
         }
         Err(e) => {
-            println!("Error reading images folder: {e}");
+            println!("Error saving sheet {}: {e}", path.display());
+            model.show_toast(String::from("Saving the sheet failed"));
         }
     }
-    images
+}
+
+/// Export the solve history as a static HTML page in the profile.
+fn export_history(model: &mut Model) {
+    let dir = profile::profile_path(EXPORT_DIR);
+    match export::export_html(&history::load(), &dir) {
+        Ok(path) => {
+            println!("Solve history exported to {}", path.display());
+            model.show_toast(String::from("Solve history exported"));
+        }
+        Err(e) => {
+            println!("Error exporting solve history: {e}");
+            model.show_toast(String::from("Export failed"));
+        }
+    }
+}
+
+/// Process a user mouse click: focus the puzzle under the mouse
+/// and move the piece clicked if it can be moved.
+fn mouse_clicked(point: Point2, button: MouseButton, app: &App, model: &mut Model) {
+    let Some((index, cell)) = hit_test(app, model, point) else {
+        return;
+    };
+    model.focused = index;
+    let Some((ix_clicked, iy_clicked)) = cell else {
+        debug!("Clicked outside the board");
+        // Without a frame, the padding around the board is used to move the window.
+        if model.flag_borderless {
+            let window = app.window(model.window_id).unwrap();
+            if let Err(e) = window.winit_window().drag_window() {
+                debug!("Could not drag the window: {e}");
+            }
+        }
+        return;
+    };
+    debug!("Indices clicked: {}, {}", ix_clicked, iy_clicked);
+    if button == MouseButton::Left && model.puzzles[index].variant == Variant::Loopover {
+        if let Some((index, shift)) = loopover_push(app, model, point, point) {
+            model.play_shift(index, shift);
+        }
+        return;
+    }
+    match button {
+        MouseButton::Right => model.play_rotation(index, ix_clicked, iy_clicked),
+        // the piece next to the empty space follows the mouse until released
+        MouseButton::Left if model.puzzles[index].is_move_valid(ix_clicked, iy_clicked) => {
+            model.drag = Some(render::Drag {
+                board: index,
+                cell: (ix_clicked, iy_clicked),
+                from: (point - model.view_offset) / model.zoom,
+                offset: 0.0,
+                moved: false,
+            });
+        }
+        _ => model.play_line(index, ix_clicked, iy_clicked),
+    }
+}
+
+/// Shift of the Loopover variant from `from` to `to`: the row or column of
+/// the piece at `from` shifts the way of the swipe, or for a click or tap,
+/// away from the centre of the piece, eg, a click near its right edge
+/// shifts its row right. Returns the index of the puzzle too.
+fn loopover_push(app: &App, model: &Model, from: Point2, to: Point2) -> Option<(usize, Shift)> {
+    let (index, (ix, iy)) = match hit_test(app, model, from)? {
+        (index, Some(cell)) => (index, cell),
+        _ => return None,
+    };
+    let grid_size = model.puzzles[index].grid_size;
+    let rects = layout_rects(model.window_rect(app), model.puzzles.len());
+    let board = board_rect(rects[index], grid_size);
+    let cell_size = board.w() / grid_size.width as f32;
+    let centre = pt2(
+        board.left() + (ix as f32 + 0.5) * cell_size,
+        board.bottom() + (iy as f32 + 0.5) * cell_size,
+    );
+    let push = match to - from {
+        swipe if swipe.length() >= SWIPE_DISTANCE => swipe,
+        _ => (from - model.view_offset) / model.zoom - centre,
+    };
+    // Row 0 is the bottom row, so up shifts the column forward.
+    let shift = match push.x.abs() > push.y.abs() {
+        true => Shift::Row(iy, push.x.signum() as isize),
+        false => Shift::Column(ix, push.y.signum() as isize),
+    };
+    Some((index, shift))
+}
+
+/// Taps move the piece tapped, with the pieces between it and the empty
+/// space. Swipes anywhere slide the piece next to the empty space the way
+/// of the swipe. In the Loopover variant, taps and swipes shift the row or
+/// column of the piece, see `loopover_push`. The board touched first gets
+/// the focus, the focused one stays if the touch began beside the boards.
+/// Only the first finger down counts.
+fn touch_event(app: &App, model: &mut Model, touch: TouchEvent) {
+    match touch.phase {
+        TouchPhase::Started if model.touch.is_none() => {
+            model.touch = Some((touch.id, touch.position));
+        }
+        TouchPhase::Ended => {
+            let Some((_, start)) = model.touch.filter(|(id, _)| *id == touch.id) else {
+                return;
+            };
+            model.touch = None;
+            let hit = hit_test(app, model, start);
+            let index = hit.map_or(model.focused, |(index, _)| index);
+            let swipe = touch.position - start;
+            if model.puzzles[index].variant == Variant::Loopover {
+                if let Some((index, shift)) = loopover_push(app, model, start, touch.position) {
+                    model.focused = index;
+                    model.play_shift(index, shift);
+                }
+            } else if swipe.length() < SWIPE_DISTANCE {
+                if let Some((index, Some((ix, iy)))) = hit {
+                    model.focused = index;
+                    model.play_line(index, ix, iy);
+                }
+            } else {
+                model.focused = index;
+                match swipe.x.abs() > swipe.y.abs() {
+                    true => model.slide(swipe.x.signum() as isize, 0),
+                    false => model.slide(0, swipe.y.signum() as isize),
+                }
+            }
+        }
+        TouchPhase::Cancelled if model.touch.is_some_and(|(id, _)| id == touch.id) => {
+            model.touch = None;
+        }
+        _ => (),
+    }
+}
+
+/// Move the dragged piece along with the mouse at `point`, between its
+/// cell and the empty space.
+fn drag_to(app: &App, model: &mut Model, point: Point2) {
+    let rects = layout_rects(model.window_rect(app), model.puzzles.len());
+    let point = (point - model.view_offset) / model.zoom;
+    let Some(drag) = model.drag.as_mut() else {
+        return;
+    };
+    // the board may have changed under the piece, eg, with the keys
+    let (ix, iy) = drag.cell;
+    let Some(puzzle) = model
+        .puzzles
+        .get(drag.board)
+        .filter(|puzzle| puzzle.is_move_valid(ix, iy))
+    else {
+        model.drag = None;
+        return;
+    };
+    let cell_size =
+        board_rect(rects[drag.board], puzzle.grid_size).w() / puzzle.grid_size.width as f32;
+    let (empty_x, empty_y) = puzzle.empty_next_to(ix, iy).unwrap_or(puzzle.index_empty());
+    // Row 0 is the bottom row.
+    let direction = vec2(
+        empty_x as f32 - drag.cell.0 as f32,
+        empty_y as f32 - drag.cell.1 as f32,
+    );
+    let moved = point - drag.from;
+    drag.moved |= moved.length() > DRAG_THRESHOLD;
+    drag.offset = (moved.dot(direction) / cell_size).clamp(0.0, 1.0);
+}
+
+/// Drop the dragged piece on whichever of its cell and the empty space is
+/// closer. A click without a drag moves it too.
+fn drag_released(model: &mut Model) {
+    let Some(drag) = model.drag.take() else {
+        return;
+    };
+    if drag.board < model.puzzles.len() && (!drag.moved || drag.offset > 0.5) {
+        let (ix, iy) = drag.cell;
+        model.play_move(drag.board, ix, iy);
+    }
+}
+
+/// Index of the puzzle at `point` and the indices of the cell
+/// under it, if `point` is on the board.
+fn hit_test(app: &App, model: &Model, point: Point2) -> Option<(usize, Option<(usize, usize)>)> {
+    // back from the zoomed view to the layout of the boards
+    let point = (point - model.view_offset) / model.zoom;
+    let rects = layout_rects(model.window_rect(app), model.puzzles.len());
+    let index = rects.iter().position(|rect| rect.contains(point))?;
+    let grid_size = model.puzzles[index].grid_size;
+    let board = board_rect(rects[index], grid_size);
+    if !board.contains(point) {
+        return Some((index, None));
+    }
+    let cell_size = board.w() / grid_size.width as f32;
+    let ix = (((point.x - board.left()) / cell_size) as usize).min(grid_size.width - 1);
+    let iy = (((point.y - board.bottom()) / cell_size) as usize).min(grid_size.height - 1);
+    Some((index, Some((ix, iy))))
+}
+
+/// Show which way the piece under the mouse would slide,
+/// or that it can't move.
+fn update_cursor(app: &App, model: &Model, point: Point2) {
+    let icon = match hit_test(app, model, point) {
+        Some((index, Some((ix, iy)))) => {
+            let puzzle = &model.puzzles[index];
+            match puzzle.empty_in_line(ix, iy) {
+                // Row 0 is the bottom row.
+                Some((empty_x, _)) if empty_x > ix => CursorIcon::EResize,
+                Some((empty_x, _)) if empty_x < ix => CursorIcon::WResize,
+                Some((_, empty_y)) if empty_y > iy => CursorIcon::NResize,
+                Some(_) => CursorIcon::SResize,
+                None if puzzle.board[iy][ix] == 0 => CursorIcon::Default,
+                None => CursorIcon::NotAllowed,
+            }
+        }
+        _ => CursorIcon::Default,
+    };
+    app.window(model.window_id).unwrap().set_cursor_icon(icon);
+}
+
+fn event(app: &App, model: &mut Model, event: WindowEvent) {
+    if let KeyPressed(_) | MousePressed(_) | MouseMoved(_) | MouseWheel(..) | Touch(_) = event {
+        if model.note_input() {
+            return;
+        }
+    }
+    match event {
+        Focused => model.focus_changed(true),
+        Unfocused => {
+            // the key release would go to another window
+            model.peek = None;
+            model.focus_changed(false);
+        }
+        _ => (),
+    }
+    if let DroppedFile(path) = &event {
+        model.open_dropped(path);
+        return;
+    }
+    if model.palette.is_some() {
+        palette_event(app, model, event);
+        return;
+    }
+    #[cfg(feature = "settings")]
+    match event {
+        KeyPressed(Key::Escape) if model.settings.open => {
+            model.settings.open = false;
+            return;
+        }
+        KeyPressed(_) if model.settings.wants_keyboard() => return,
+        MousePressed(_) | MouseReleased(_) | MouseMoved(_) | MouseWheel(..)
+            if model.settings.wants_pointer() =>
+        {
+            return
+        }
+        _ => (),
+    }
+    if model.gallery.is_some() {
+        gallery_event(app, model, event);
+        return;
+    }
+    if model.stats.is_some() {
+        // any key or click closes the stats
+        if let KeyPressed(_) | MousePressed(_) | Touch(_) = event {
+            model.stats = None;
+            return;
+        }
+    }
+    match &mut model.state {
+        GameState::Menu(_) => {
+            menu_event(app, model, event);
+            return;
+        }
+        GameState::Scrambling => match event {
+            // the scramble runs to the end, only quitting is let through
+            KeyPressed(Key::Escape) => app.quit(),
+            KeyPressed(_) | MousePressed(MouseButton::Left | MouseButton::Right) | Touch(_) => {
+                return
+            }
+            _ => (),
+        },
+        GameState::Playing => (),
+        GameState::Paused => match event {
+            // only the pause key and quitting are let through
+            KeyPressed(key) => {
+                match model.keys.action(key, app.keys.mods) {
+                    Some(Action::Pause) => model.toggle_pause(),
+                    Some(Action::Quit) => app.quit(),
+                    _ => (),
+                }
+                return;
+            }
+            MousePressed(MouseButton::Left | MouseButton::Right) | Touch(_) => return,
+            _ => (),
+        },
+        GameState::Replaying(playback) => match event {
+            // the board is left where the playback is when it stops
+            KeyPressed(Key::Escape) => {
+                model.state = GameState::Playing;
+                return;
+            }
+            KeyPressed(key)
+                if model.keys.action(key, app.keys.mods) == Some(Action::ExportReplay) =>
+            {
+                let replay = playback.replay().clone();
+                model.export_replay_gif(replay);
+                return;
+            }
+            KeyPressed(key) => {
+                match key {
+                    Key::Space => playback.toggle_pause(),
+                    Key::Right => {
+                        let board = playback.board;
+                        if let (Some(step), Some(puzzle)) =
+                            (playback.step(), model.puzzles.get_mut(board))
+                        {
+                            puzzle.play_step(step);
+                        }
+                    }
+                    Key::Up => playback.change_speed(1),
+                    Key::Down => playback.change_speed(-1),
+                    _ => (),
+                }
+                return;
+            }
+            MousePressed(MouseButton::Left | MouseButton::Right) | Touch(_) => return,
+            _ => (),
+        },
+        GameState::Editing(_) => {
+            editor_event(app, model, event);
+            return;
+        }
+        GameState::Cropping(_) => {
+            crop_event(app, model, event);
+            return;
+        }
+        GameState::Klotski(_) => {
+            klotski_event(app, model, event);
+            return;
+        }
+        GameState::Solved(victory) => match event {
+            // Esc only closes the victory screen, other keys start a new round
+            KeyPressed(Key::Escape) => {
+                model.state = GameState::Playing;
+                return;
+            }
+            KeyPressed(_)
+            | Touch(TouchEvent {
+                phase: TouchPhase::Ended,
+                ..
+            }) => {
+                let (board, animated) = (victory.board, model.config.animated_scramble);
+                model.state = GameState::Playing;
+                model.puzzles[board].start_scramble(animated, &mut model.rng);
+                return;
+            }
+            // the solved board waits for a key, clicks would move it
+            MousePressed(MouseButton::Left | MouseButton::Right) | Touch(_) => return,
+            _ => (),
+        },
+    }
+    // the peek lasts while its key is held
+    if let Some(peek) = &model.peek {
+        match event {
+            KeyReleased(key) if peek.key == Some(key) => model.peek = None,
+            KeyPressed(_) if peek.key.is_none() => {
+                model.peek = None;
+                return;
+            }
+            _ => (),
+        }
+    }
+    let action = match event {
+        MousePressed(MouseButton::Middle) => {
+            model.pan_from = Some((app.mouse.position(), model.view_offset));
+            return;
+        }
+        MousePressed(button) => {
+            mouse_clicked(app.mouse.position(), button, app, model);
+            update_cursor(app, model, app.mouse.position());
+            return;
+        }
+        MouseReleased(MouseButton::Left) => {
+            drag_released(model);
+            return;
+        }
+        Touch(touch) => {
+            touch_event(app, model, touch);
+            return;
+        }
+        MouseReleased(MouseButton::Middle) => {
+            model.pan_from = None;
+            return;
+        }
+        MouseMoved(point) => {
+            if let Some((from, offset)) = model.pan_from.filter(|_| model.zoom > 1.0) {
+                model.view_offset = offset + (point - from);
+            }
+            drag_to(app, model, point);
+            update_cursor(app, model, point);
+            return;
+        }
+        MouseWheel(delta, _phase) if app.keys.mods.ctrl() => {
+            let steps = match delta {
+                MouseScrollDelta::LineDelta(_, y) => y,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32 / 50.0,
+            };
+            model.zoom_at(app.mouse.position(), steps);
+            return;
+        }
+        Moved(_) => {
+            save_window_state(app, model);
+            return;
+        }
+        KeyPressed(Key::P) if app.keys.mods.ctrl() => {
+            model.palette = Some(palette::Palette::default());
+            return;
+        }
+        KeyPressed(key) => match model.keys.action(key, app.keys.mods) {
+            Some(Action::Peek) => {
+                model.start_peek(app, Some(key));
+                return;
+            }
+            Some(action) => action,
+            None => return,
+        },
+        _ => return,
+    };
+    run_action(app, model, action);
+}
+
+/// Action run by `key` with the modifiers `mods`, see `palette::Action::key`.
+fn run_action(app: &App, model: &mut Model, action: Action) {
+    // the palette may run actions while paused, only those not playing go
+    let allowed = matches!(
+        action,
+        Action::Pause | Action::Quit | Action::Minimize | Action::Fullscreen
+    );
+    if matches!(model.state, GameState::Paused) && !allowed {
+        return;
+    }
+    match action {
+        Action::Reset => model.puzzle_mut().reset(),
+        Action::SlideUp => model.slide(0, 1),
+        Action::SlideDown => model.slide(0, -1),
+        Action::SlideLeft => model.slide(-1, 0),
+        Action::SlideRight => model.slide(1, 0),
+        Action::ToggleNumbers => model.flag_show_numbers = !model.flag_show_numbers,
+        Action::ToggleOutline => model.flag_outline = !model.flag_outline,
+        Action::ToggleThumbnail => model.flag_thumbnail = !model.flag_thumbnail,
+        Action::ToggleGenerated => model.puzzle_mut().toggle_generated(),
+        Action::ToggleColors => model.puzzle_mut().toggle_colors(),
+        Action::NewPicture => {
+            if !model.puzzle_mut().new_procedural() {
+                let message = "Pictures are made up when the images folder is empty";
+                model.show_toast(String::from(message));
+            }
+        }
+        Action::ToggleJigsaw => {
+            model.puzzle_mut().toggle_jigsaw();
+            let message = match model.puzzle().flag_jigsaw {
+                true => "Jigsaw edges on",
+                false => "Jigsaw edges off",
+            };
+            model.show_toast(String::from(message));
+        }
+        Action::ToggleRotation => {
+            model.puzzle_mut().toggle_rotation();
+            let message = match model.puzzle().flag_rotation {
+                true => "Rotation on, from the next scramble",
+                false => "Rotation off",
+            };
+            model.show_toast(String::from(message));
+        }
+        Action::ToggleLoopover => {
+            model.puzzle_mut().toggle_loopover();
+            let message = match model.puzzle().variant {
+                Variant::Loopover => "Loopover on, rows and columns wrap around",
+                Variant::Sliding => "Loopover off",
+            };
+            model.show_toast(String::from(message));
+        }
+        Action::RotatePiece => {
+            if let Some((index, Some((ix, iy)))) = hit_test(app, model, app.mouse.position()) {
+                model.focused = index;
+                model.play_rotation(index, ix, iy);
+            }
+        }
+        Action::CycleNumbering => {
+            model.numbering = model.numbering.next();
+            model.flag_show_numbers = true;
+            model.show_toast(String::from(model.numbering.name()));
+        }
+        Action::NextImage => model.next_image(),
+        Action::PreviousImage => model.previous_image(),
+        Action::RandomImage => model.random_image(),
+        Action::Scramble => model.scramble(),
+        Action::NextCollection => model.next_collection(),
+        Action::NextLayout => model.next_layout(app),
+        Action::GrowGrid => model.change_grid_size(1),
+        Action::ShrinkGrid => model.change_grid_size(-1),
+        Action::ExportHistory => export_history(model),
+        Action::ExportPicture => export_picture(model),
+        Action::ExportSheet => export_sheet(model),
+        Action::Minimize => minimize(app, model),
+        Action::ToggleBorderless => model.toggle_borderless(app),
+        Action::ToggleAlwaysOnTop => model.toggle_always_on_top(app),
+        Action::Challenge => {
+            let challenge = model.puzzles[model.focused].new_challenge(&mut model.rng);
+            println!("Challenge code: {challenge}");
+            model.show_toast(format!("Challenge {challenge}"));
+            show_challenge_qr(app, model);
+        }
+        Action::ControlPanel => panel::open(app, model),
+        Action::Fullscreen => {
+            let window = app.window(model.window_id).unwrap();
+            window.set_fullscreen(!window.is_fullscreen());
+        }
+        Action::Screenshot => take_screenshot(app, model),
+        Action::ResetZoom => {
+            model.zoom = 1.0;
+            model.view_offset = Vec2::ZERO;
+        }
+        Action::Gallery => {
+            let proxy = app.create_proxy();
+            model.gallery = Some(gallery::Gallery::new(
+                model.puzzle(),
+                &history::load(),
+                proxy,
+            ));
+        }
+        Action::Stats => model.stats = Some(stats::Stats::new(&history::load())),
+        Action::Hint => match model.puzzle_mut().next_hint() {
+            Ok(level) => {
+                let puzzle = model.puzzle();
+                let (ix, iy) = puzzle.index_of(puzzle.hint_piece().unwrap_or(0));
+                // in board sizes from the centre of the board
+                let GridSize { width, height } = puzzle.grid_size;
+                let offset = vec2(
+                    ix as f32 - width as f32 / 2.0,
+                    iy as f32 - height as f32 / 2.0,
+                );
+                let position = (offset + 0.5) / puzzle.grid_size.longest() as f32;
+                let message = format!("Hint {level}, cost so far {}", puzzle.hint_cost);
+                model.particles.sparkle(model.focused, position);
+                model.show_toast(message);
+            }
+            Err(message) => model.show_toast(message),
+        },
+        Action::Bookmark => {
+            let slot = model.puzzle_mut().bookmark();
+            model.show_toast(format!("Bookmark {slot} saved"));
+        }
+        Action::RestoreBookmark => match model.puzzle_mut().restore_bookmark() {
+            Some(slot) => model.show_toast(format!("Back to bookmark {slot}")),
+            None => model.show_toast(String::from("No bookmarks")),
+        },
+        Action::Undo => {
+            if !model.puzzle_mut().undo() {
+                model.show_toast(String::from("Nothing to undo"));
+            }
+        }
+        Action::Redo => {
+            if !model.puzzle_mut().redo() {
+                model.show_toast(String::from("Nothing to redo"));
+            }
+        }
+        Action::AutoSolve => model.toggle_autosolve(),
+        Action::Pause => model.toggle_pause(),
+        Action::Peek => model.start_peek(app, None),
+        Action::Menu => model.state = GameState::Menu(Menu::default()),
+        #[cfg(feature = "settings")]
+        Action::Settings => model.settings.open = !model.settings.open,
+        #[cfg(feature = "sound")]
+        Action::ToggleSound => model.toggle_sound(),
+        #[cfg(feature = "sound")]
+        Action::NextTrack => model.next_track(),
+        #[cfg(feature = "sound")]
+        Action::ToggleMusic => model.toggle_music(),
+        Action::SaveGame => model.save_game(),
+        Action::LoadGame => model.load_game(),
+        Action::Editor => {
+            model.autosolve = None;
+            model.state = GameState::Editing(Editor::new(model.focused));
+        }
+        Action::CropImage => {
+            let puzzle = model.puzzle();
+            if puzzle.stage != Stage::Free {
+                model.show_toast(String::from("Crop the image before scrambling"));
+            } else if puzzle.flag_generated || puzzle.flag_colors {
+                model.show_toast(String::from("No image to crop"));
+            } else {
+                let cropper = crop::Cropper::new(
+                    app,
+                    model.focused,
+                    &puzzle.image_original,
+                    puzzle.grid_size,
+                    puzzle.crop,
+                );
+                model.state = GameState::Cropping(cropper);
+            }
+        }
+        Action::Klotski => {
+            model.autosolve = None;
+            model.state = GameState::Klotski(klotski_game::KlotskiGame::new(0));
+        }
+        Action::CopyPosition => model.copy_position(),
+        Action::PastePosition => model.paste_position(),
+        Action::Replay => match replay::Replay::latest() {
+            Ok(replay) => model.start_playback(replay),
+            Err(e) => model.show_toast(e),
+        },
+        Action::ExportReplay => match replay::Replay::latest() {
+            Ok(replay) => model.export_replay_gif(replay),
+            Err(e) => model.show_toast(e),
+        },
+        #[cfg(feature = "twitch")]
+        Action::TwitchMode => {
+            if let Some(chat) = model.twitch.as_mut() {
+                chat.toggle_mode();
+                let message = format!("#{}: {:?} mode", chat.channel, chat.mode);
+                model.show_toast(message);
+            }
+        }
+        #[cfg(feature = "record")]
+        Action::Record => toggle_recording(app, model),
+        #[cfg(feature = "url")]
+        Action::PasteUrl => match clipboard::paste() {
+            _ if model.download.is_some() => {
+                model.show_toast(String::from("Already downloading an image"))
+            }
+            Ok(text) if web::is_url(&text) => {
+                model.download = Some((download_image(&text, app.create_proxy()), false));
+                model.show_toast(String::from("Downloading the image..."));
+            }
+            Ok(_) => model.show_toast(String::from("No URL on the clipboard")),
+            Err(e) => {
+                println!("Error reading the clipboard: {e}");
+                model.show_toast(String::from("No URL on the clipboard"));
+            }
+        },
+        #[cfg(feature = "url")]
+        Action::Surprise => {
+            if model.download.is_none() {
+                let puzzle = model.puzzle();
+                let (width, height) = puzzle::image_dimensions(puzzle.grid_size, puzzle.img_size());
+                let url = model
+                    .config
+                    .surprise_url
+                    .replace("{width}", &width.to_string())
+                    .replace("{height}", &height.to_string());
+                model.download = Some((download_image(&url, app.create_proxy()), true));
+            }
+        }
+        #[cfg(feature = "webcam")]
+        Action::Webcam => {
+            if model.webcam.is_none() {
+                let device = &model.config.webcam_device;
+                model.webcam = Some(webcam::snapshot(device, app.create_proxy()));
+                model.show_toast(String::from("Smile!"));
+            }
+        }
+        Action::Quit => app.quit(),
+    }
+}
+
+/// Clicks, or taps, swap two pieces in the editor, `Enter` plays the
+/// position and `Esc` leaves the editor with the board as it is.
+fn editor_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let point = match event {
+        MousePressed(MouseButton::Left) => app.mouse.position(),
+        Touch(TouchEvent {
+            phase: TouchPhase::Ended,
+            position,
+            ..
+        }) => position,
+        KeyPressed(Key::Escape) => {
+            model.state = GameState::Playing;
+            return;
+        }
+        KeyPressed(Key::Return | Key::NumpadEnter) => {
+            let GameState::Editing(editor) = &model.state else {
+                return;
+            };
+            let puzzle = &mut model.puzzles[editor.board];
+            if !puzzle.board.is_solvable() {
+                model.show_toast(String::from("This position can't be solved"));
+                return;
+            }
+            let board = puzzle.board.clone();
+            puzzle.start_from_position(board);
+            model.state = GameState::Playing;
+            return;
+        }
+        _ => return,
+    };
+    let hit = hit_test(app, model, point);
+    let GameState::Editing(editor) = &mut model.state else {
+        return;
+    };
+    let Some((index, Some(cell))) = hit.filter(|&(index, _)| index == editor.board) else {
+        editor.selected = None;
+        return;
+    };
+    match editor.selected.take() {
+        Some(selected) if selected != cell => model.puzzles[index].swap_cells(selected, cell),
+        Some(_) => (),
+        None => editor.selected = Some(cell),
+    }
+}
+
+/// Drags, the mouse wheel and the keys move and resize the frame of the
+/// crop screen, `Enter` plays the region framed and `Esc` leaves.
+fn crop_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let win = model.window_rect(app);
+    let GameState::Cropping(cropper) = &mut model.state else {
+        return;
+    };
+    match event {
+        KeyPressed(key) => match (model.keys.action(key, app.keys.mods), key) {
+            (Some(Action::Quit), _) => app.quit(),
+            (Some(Action::CropImage), _) | (_, Key::Escape) => model.state = GameState::Playing,
+            (Some(Action::GrowGrid), _) => cropper.resize(1.0),
+            (Some(Action::ShrinkGrid), _) => cropper.resize(-1.0),
+            (_, Key::Up) => cropper.nudge(0.0, 1.0),
+            (_, Key::Down) => cropper.nudge(0.0, -1.0),
+            (_, Key::Left) => cropper.nudge(-1.0, 0.0),
+            (_, Key::Right) => cropper.nudge(1.0, 0.0),
+            (_, Key::R) => cropper.reset(),
+            (_, Key::Return | Key::NumpadEnter) => {
+                let (board, region) = (cropper.board, cropper.region());
+                model.state = GameState::Playing;
+                model.puzzles[board].set_crop(Some(region));
+                model.show_toast(String::from("Image cropped"));
+            }
+            _ => (),
+        },
+        MousePressed(MouseButton::Left) => cropper.start_drag(app.mouse.position()),
+        MouseMoved(point) => cropper.drag_to(win, point),
+        MouseReleased(MouseButton::Left) => cropper.dragged = None,
+        MouseWheel(delta, _phase) => {
+            let steps = match delta {
+                MouseScrollDelta::LineDelta(_, y) => y,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32 / 50.0,
+            };
+            cropper.resize(steps);
+        }
+        Touch(TouchEvent {
+            phase, position, ..
+        }) => match phase {
+            TouchPhase::Started => cropper.start_drag(position),
+            TouchPhase::Moved => cropper.drag_to(win, position),
+            _ => cropper.dragged = None,
+        },
+        _ => (),
+    }
+}
+
+/// The arrows slide the block picked in the Klotski mode, clicks and taps
+/// pick a block or slide it, drags and swipes slide the block they start
+/// on. `Esc`, or `Ctrl+K` again, leaves it for the boards.
+fn klotski_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let win = model.window_rect(app);
+    let GameState::Klotski(game) = &mut model.state else {
+        return;
+    };
+    let moved = match event {
+        KeyPressed(key) => match (model.keys.action(key, app.keys.mods), key) {
+            (Some(Action::Quit), _) => return app.quit(),
+            (Some(Action::Klotski), _) | (_, Key::Escape) => {
+                model.state = GameState::Playing;
+                return;
+            }
+            (_, Key::Up) => game.slide_selected(0, 1),
+            (_, Key::Down) => game.slide_selected(0, -1),
+            (_, Key::Left) => game.slide_selected(-1, 0),
+            (_, Key::Right) => game.slide_selected(1, 0),
+            (_, Key::Tab) => {
+                game.next_layout();
+                false
+            }
+            (_, Key::R) => {
+                game.restart();
+                false
+            }
+            _ => false,
+        },
+        MousePressed(MouseButton::Left) => {
+            game.pressed = Some(app.mouse.position());
+            false
+        }
+        Touch(TouchEvent {
+            phase: TouchPhase::Started,
+            position,
+            ..
+        }) => {
+            game.pressed = Some(position);
+            false
+        }
+        MouseReleased(MouseButton::Left)
+        | Touch(TouchEvent {
+            phase: TouchPhase::Ended,
+            ..
+        }) => {
+            let point = match event {
+                Touch(touch) => touch.position,
+                _ => app.mouse.position(),
+            };
+            let Some(start) = game.pressed.take() else {
+                return;
+            };
+            let Some(cell) = game.cell_at(win, start) else {
+                return;
+            };
+            let swipe = point - start;
+            if swipe.length() < SWIPE_DISTANCE {
+                game.click(game.cell_at(win, point).unwrap_or(cell))
+            } else if swipe.x.abs() > swipe.y.abs() {
+                game.push(cell, swipe.x.signum() as isize, 0)
+            } else {
+                game.push(cell, 0, swipe.y.signum() as isize)
+            }
+        }
+        _ => false,
+    };
+    if !moved {
+        return;
+    }
+    let solved = game.is_solved().then(|| game.moves());
+    #[cfg(feature = "sound")]
+    model.play_sound(sound::Effect::Slide);
+    if let Some(moves) = solved {
+        model.show_toast(format!("Klotski solved in {moves} moves"));
+    }
+}
+
+/// Keys and clicks pick the grid size and the image in the start menu.
+fn menu_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let win = model.window_rect(app);
+    let GameState::Menu(menu) = &mut model.state else {
+        return;
+    };
+    let (row, delta) = match event {
+        KeyPressed(Key::Up) => return menu.move_selection(-1),
+        KeyPressed(Key::Down) => return menu.move_selection(1),
+        KeyPressed(Key::Left) => (menu.row(), -1),
+        KeyPressed(Key::Right) => (menu.row(), 1),
+        KeyPressed(Key::Return | Key::NumpadEnter) => (Row::Play, 0),
+        KeyPressed(Key::Escape | Key::Home) => {
+            model.state = GameState::Playing;
+            return;
+        }
+        Touch(TouchEvent {
+            phase: TouchPhase::Ended,
+            position,
+            ..
+        }) => match menu.hit(win, position) {
+            Some((index, increase)) => {
+                menu.selected = index;
+                (menu.row(), if increase { 1 } else { -1 })
+            }
+            None => return,
+        },
+        MousePressed(MouseButton::Left) => match menu.hit(win, app.mouse.position()) {
+            Some((index, increase)) => {
+                menu.selected = index;
+                (menu.row(), if increase { 1 } else { -1 })
+            }
+            None => return,
+        },
+        _ => return,
+    };
+    model.menu_change(row, delta);
+}
+
+/// Clicks or the arrow keys and `Enter` pick an image in the gallery
+/// while it is open.
+fn gallery_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let win = model.window_rect(app);
+    let count = model.puzzle().image_list.len();
+    let Some(gallery) = model.gallery.as_mut() else {
+        return;
+    };
+    let picked = match event {
+        MousePressed(MouseButton::Left) => gallery.hit(win, app.mouse.position()),
+        KeyPressed(Key::Return | Key::NumpadEnter) => Some(gallery.selected),
+        _ => None,
+    };
+    if let Some(index) = picked.filter(|&i| i < count) {
+        model.gallery = None;
+        model.state = GameState::Playing;
+        model.select_image(index);
+        model.scramble();
+        return;
+    }
+    match event {
+        MouseMoved(point) => {
+            if let Some(index) = gallery.hit(win, point) {
+                gallery.selected = index;
+            }
+        }
+        KeyPressed(Key::Left) => gallery.move_selection(-1, 0, win),
+        KeyPressed(Key::Right) => gallery.move_selection(1, 0, win),
+        KeyPressed(Key::Up) => gallery.move_selection(0, -1, win),
+        KeyPressed(Key::Down) => gallery.move_selection(0, 1, win),
+        MouseWheel(delta, _phase) => {
+            let dy = match delta {
+                MouseScrollDelta::LineDelta(_, y) => y * 40.0,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32,
+            };
+            gallery.scroll_by(dy, win);
+        }
+        KeyPressed(Key::G | Key::Escape) => model.gallery = None,
+        _ => (),
+    }
+}
+
+/// Typing goes to the command palette while it is open.
+fn palette_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let Some(palette) = model.palette.as_mut() else {
+        return;
+    };
+    match event {
+        ReceivedCharacter(c) => palette.type_char(c),
+        KeyPressed(Key::Back) => palette.backspace(),
+        KeyPressed(Key::Up) => palette.move_selection(-1),
+        KeyPressed(Key::Down) => palette.move_selection(1),
+        KeyPressed(Key::Escape) => model.palette = None,
+        KeyPressed(Key::P) if app.keys.mods.ctrl() => model.palette = None,
+        KeyPressed(Key::Return | Key::NumpadEnter) => {
+            let action = palette.selection();
+            model.palette = None;
+            if let Some(action) = action {
+                run_action(app, model, action);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(BLACK);
+
+    let draw = app.draw();
+    let win = model.window_rect(app);
+
+    // the demo replaces the boards of the player
+    if let Some(attract) = &model.attract {
+        render::draw_puzzle(&draw, &attract.puzzle, win, None, false);
+        let pad = win.h() * PAD_HEIGHT_FACTOR;
+        draw.text("Press any key to play")
+            .font_size((pad / 3.0).max(8.0) as u32)
+            .x_y(0.0, win.top() - pad / 2.0)
+            .w(win.w())
+            .center_justify()
+            .color(WHITE);
+        draw.to_frame(app, &frame).unwrap();
+        return;
+    }
+
+    // the boards are zoomed and panned, the overlays are not
+    let boards = draw
+        .translate(model.view_offset.extend(0.0))
+        .scale(model.zoom);
+    let rects = layout_rects(win, model.puzzles.len());
+    for (i, (puzzle, rect)) in model.puzzles.iter().zip(&rects).enumerate() {
+        let numbers = model.flag_show_numbers.then_some(model.numbering);
+        render::draw_puzzle(&boards, puzzle, *rect, numbers, model.flag_outline);
+        let dragged = |d: &&render::Drag| d.board == i && puzzle.is_move_valid(d.cell.0, d.cell.1);
+        if let Some(drag) = model.drag.as_ref().filter(dragged) {
+            render::draw_drag(&boards, puzzle, drag, *rect);
+        }
+        if let Some((_, cell, at)) = model.rejected.filter(|(board, ..)| *board == i) {
+            render::draw_rejected(&boards, puzzle, cell, at.elapsed(), *rect);
+        }
+        if let GameState::Editing(editor) = &model.state {
+            if editor.board == i {
+                state::draw_editor(&boards, editor, puzzle, *rect);
+            }
+        }
+        render::draw_status_bar(&boards, puzzle, *rect);
+        render::draw_hud(&boards, puzzle, *rect);
+        let paused = match model.state {
+            GameState::Paused => true,
+            _ => {
+                model.flag_focus_paused
+                    && model.config.dim_when_paused
+                    && puzzle.paused_at.is_some()
+            }
+        };
+        if paused {
+            render::draw_paused(&boards, board_rect(*rect, puzzle.grid_size));
+        }
+    }
+    if let Some(peek) = model.peek.as_ref().filter(|p| p.board < rects.len()) {
+        let board = board_rect(rects[peek.board], model.puzzles[peek.board].grid_size);
+        boards.texture(&peek.texture).xy(board.xy()).wh(board.wh());
+    }
+    if let GameState::Solved(victory) = &model.state {
+        let grid_size = model.puzzles[victory.board].grid_size;
+        render::draw_victory(
+            &boards,
+            victory,
+            board_rect(rects[victory.board], grid_size),
+        );
+    }
+    model.particles.draw(&boards, &rects);
+
+    // frame the focused board when there are several
+    let rect = rects[model.focused];
+    let pad = rect.h() * PAD_HEIGHT_FACTOR;
+    if model.puzzles.len() > 1 {
+        let board = board_rect(rect, model.puzzle().grid_size);
+        boards
+            .rect()
+            .xy(board.xy())
+            .wh(board.wh() + pad / 2.0)
+            .no_fill()
+            .stroke(WHITE)
+            .stroke_weight(2.0);
+    }
+
+    // the target image in the top right corner of the window
+    if let Some(texture) = model
+        .puzzle()
+        .thumbnail
+        .as_ref()
+        .filter(|_| model.flag_thumbnail)
+    {
+        let board = board_rect(rect, model.puzzle().grid_size);
+        let wh = board.wh() * THUMBNAIL_FACTOR;
+        let corner = win.top_right() - wh / 2.0 - pad / 4.0;
+        draw.texture(texture).xy(corner).wh(wh);
+        draw.rect()
+            .xy(corner)
+            .wh(wh)
+            .no_fill()
+            .stroke(WHITE)
+            .stroke_weight(1.0);
+    }
+
+    // turning dots in the top left corner while an image downloads
+    #[cfg(feature = "url")]
+    if model.download.is_some() {
+        let radius = pad / 4.0;
+        let corner = win.top_left() + vec2(radius, -radius) * 2.0;
+        render::draw_spinner(&draw, corner, radius, app.time);
+    }
+
+    if let GameState::Menu(menu) = &model.state {
+        state::draw_menu(&draw, menu, model.puzzle(), win);
+    }
+
+    if let GameState::Cropping(cropper) = &model.state {
+        cropper.draw(&draw, win);
+    }
+
+    if let GameState::Klotski(game) = &model.state {
+        game.draw(&draw, win);
+    }
+
+    if let Some(gallery) = &model.gallery {
+        gallery.draw(&draw, win);
+    }
+
+    if let Some(stats) = &model.stats {
+        stats.draw(&draw, win);
+    }
+
+    // draw the toast message in the top padding of the focused board,
+    // unless the control panel shows it
+    // the status of a replay played back stays there instead
+    let status = match &model.state {
+        GameState::Replaying(playback) => Some(playback.status()),
+        _ => model.toast.as_ref().map(|(message, _)| message.clone()),
+    };
+    if let (Some(message), None) = (&status, &model.panel) {
+        draw.text(message)
+            .font_size((pad / 3.0).max(8.0) as u32)
+            .x_y(rect.x(), rect.top() - pad / 2.0)
+            .w(rect.w())
+            .center_justify()
+            .color(WHITE);
+    }
+
+    if let Some(palette) = &model.palette {
+        palette::draw(&draw, palette, &model.keys, win);
+    }
+
+    draw.to_frame(app, &frame).unwrap();
+    #[cfg(feature = "settings")]
+    model.settings.draw(&frame);
+}
+
+/// Collection and index of the image at `path`, if it is in one of the
+/// `collections`.
+fn find_image(collections: &[packs::Collection], path: &Path) -> Option<(usize, usize)> {
+    let path = fs::canonicalize(path).ok()?;
+    collections.iter().enumerate().find_map(|(c, collection)| {
+        let same = |image: &PathBuf| fs::canonicalize(image).is_ok_and(|image| image == path);
+        let index = collection.images.iter().position(same)?;
+        Some((c, index))
+    })
+}
+
+/// Open a popup window showing the current challenge as a QR code,
+/// so it can be picked up with a phone. An already open popup just
+/// shows the new challenge.
+fn show_challenge_qr(app: &App, model: &mut Model) {
+    if model.qr_window.is_some() {
+        return;
+    }
+    let window = app
+        .new_window()
+        .size(START_WINDOW_SIZE, START_WINDOW_SIZE + START_WINDOW_SIZE / 5)
+        .title("Challenge")
+        .view(view_challenge_qr)
+        .closed(challenge_qr_closed)
+        .build();
+    match window {
+        Ok(id) => model.qr_window = Some(id),
+        Err(e) => println!("Error opening the challenge window: {e}"),
+    }
+}
+
+fn challenge_qr_closed(_app: &App, model: &mut Model) {
+    model.qr_window = None;
+}
+
+fn view_challenge_qr(app: &App, model: &Model, frame: Frame) {
+    frame.clear(WHITE);
+    let Some((challenge, (width, modules))) = model
+        .puzzle()
+        .challenge
+        .and_then(|challenge| Some((challenge, challenge.qr_modules()?)))
+    else {
+        return;
+    };
+
+    let draw = app.draw();
+    let win = frame.rect();
+    let text_height = win.h() / 6.0;
+    // Leave a quiet zone of 4 modules around the code.
+    let module = win.w().min(win.h() - text_height) / (width + 8) as f32;
+    let left = -module * width as f32 / 2.0;
+    let top = win.top() - (win.h() - text_height - module * width as f32) / 2.0;
+    for (i, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+        let (row, col) = (i / width, i % width);
+        draw.rect()
+            .x_y(
+                left + (col as f32 + 0.5) * module,
+                top - (row as f32 + 0.5) * module,
+            )
+            .w_h(module, module)
+            .color(BLACK);
+    }
+    draw.text(&challenge.to_string())
+        .font_size((text_height / 3.0) as u32)
+        .x_y(0.0, win.bottom() + text_height / 2.0)
+        .w(win.w())
+        .center_justify()
+        .color(BLACK);
+
+    draw.to_frame(app, &frame).unwrap();
 }
@@ -0,0 +1,148 @@
+//! Styles of the labels drawn on the pieces, cycled with `Shift+N`.
+
+use nannou::prelude::*;
+
+/// Largest grid size drawn with any label style, see `Numbering::compact`.
+static COMPACT_GRID_SIZE: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Numbering {
+    #[default]
+    Decimal,
+    Hexadecimal,
+    Letters,
+    Roman,
+    Pips,
+}
+
+impl Numbering {
+    /// The style after this one.
+    pub fn next(self) -> Self {
+        match self {
+            Numbering::Decimal => Numbering::Hexadecimal,
+            Numbering::Hexadecimal => Numbering::Letters,
+            Numbering::Letters => Numbering::Roman,
+            Numbering::Roman => Numbering::Pips,
+            Numbering::Pips => Numbering::Decimal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Numbering::Decimal => "Numbers",
+            Numbering::Hexadecimal => "Hexadecimal",
+            Numbering::Letters => "Letters",
+            Numbering::Roman => "Roman numerals",
+            Numbering::Pips => "Pips",
+        }
+    }
+
+    /// Style used on boards with `grid_size` cells on the longest side:
+    /// past `COMPACT_GRID_SIZE`, the long roman numerals and pips don't fit
+    /// the cells and are drawn as letters, the most compact labels.
+    pub fn compact(self, grid_size: usize) -> Self {
+        match self {
+            Numbering::Roman | Numbering::Pips if grid_size > COMPACT_GRID_SIZE => {
+                Numbering::Letters
+            }
+            _ => self,
+        }
+    }
+
+    /// Text of the label of `piece`, `None` for pips which are drawn.
+    pub fn label(self, piece: usize) -> Option<String> {
+        match self {
+            Numbering::Decimal => Some(piece.to_string()),
+            Numbering::Hexadecimal => Some(format!("{piece:X}")),
+            Numbering::Letters => Some(letters(piece)),
+            Numbering::Roman => Some(roman(piece)),
+            Numbering::Pips => None,
+        }
+    }
+
+    /// Draw the label of `piece` on the cell centred at `(x, y)`.
+    pub fn draw(self, draw: &Draw, piece: usize, x: f32, y: f32, cell_size: f32) {
+        if piece == 0 {
+            return;
+        }
+        match self.label(piece) {
+            Some(text) => {
+                // long labels are made smaller to fit the cell
+                let scale = match text.len() {
+                    0..=2 => 0.5,
+                    3 => 0.4,
+                    _ => 0.3,
+                };
+                draw.text(&text)
+                    .font_size(((cell_size * scale) as u32).max(1))
+                    .x_y(x, y)
+                    .w_h(cell_size, cell_size)
+                    .align_text_middle_y()
+                    .center_justify()
+                    .color(BLACK);
+            }
+            None => draw_pips(draw, piece, x, y, cell_size),
+        }
+    }
+}
+
+/// Spreadsheet column style: A to Z, then AA, AB...
+fn letters(piece: usize) -> String {
+    let mut n = piece;
+    let mut text = vec![];
+    while n > 0 {
+        n -= 1;
+        text.push((b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    text.iter().rev().collect()
+}
+
+fn roman(piece: usize) -> String {
+    let symbols = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut n = piece;
+    let mut text = String::new();
+    for (value, symbol) in symbols {
+        while n >= value {
+            text.push_str(symbol);
+            n -= value;
+        }
+    }
+    text
+}
+
+/// `piece` dots in a square, filled row by row from the top.
+fn draw_pips(draw: &Draw, piece: usize, x: f32, y: f32, cell_size: f32) {
+    let side = (piece as f32).sqrt().ceil() as usize;
+    let rows = piece.div_ceil(side);
+    let spacing = cell_size * 0.7 / side as f32;
+    let radius = spacing * 0.35;
+    for i in 0..piece {
+        let (col, row) = (i % side, i / side);
+        // the last row is centred when it is not full
+        let in_row = match row == rows - 1 {
+            true => piece - row * side,
+            false => side,
+        };
+        let dx = (col as f32 - (in_row - 1) as f32 / 2.0) * spacing;
+        let dy = ((rows - 1) as f32 / 2.0 - row as f32) * spacing;
+        draw.ellipse()
+            .x_y(x + dx, y + dy)
+            .radius(radius)
+            .color(BLACK);
+    }
+}
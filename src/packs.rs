@@ -0,0 +1,138 @@
+//! Community puzzle packs.
+//!
+//! A pack is a zip file with images and a `manifest.json`:
+//!
+//! ```json
+//! {
+//!     "name": "Lighthouses",
+//!     "images": [
+//!         { "file": "cape.jpg", "crop": [120, 0, 800, 800], "sizes": [3, 4], "seeds": [42, 7] },
+//!         { "file": "harbour.png" }
+//!     ]
+//! }
+//! ```
+//!
+//! `crop` is `[x, y, width, height]` in pixels of the source image, `sizes` are the
+//! recommended grid sizes and `seeds` are curated scrambles. All three are optional.
+//! Imported packs are extracted into the `packs` folder of the profile and show up
+//! as a collection of images next to the `images` folder.
+
+use std::fs::{self, File};
+use std::io::{self, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::profile;
+
+/// Folder in the profile where packs are extracted.
+static PACKS_DIR: &str = "packs";
+
+/// Name of the manifest inside a pack.
+static MANIFEST: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub images: Vec<PackImage>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PackImage {
+    pub file: String,
+    #[serde(default)]
+    pub crop: Option<[u32; 4]>,
+    #[serde(default)]
+    pub sizes: Vec<usize>,
+    #[serde(default)]
+    pub seeds: Vec<u64>,
+}
+
+/// A named list of images, either the `images` folder or an imported pack.
+pub struct Collection {
+    pub name: String,
+    pub images: Vec<PathBuf>,
+    pub info: Vec<Option<PackImage>>, // Pack details of each image, if any
+}
+
+impl Collection {
+    /// Collection of plain images, without any pack details.
+    pub fn from_images(name: &str, images: Vec<PathBuf>) -> Self {
+        let info = vec![None; images.len()];
+        Collection {
+            name: name.to_string(),
+            images,
+            info,
+        }
+    }
+}
+
+/// Extract the pack at `zip_path` into the profile and return its name.
+/// Only the images listed in the manifest are extracted.
+pub fn import(zip_path: &Path) -> io::Result<String> {
+    let mut archive = zip::ZipArchive::new(File::open(zip_path)?)?;
+    let manifest: Manifest = serde_json::from_reader(archive.by_name(MANIFEST)?)?;
+    if manifest.name.trim().is_empty() || manifest.images.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "pack needs a name and images",
+        ));
+    }
+
+    let dir = profile::profile_path(PACKS_DIR).join(safe_name(&manifest.name));
+    fs::create_dir_all(&dir)?;
+    for image in &manifest.images {
+        let target = dir.join(safe_name(&image.file));
+        let mut entry = archive.by_name(&image.file)?;
+        io::copy(&mut entry, &mut File::create(&target)?)?;
+        debug!("Extracted {} to {}", image.file, target.display());
+    }
+    let manifest_file = File::create(dir.join(MANIFEST))?;
+    serde_json::to_writer_pretty(manifest_file, &manifest)?;
+    Ok(manifest.name)
+}
+
+/// Load all the packs imported in the profile.
+pub fn load_collections() -> Vec<Collection> {
+    let mut collections = vec![];
+    let Ok(dirs) = fs::read_dir(profile::profile_dir().join(PACKS_DIR)) else {
+        return collections;
+    };
+    for dir in dirs.flatten() {
+        let path = dir.path().join(MANIFEST);
+        let manifest = match read_manifest(&path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                println!("Error reading pack manifest {}: {e}", path.display());
+                continue;
+            }
+        };
+        let (images, info) = manifest
+            .images
+            .into_iter()
+            .map(|image| (dir.path().join(safe_name(&image.file)), Some(image)))
+            .unzip();
+        collections.push(Collection {
+            name: manifest.name,
+            images,
+            info,
+        });
+    }
+    collections.sort_by(|a, b| a.name.cmp(&b.name));
+    collections
+}
+
+fn read_manifest(path: &Path) -> io::Result<Manifest> {
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+/// Keep only the file name part and drop anything that could
+/// escape the pack folder.
+fn safe_name(name: &str) -> String {
+    let name = Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    name.replace(['/', '\\', ':'], "_")
+}
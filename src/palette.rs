@@ -0,0 +1,381 @@
+//! Command palette, opened with `Ctrl+P`, listing every action of the game
+//! so none of them needs its key to be remembered. Typing filters the list
+//! with a fuzzy match, the arrows pick a line and `Enter` runs it.
+
+use nannou::prelude::*;
+
+use crate::keys::KeyMap;
+
+/// Most lines shown at once.
+static MAX_LINES: usize = 10;
+
+/// Everything the player can do from the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Reset,
+    Scramble,
+    SlideUp,
+    SlideDown,
+    SlideLeft,
+    SlideRight,
+    ToggleNumbers,
+    CycleNumbering,
+    ToggleOutline,
+    ToggleThumbnail,
+    ToggleGenerated,
+    ToggleColors,
+    NewPicture,
+    ToggleJigsaw,
+    ToggleRotation,
+    ToggleLoopover,
+    Klotski,
+    RotatePiece,
+    NextImage,
+    PreviousImage,
+    RandomImage,
+    CropImage,
+    NextCollection,
+    NextLayout,
+    GrowGrid,
+    ShrinkGrid,
+    ExportHistory,
+    ExportPicture,
+    ExportSheet,
+    Minimize,
+    ToggleBorderless,
+    ToggleAlwaysOnTop,
+    Challenge,
+    ControlPanel,
+    Fullscreen,
+    Screenshot,
+    ResetZoom,
+    Gallery,
+    Stats,
+    Hint,
+    Peek,
+    Bookmark,
+    RestoreBookmark,
+    Undo,
+    Redo,
+    AutoSolve,
+    Pause,
+    SaveGame,
+    LoadGame,
+    Editor,
+    CopyPosition,
+    PastePosition,
+    Replay,
+    ExportReplay,
+    Menu,
+    #[cfg(feature = "settings")]
+    Settings,
+    #[cfg(feature = "sound")]
+    ToggleSound,
+    #[cfg(feature = "sound")]
+    NextTrack,
+    #[cfg(feature = "sound")]
+    ToggleMusic,
+    #[cfg(feature = "record")]
+    Record,
+    #[cfg(feature = "url")]
+    PasteUrl,
+    #[cfg(feature = "url")]
+    Surprise,
+    #[cfg(feature = "webcam")]
+    Webcam,
+    #[cfg(feature = "twitch")]
+    TwitchMode,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::Reset,
+        Action::Scramble,
+        Action::SlideUp,
+        Action::SlideDown,
+        Action::SlideLeft,
+        Action::SlideRight,
+        Action::ToggleNumbers,
+        Action::CycleNumbering,
+        Action::ToggleOutline,
+        Action::ToggleThumbnail,
+        Action::ToggleGenerated,
+        Action::ToggleColors,
+        Action::NewPicture,
+        Action::ToggleJigsaw,
+        Action::ToggleRotation,
+        Action::ToggleLoopover,
+        Action::Klotski,
+        Action::RotatePiece,
+        Action::NextImage,
+        Action::PreviousImage,
+        Action::RandomImage,
+        Action::CropImage,
+        Action::NextCollection,
+        Action::NextLayout,
+        Action::GrowGrid,
+        Action::ShrinkGrid,
+        Action::ExportHistory,
+        Action::ExportPicture,
+        Action::ExportSheet,
+        Action::Minimize,
+        Action::ToggleBorderless,
+        Action::ToggleAlwaysOnTop,
+        Action::Challenge,
+        Action::ControlPanel,
+        Action::Fullscreen,
+        Action::Screenshot,
+        Action::ResetZoom,
+        Action::Gallery,
+        Action::Stats,
+        Action::Hint,
+        Action::Peek,
+        Action::Bookmark,
+        Action::RestoreBookmark,
+        Action::Undo,
+        Action::Redo,
+        Action::AutoSolve,
+        Action::Pause,
+        Action::SaveGame,
+        Action::LoadGame,
+        Action::Editor,
+        Action::CopyPosition,
+        Action::PastePosition,
+        Action::Replay,
+        Action::ExportReplay,
+        Action::Menu,
+        #[cfg(feature = "settings")]
+        Action::Settings,
+        #[cfg(feature = "sound")]
+        Action::ToggleSound,
+        #[cfg(feature = "sound")]
+        Action::NextTrack,
+        #[cfg(feature = "sound")]
+        Action::ToggleMusic,
+        #[cfg(feature = "record")]
+        Action::Record,
+        #[cfg(feature = "url")]
+        Action::PasteUrl,
+        #[cfg(feature = "url")]
+        Action::Surprise,
+        #[cfg(feature = "webcam")]
+        Action::Webcam,
+        #[cfg(feature = "twitch")]
+        Action::TwitchMode,
+        Action::Quit,
+    ];
+
+    /// Name shown in the palette.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Reset => "Reset the board",
+            Action::Scramble => "Scramble",
+            Action::SlideUp => "Slide a tile up",
+            Action::SlideDown => "Slide a tile down",
+            Action::SlideLeft => "Slide a tile left",
+            Action::SlideRight => "Slide a tile right",
+            Action::ToggleNumbers => "Toggle numbers",
+            Action::CycleNumbering => "Change the numbering style",
+            Action::ToggleOutline => "Outline pieces in or out of place",
+            Action::ToggleThumbnail => "Show the target image in a corner",
+            Action::ToggleGenerated => "Toggle generated tile art",
+            Action::ToggleColors => "Toggle plain color tiles",
+            Action::NewPicture => "Make up a new picture, without images",
+            Action::ToggleJigsaw => "Toggle jigsaw edges",
+            Action::ToggleRotation => "Toggle the rotation variant",
+            Action::ToggleLoopover => "Toggle the Loopover variant",
+            Action::Klotski => "Play Klotski, the block puzzle",
+            Action::RotatePiece => "Turn the piece under the mouse",
+            Action::NextImage => "Next image",
+            Action::PreviousImage => "Previous image",
+            Action::RandomImage => "Random image",
+            Action::CropImage => "Pick the part of the image to play",
+            Action::NextCollection => "Next collection",
+            Action::NextLayout => "Change the number of boards",
+            Action::GrowGrid => "Grow the grid size",
+            Action::ShrinkGrid => "Shrink the grid size",
+            Action::ExportHistory => "Export the solve history",
+            Action::ExportPicture => "Export the picture",
+            Action::ExportSheet => "Export a printable sheet",
+            Action::Minimize => "Minimize",
+            Action::ToggleBorderless => "Toggle borderless window",
+            Action::ToggleAlwaysOnTop => "Toggle always on top",
+            Action::Challenge => "New challenge",
+            Action::ControlPanel => "Open the settings panel",
+            Action::Fullscreen => "Toggle fullscreen",
+            Action::Screenshot => "Take a screenshot",
+            Action::ResetZoom => "Reset the zoom",
+            Action::Gallery => "Gallery of the images",
+            Action::Stats => "Stats of the solves",
+            Action::Hint => "Hint, more with each press",
+            Action::Peek => "Peek at the solved image",
+            Action::Bookmark => "Bookmark the position",
+            Action::RestoreBookmark => "Go back to a bookmark",
+            Action::Undo => "Undo the last move",
+            Action::Redo => "Redo the move undone",
+            Action::AutoSolve => "Solve the board automatically",
+            Action::Pause => "Pause or resume the game",
+            Action::SaveGame => "Save the game",
+            Action::LoadGame => "Load the saved game",
+            Action::Editor => "Set a position up in the editor",
+            Action::CopyPosition => "Copy the position",
+            Action::PastePosition => "Play the position copied",
+            Action::Replay => "Play the last solve back",
+            Action::ExportReplay => "Save the replay as a GIF",
+            Action::Menu => "Back to the start menu",
+            #[cfg(feature = "settings")]
+            Action::Settings => "Open the settings",
+            #[cfg(feature = "sound")]
+            Action::ToggleSound => "Turn the sound off or on",
+            #[cfg(feature = "sound")]
+            Action::NextTrack => "Play the next track",
+            #[cfg(feature = "sound")]
+            Action::ToggleMusic => "Turn the music off or on",
+            #[cfg(feature = "record")]
+            Action::Record => "Start or stop recording",
+            #[cfg(feature = "url")]
+            Action::PasteUrl => "Open the image at the URL on the clipboard",
+            #[cfg(feature = "url")]
+            Action::Surprise => "Surprise me with an image from the web",
+            #[cfg(feature = "webcam")]
+            Action::Webcam => "Play a snapshot of the webcam",
+            #[cfg(feature = "twitch")]
+            Action::TwitchMode => "Change the Twitch mode",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Palette {
+    pub query: String,   // Text typed so far
+    pub selected: usize, // Index of the picked line among the matches
+}
+
+impl Palette {
+    /// Actions matching the query, best first.
+    pub fn matches(&self) -> Vec<Action> {
+        let mut scored: Vec<(i32, Action)> = Action::ALL
+            .iter()
+            .filter_map(|&action| score(&self.query, action.name()).map(|s| (s, action)))
+            .collect();
+        // stable, so equal scores keep the order of `Action::ALL`
+        scored.sort_by_key(|(s, _)| -s);
+        scored.into_iter().map(|(_, action)| action).collect()
+    }
+
+    /// The picked action, if anything matches.
+    pub fn selection(&self) -> Option<Action> {
+        self.matches().get(self.selected).copied()
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        if !c.is_control() {
+            self.query.push(c);
+            self.selected = 0;
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    /// Move the selection by `delta` lines, wrapping around.
+    pub fn move_selection(&mut self, delta: isize) {
+        let count = self.matches().len() as isize;
+        if count > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(count) as usize;
+        }
+    }
+}
+
+/// Score of `name` for `query` if all the letters of the query appear in
+/// it in order. Letters following each other or starting a word count
+/// more.
+fn score(query: &str, name: &str) -> Option<i32> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let i = start + name[start..].iter().position(|&c| c == q)?;
+        score += 1;
+        if previous == Some(i.wrapping_sub(1)) {
+            score += 3;
+        }
+        if i == 0 || name[i - 1] == ' ' {
+            score += 2;
+        }
+        previous = Some(i);
+        start = i + 1;
+    }
+    Some(score)
+}
+
+/// Draw the palette over the top part of `win`.
+pub fn draw(draw: &Draw, palette: &Palette, keys: &KeyMap, win: Rect) {
+    let width = (win.w() * 0.8).min(500.0);
+    let line_height = (win.h() / 20.0).clamp(16.0, 28.0);
+    let font_size = (line_height * 0.55) as u32;
+    let matches = palette.matches();
+    let first = palette.selected.saturating_sub(MAX_LINES - 1);
+    let shown = &matches[first..matches.len().min(first + MAX_LINES)];
+    let lines = shown.len().max(1) + 1;
+    let height = lines as f32 * line_height;
+    let top = win.top() - line_height;
+
+    draw.rect()
+        .x_y(win.x(), top - height / 2.0)
+        .w_h(width, height)
+        .color(rgba(0.1, 0.1, 0.1, 0.95))
+        .stroke(GREY)
+        .stroke_weight(1.0);
+
+    let line = |i: usize| top - (i as f32 + 0.5) * line_height;
+    let text_w = width - line_height;
+    draw.text(&format!("> {}_", palette.query))
+        .font_size(font_size)
+        .x_y(win.x(), line(0))
+        .w_h(text_w, line_height)
+        .left_justify()
+        .align_text_middle_y()
+        .color(WHITE);
+    if shown.is_empty() {
+        draw.text("No matching action")
+            .font_size(font_size)
+            .x_y(win.x(), line(1))
+            .w_h(text_w, line_height)
+            .left_justify()
+            .align_text_middle_y()
+            .color(GREY);
+    }
+    for (i, action) in shown.iter().enumerate() {
+        let y = line(i + 1);
+        let color = match first + i == palette.selected {
+            true => {
+                draw.rect()
+                    .x_y(win.x(), y)
+                    .w_h(width, line_height)
+                    .color(DARKSLATEGREY);
+                YELLOW
+            }
+            false => LIGHTSKYBLUE,
+        };
+        draw.text(action.name())
+            .font_size(font_size)
+            .x_y(win.x(), y)
+            .w_h(text_w, line_height)
+            .left_justify()
+            .align_text_middle_y()
+            .color(color);
+        draw.text(&keys.key_name(*action))
+            .font_size(font_size)
+            .x_y(win.x(), y)
+            .w_h(text_w, line_height)
+            .right_justify()
+            .align_text_middle_y()
+            .color(GREY);
+    }
+}
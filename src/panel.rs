@@ -0,0 +1,254 @@
+//! Control panel, an optional second window with the settings, the stats
+//! and the images of the focused puzzle, so the main window shows nothing
+//! but the boards. Handy on a second monitor or when presenting.
+//!
+//! Every line with an action is clicked to apply it, the grid size grows
+//! with the left button and shrinks with the right one.
+
+use nannou::prelude::*;
+
+use crate::{clock, history, Model};
+
+/// Initial size of the panel window.
+static PANEL_WIDTH: u32 = 260;
+static PANEL_HEIGHT: u32 = 480;
+
+/// Height of one line of the panel.
+static ROW_HEIGHT: f32 = 20.0;
+
+pub struct Panel {
+    pub window_id: window::Id,       // The panel window
+    pub scroll: f32,                 // How far the lines are scrolled up
+    pub solves: Vec<history::Solve>, // Solve history, for the stats
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    ToggleNumbers,
+    ToggleOutline,
+    ToggleAlwaysOnTop,
+    ToggleBorderless,
+    NextLayout,
+    GridSize,
+    Scramble,
+    Image(usize),
+}
+
+struct Row {
+    text: String,
+    action: Option<Action>,
+    color: Rgb<u8>,
+}
+
+impl Row {
+    fn header(text: &str) -> Self {
+        Row {
+            text: text.to_uppercase(),
+            action: None,
+            color: GREY,
+        }
+    }
+
+    fn info(text: String) -> Self {
+        Row {
+            text,
+            action: None,
+            color: WHITE,
+        }
+    }
+
+    fn action(text: String, action: Action) -> Self {
+        Row {
+            text,
+            action: Some(action),
+            color: LIGHTSKYBLUE,
+        }
+    }
+}
+
+/// Open the control panel, or bring it to the front if it is open.
+pub fn open(app: &App, model: &mut Model) {
+    if let Some(panel) = &model.panel {
+        if let Some(window) = app.window(panel.window_id) {
+            window.winit_window().focus_window();
+        }
+        return;
+    }
+    let window = app
+        .new_window()
+        .size(PANEL_WIDTH, PANEL_HEIGHT)
+        .title("Control panel")
+        .view(view)
+        .event(event)
+        .closed(closed)
+        .build();
+    match window {
+        Ok(window_id) => {
+            model.panel = Some(Panel {
+                window_id,
+                scroll: 0.0,
+                solves: history::load(),
+            })
+        }
+        Err(e) => println!("Error opening the control panel: {e}"),
+    }
+}
+
+fn closed(_app: &App, model: &mut Model) {
+    model.panel = None;
+}
+
+/// Lines of the panel for the focused puzzle.
+fn rows(model: &Model, panel: &Panel) -> Vec<Row> {
+    let on_off = |flag| match flag {
+        true => "on",
+        false => "off",
+    };
+    let puzzle = model.puzzle();
+    let size = puzzle.grid_size;
+    let mut rows = vec![
+        Row::header("Settings"),
+        Row::action(
+            format!("Numbers: {}", on_off(model.flag_show_numbers)),
+            Action::ToggleNumbers,
+        ),
+        Row::action(
+            format!("Outline: {}", on_off(model.flag_outline)),
+            Action::ToggleOutline,
+        ),
+        Row::action(
+            format!("Always on top: {}", on_off(model.flag_always_on_top)),
+            Action::ToggleAlwaysOnTop,
+        ),
+        Row::action(
+            format!("Borderless: {}", on_off(model.flag_borderless)),
+            Action::ToggleBorderless,
+        ),
+        Row::action(
+            format!("Boards: {}", model.puzzles.len()),
+            Action::NextLayout,
+        ),
+        Row::action(format!("Grid: {size}"), Action::GridSize),
+        Row::action(String::from("Scramble"), Action::Scramble),
+        Row::header("Stats"),
+    ];
+
+    let seconds = puzzle.elapsed().as_secs();
+    rows.push(Row::info(format!("Moves: {}", puzzle.move_count)));
+    rows.push(Row::info(format!("Time: {}", clock::format_clock(seconds))));
+    let solves: Vec<_> = panel
+        .solves
+        .iter()
+        .filter(|s| s.grid_size() == size)
+        .collect();
+    rows.push(Row::info(format!("Solves {size}: {}", solves.len())));
+    if let Some(best) = solves.iter().map(|s| s.seconds).reduce(f64::min) {
+        let fewest = solves.iter().map(|s| s.moves).min().unwrap_or(0);
+        rows.push(Row::info(format!(
+            "Best: {}, {fewest} moves",
+            clock::format_duration(best)
+        )));
+    }
+
+    rows.push(Row::header("Images"));
+    for (i, path) in puzzle.image_list.iter().enumerate() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut row = Row::action(name.into_owned(), Action::Image(i));
+        if i == puzzle.image_index_current {
+            row.color = YELLOW;
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+fn apply(app: &App, model: &mut Model, action: Action, button: MouseButton) {
+    match action {
+        Action::ToggleNumbers => model.flag_show_numbers = !model.flag_show_numbers,
+        Action::ToggleOutline => model.flag_outline = !model.flag_outline,
+        Action::ToggleAlwaysOnTop => model.toggle_always_on_top(app),
+        Action::ToggleBorderless => model.toggle_borderless(app),
+        Action::NextLayout => model.next_layout(app),
+        Action::GridSize => match button {
+            MouseButton::Right => model.change_grid_size(-1),
+            _ => model.change_grid_size(1),
+        },
+        Action::Scramble => model.scramble(),
+        Action::Image(index) => model.select_image(index),
+    }
+}
+
+/// Clicks and scrolling are handled here, keys work as in the main window.
+fn event(app: &App, model: &mut Model, event: WindowEvent) {
+    let Some(panel) = &model.panel else {
+        return;
+    };
+    let Some(window) = app.window(panel.window_id) else {
+        return;
+    };
+    let win = window.rect();
+    let scroll = panel.scroll;
+    let rows = rows(model, panel);
+    match event {
+        MousePressed(button) => {
+            model.note_input();
+            let row = ((win.top() - app.mouse.y + scroll) / ROW_HEIGHT) as usize;
+            if let Some(action) = rows.get(row).and_then(|row| row.action) {
+                apply(app, model, action, button);
+            }
+        }
+        MouseWheel(delta, _phase) => {
+            let dy = match delta {
+                MouseScrollDelta::LineDelta(_, y) => y * ROW_HEIGHT,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32,
+            };
+            let max = (rows.len() as f32 * ROW_HEIGHT - win.h()).max(0.0);
+            if let Some(panel) = &mut model.panel {
+                panel.scroll = (scroll - dy).clamp(0.0, max);
+            }
+        }
+        KeyPressed(_) => crate::event(app, model, event),
+        // going between the game and its panel keeps the game focused
+        Focused => model.focus_changed(true),
+        Unfocused => model.focus_changed(false),
+        _ => (),
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(BLACK);
+    let Some(panel) = &model.panel else {
+        return;
+    };
+
+    let draw = app.draw();
+    let win = frame.rect();
+    let font_size = (ROW_HEIGHT * 0.6) as u32;
+    for (i, row) in rows(model, panel).iter().enumerate() {
+        let y = win.top() + panel.scroll - (i as f32 + 0.5) * ROW_HEIGHT;
+        draw.text(&row.text)
+            .font_size(font_size)
+            .x_y(0.0, y)
+            .w_h(win.w() - ROW_HEIGHT, ROW_HEIGHT)
+            .left_justify()
+            .align_text_middle_y()
+            .color(row.color);
+    }
+
+    // the toast goes here, rather than on top of the boards
+    if let Some((message, _)) = &model.toast {
+        let y = win.bottom() + ROW_HEIGHT;
+        draw.rect()
+            .x_y(0.0, y)
+            .w_h(win.w(), 2.0 * ROW_HEIGHT)
+            .color(DARKSLATEGREY);
+        draw.text(message)
+            .font_size(font_size)
+            .x_y(0.0, y)
+            .w(win.w())
+            .center_justify()
+            .color(WHITE);
+    }
+
+    draw.to_frame(app, &frame).unwrap();
+}
@@ -0,0 +1,180 @@
+//! The image side of a board, apart from the game: the solved image cut
+//! into pieces, and the pieces laid out as on the board for the pictures
+//! and sheets. The window draws the pieces as textures instead, see
+//! `render`. The benchmarks use this module on its own.
+
+use log::debug;
+use nannou::image::{self, GenericImage, GenericImageView};
+use nannou::prelude::*;
+use sliding_puzzle::{Board, GridSize};
+
+use crate::jigsaw;
+
+/// Region of `original` played, `crop` or else the middle, resized to
+/// `width` by `height` pixels.
+pub fn fill(
+    original: &image::DynamicImage,
+    crop: Option<[u32; 4]>,
+    width: u32,
+    height: u32,
+    filter: image::imageops::FilterType,
+) -> image::DynamicImage {
+    let Some([x, y, w, h]) = crop else {
+        return original.resize_to_fill(width, height, filter);
+    };
+    let (original_width, original_height) = original.dimensions();
+    let x = x.min(original_width - 1);
+    let y = y.min(original_height - 1);
+    original
+        .crop_imm(
+            x,
+            y,
+            w.min(original_width - x).max(1),
+            h.min(original_height - y).max(1),
+        )
+        .resize_to_fill(width, height, filter)
+}
+
+/// Pieces of `solved` for a board of `grid_size`, indexed by piece number,
+/// with jigsaw edges if `jigsaw` is set. Piece 0, the empty space, is an
+/// empty image. The piece of the last cell is cut too, for the Loopover
+/// variant.
+pub fn cut(
+    solved: &image::DynamicImage,
+    grid_size: GridSize,
+    jigsaw: bool,
+) -> Vec<image::RgbaImage> {
+    if jigsaw {
+        return jigsaw::cut(solved, grid_size);
+    }
+    let cell_size = solved.width() / grid_size.width as u32;
+    let mut pieces = vec![image::RgbaImage::new(0, 0)];
+    for piece in 1..=grid_size.cells() {
+        let x0 = ((piece - 1) % grid_size.width) as u32 * cell_size;
+        let y0 = ((piece - 1) / grid_size.width) as u32 * cell_size;
+        pieces.push(solved.crop_imm(x0, y0, cell_size, cell_size).to_rgba8());
+    }
+    pieces
+}
+
+/// Lay the `pieces` cut from `solved` out as on `board`, each turned by
+/// its `turns`. `jigsaw` tells the pieces were cut with jigsaw edges.
+pub fn compose(
+    solved: &image::DynamicImage,
+    board: &Board,
+    turns: &[usize],
+    pieces: &[image::RgbaImage],
+    jigsaw: bool,
+) -> image::DynamicImage {
+    let (width, height) = solved.dimensions();
+
+    // Create a new image with the same size as the board
+    let mut new_image = image::DynamicImage::new_rgba8(width, height);
+
+    // Draw the pieces on the new image
+    let size = board.size();
+    for row in 0..size.height {
+        for col in 0..size.width {
+            draw_cell(&mut new_image, board, turns, pieces, jigsaw, (row, col));
+        }
+    }
+    new_image
+}
+
+/// Draw the piece at `(row, col)` of `board` on `target`, one of `pieces`.
+/// The empty space is left as it is.
+fn draw_cell(
+    target: &mut image::DynamicImage,
+    board: &Board,
+    turns: &[usize],
+    pieces: &[image::RgbaImage],
+    jigsaw: bool,
+    (row, col): (usize, usize),
+) {
+    let (width, height) = target.dimensions();
+    let cell_size = width as usize / board.size().width;
+    let piece = board[row][col];
+    if piece == 0 {
+        return;
+    }
+    let turned = match turns[piece] {
+        1 => image::imageops::rotate90(&pieces[piece]),
+        2 => image::imageops::rotate180(&pieces[piece]),
+        3 => image::imageops::rotate270(&pieces[piece]),
+        _ => pieces[piece].clone(),
+    };
+    // jigsaw pieces reach over the cells around
+    let margin = match jigsaw {
+        true => jigsaw::margin(cell_size as u32) as i64,
+        false => 0,
+    };
+    let x = (col * cell_size) as i64 - margin;
+    let y = (height as usize - (row + 1) * cell_size) as i64 - margin;
+    debug!("Row {row}, Col {col}, piece: {piece:2} into x: {x:3}, y: {y:3}");
+    paste_masked(target, &turned, x, y);
+}
+
+/// Paste the visible pixels of `piece` onto `target` with its top left
+/// corner at `(x, y)`, leaving out what falls outside of it.
+fn paste_masked(target: &mut image::DynamicImage, piece: &image::RgbaImage, x: i64, y: i64) {
+    let (width, height) = target.dimensions();
+    for (px, py, pixel) in piece.enumerate_pixels() {
+        let (tx, ty) = (x + px as i64, y + py as i64);
+        if pixel[3] > 0 && tx >= 0 && ty >= 0 && tx < width as i64 && ty < height as i64 {
+            target.put_pixel(tx as u32, ty as u32, *pixel);
+        }
+    }
+}
+
+/// Draw the number of each piece of `board` on its cell of `picture`.
+pub fn draw_numbers(picture: &mut image::RgbaImage, board: &Board) {
+    let font = text::font::default_notosans();
+    let cell_size = (picture.width() as usize / board.size().width) as f32;
+    for (row, cells) in board.rows().enumerate() {
+        for (col, &piece) in cells.iter().enumerate() {
+            if piece != 0 {
+                let x = (col as f32 + 0.5) * cell_size;
+                let y = picture.height() as f32 - (row as f32 + 0.5) * cell_size;
+                let text = piece.to_string();
+                draw_text(picture, &font, &text, pt2(x, y), cell_size / 2.0);
+            }
+        }
+    }
+}
+
+/// Draw `text` in black, centred on `centre` in pixels of `picture`.
+fn draw_text(
+    picture: &mut image::RgbaImage,
+    font: &text::Font,
+    text: &str,
+    centre: Point2,
+    size: f32,
+) {
+    let scale = text::Scale::uniform(size);
+    let width = font
+        .layout(text, scale, text::rt::point(0.0, 0.0))
+        .last()
+        .map(|g| g.position().x + g.unpositioned().h_metrics().advance_width)
+        .unwrap_or(0.0);
+    let v_metrics = font.v_metrics(scale);
+    let origin = text::rt::point(
+        centre.x - width / 2.0,
+        centre.y + (v_metrics.ascent + v_metrics.descent) / 2.0,
+    );
+    for glyph in font.layout(text, scale, origin) {
+        let Some(bounds) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|x, y, coverage| {
+            let x = bounds.min.x + x as i32;
+            let y = bounds.min.y + y as i32;
+            if x < 0 || y < 0 || x >= picture.width() as i32 || y >= picture.height() as i32 {
+                return;
+            }
+            let pixel = picture.get_pixel_mut(x as u32, y as u32);
+            for channel in &mut pixel.0[..3] {
+                *channel = (*channel as f32 * (1.0 - coverage)) as u8;
+            }
+        });
+    }
+}
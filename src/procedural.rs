@@ -0,0 +1,182 @@
+//! Pictures made up from a seed, played when the images folder is empty
+//! so the game needs no assets at all: noise landscapes, Voronoi mosaics
+//! and plasma fractals, the seed picking which and drawing it. `Shift+G`
+//! makes a new one.
+
+use nannou::color::{hsv, Rgb};
+use nannou::image::{DynamicImage, Rgba, RgbaImage};
+use nannou::noise::{NoiseFn, OpenSimplex, Seedable};
+use nannou::rand::rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Kinds of pictures, the seed picks one in turn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Landscape, // Mountain ridges fading into the distance under a sky
+    Mosaic,    // Voronoi cells of random colors with dark joints
+    Plasma,    // Interfering waves of color
+}
+
+impl Kind {
+    pub fn of_seed(seed: u64) -> Self {
+        match seed % 3 {
+            0 => Kind::Landscape,
+            1 => Kind::Mosaic,
+            _ => Kind::Plasma,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Landscape => "Landscape",
+            Kind::Mosaic => "Mosaic",
+            Kind::Plasma => "Plasma",
+        }
+    }
+}
+
+/// Mountain ridges of a landscape, the farthest first.
+static RIDGES: usize = 4;
+
+/// Cells of a mosaic.
+static MOSAIC_CELLS: usize = 40;
+
+/// Width of the joints between the cells of a mosaic, as a share of the
+/// side of the picture.
+static MOSAIC_JOINT: f32 = 0.006;
+
+/// Square picture of `size` pixels made up from `seed`.
+pub fn generate(seed: u64, size: u32) -> DynamicImage {
+    let size = size.max(1);
+    let image = match Kind::of_seed(seed) {
+        Kind::Landscape => landscape(seed, size),
+        Kind::Mosaic => mosaic(seed, size),
+        Kind::Plasma => plasma(seed, size),
+    };
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Noise summed over `octaves` octaves, each twice as fine and half as
+/// strong as the one before, from about -1 to 1.
+fn fractal_noise(noise: &OpenSimplex, x: f64, y: f64, octaves: usize) -> f64 {
+    let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+    for _ in 0..octaves {
+        sum += noise.get([x * frequency, y * frequency]) * amplitude;
+        total += amplitude;
+        amplitude /= 2.0;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+/// Pixel of a color with components from 0 to 1.
+fn pixel(color: Rgb) -> Rgba<u8> {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0) as u8;
+    Rgba([
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue),
+        255,
+    ])
+}
+
+/// Ridges of mountains, darker and bluer with the distance, under a sky
+/// fading to the horizon.
+fn landscape(seed: u64, size: u32) -> RgbaImage {
+    let noise = OpenSimplex::new().set_seed(seed as u32);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let hue = rng.gen_range(0.0..1.0);
+    // height of each ridge along the picture, from the bottom, in pixels
+    let ridges: Vec<Vec<f32>> = (0..RIDGES)
+        .map(|ridge| {
+            let base = 0.65 - ridge as f64 * 0.13;
+            (0..size)
+                .map(|x| {
+                    let x = x as f64 / size as f64 * (2.0 + ridge as f64);
+                    let height = base + fractal_noise(&noise, x, ridge as f64 * 10.0, 5) * 0.15;
+                    (height * size as f64) as f32
+                })
+                .collect()
+        })
+        .collect();
+    RgbaImage::from_fn(size, size, |x, y| {
+        let up = (size - y) as f32;
+        // the nearest ridge in front of the pixel, the last ones are nearer
+        let ridge = (0..RIDGES).rev().find(|&r| up < ridges[r][x as usize]);
+        let color: Rgb = match ridge {
+            Some(ridge) => {
+                let near = (ridge + 1) as f32 / RIDGES as f32;
+                hsv(hue + 0.5 - near * 0.2, 0.3 + near * 0.4, 0.8 - near * 0.55).into()
+            }
+            None => {
+                let height = up / size as f32;
+                hsv(hue, 0.25 + height * 0.35, 0.95 - height * 0.25).into()
+            }
+        };
+        pixel(color)
+    })
+}
+
+/// Cells of random colors around random points, each pixel taking the
+/// color of the nearest point, with dark joints where two are as near.
+fn mosaic(seed: u64, size: u32) -> RgbaImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let hue = rng.gen_range(0.0..1.0);
+    let cells: Vec<((f32, f32), Rgb)> = (0..MOSAIC_CELLS)
+        .map(|_| {
+            let point = (rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+            let color = hsv(
+                hue + rng.gen_range(-0.15..0.15),
+                rng.gen_range(0.4..0.9),
+                rng.gen_range(0.5..1.0),
+            );
+            (point, color.into())
+        })
+        .collect();
+    RgbaImage::from_fn(size, size, |x, y| {
+        let (u, v) = (x as f32 / size as f32, y as f32 / size as f32);
+        let (mut nearest, mut second) = ((f32::MAX, 0), f32::MAX);
+        for (i, ((px, py), _)) in cells.iter().enumerate() {
+            let distance = ((u - px).powi(2) + (v - py).powi(2)).sqrt();
+            if distance < nearest.0 {
+                second = nearest.0;
+                nearest = (distance, i);
+            } else if distance < second {
+                second = distance;
+            }
+        }
+        match second - nearest.0 < MOSAIC_JOINT {
+            true => Rgba([30, 30, 30, 255]),
+            false => pixel(cells[nearest.1].1),
+        }
+    })
+}
+
+/// Sines of random directions and frequencies added up, with a bit of
+/// noise, mapped to a band of hues.
+fn plasma(seed: u64, size: u32) -> RgbaImage {
+    let noise = OpenSimplex::new().set_seed(seed as u32);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let hue = rng.gen_range(0.0..1.0);
+    let waves: Vec<(f32, f32, f32)> = (0..4)
+        .map(|_| {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let frequency = rng.gen_range(4.0..12.0);
+            (
+                angle.cos() * frequency,
+                angle.sin() * frequency,
+                rng.gen_range(0.0..6.3),
+            )
+        })
+        .collect();
+    RgbaImage::from_fn(size, size, |x, y| {
+        let (u, v) = (x as f32 / size as f32, y as f32 / size as f32);
+        let waves: f32 = waves
+            .iter()
+            .map(|(fx, fy, phase)| (u * fx + v * fy + phase).sin())
+            .sum::<f32>()
+            / waves.len() as f32;
+        let turbulence = fractal_noise(&noise, u as f64 * 3.0, v as f64 * 3.0, 3) as f32;
+        let value = (waves + turbulence * 0.5 + 1.0) / 2.0;
+        pixel(hsv(hue + value * 0.4, 0.7, 0.5 + value * 0.5).into())
+    })
+}
@@ -0,0 +1,43 @@
+//! Location of the player's profile, ie, everything the game writes:
+//! stats, records, config and screenshots.
+//!
+//! The profile lives in the platform data folder unless `PUZZLE_PROFILE_DIR`
+//! points somewhere else. Pointing it at a synced folder (Dropbox, OneDrive,
+//! Syncthing, ...) makes records follow the player between machines.
+
+use std::{env, fs, path::PathBuf};
+
+/// Name of the profile folder inside the platform data folder.
+static APP_DIR: &str = "sliding_puzzle";
+
+/// Root folder of the profile.
+pub fn profile_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("PUZZLE_PROFILE_DIR") {
+        return PathBuf::from(dir);
+    }
+    platform_data_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Path of `name` inside the profile, creating the profile folder if needed.
+pub fn profile_path(name: &str) -> PathBuf {
+    let dir = profile_dir();
+    if let Err(e) = fs::create_dir_all(&dir) {
+        println!("Error creating profile folder {}: {e}", dir.display());
+    }
+    dir.join(name)
+}
+
+/// Per-user data folder of the platform, if it can be found.
+fn platform_data_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    }
+}
@@ -0,0 +1,1478 @@
+//! One puzzle: the board, the image it is made of and the solve in progress.
+//! The window shows one or more of these side by side.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use log::debug;
+use nannou::image::{self, GenericImageView};
+use nannou::prelude::*;
+use nannou::rand::rand::{rngs::StdRng, Rng, SeedableRng};
+use sliding_puzzle::{solver, Board, GridSize, Shift, Variant};
+
+use crate::artwork;
+use crate::challenge::Challenge;
+use crate::clock;
+use crate::config::ResizeFilter;
+use crate::exif;
+use crate::history;
+use crate::packs;
+use crate::pieces;
+use crate::procedural;
+use crate::replay::{Replay, Step};
+use crate::save::SavedGame;
+
+/// Cost of each level of hint, from the region to work on to the exact
+/// move, added to the hint cost of the solve.
+static HINT_COSTS: [usize; 3] = [1, 2, 3];
+
+/// Time the solver searches at most for a hint, the game waiting for it.
+/// Longer searches give up for the way back of the scramble.
+static HINT_TIME: Duration = Duration::from_millis(100);
+
+/// Longest side of the images once opened, in pixels. Bigger photos are
+/// shrunk to it, twice a board as tall as a 4K screen, so resizing them to
+/// the board stays quick and they don't hold on to memory for nothing.
+static MAX_IMAGE_SIZE: u32 = 4096;
+
+/// Time between two moves of an animated scramble, unless set.
+pub static SCRAMBLE_INTERVAL: Duration = Duration::from_millis(15);
+
+/// Random moves of an animated scramble of a 4x4 board, unless set. Other
+/// sizes take as many moves per cell.
+static SCRAMBLE_MOVES: usize = 100;
+
+/// Least distance of a scramble from the solved board, unless set, as a
+/// share of the average distance of a random board. See
+/// `Board::manhattan_distance`.
+static SCRAMBLE_DIFFICULTY: f32 = 0.5;
+
+/// Animated scrambles still too close to the solved board go on for up to
+/// this many times their moves, a random walk can take long to get away.
+static SCRAMBLE_EXTENSION: usize = 4;
+
+/// Random boards drawn at most for an instant scramble far enough from
+/// the solved board, the last one is kept.
+static SCRAMBLE_DRAWS: usize = 100;
+
+/// Most scramble moves played at once to catch up, past that the
+/// scramble goes on from where it was, eg, after the window was dragged.
+static SCRAMBLE_CATCH_UP: u32 = 20;
+
+/// Most positions kept with `B`, the oldest is dropped past it.
+static BOOKMARK_SLOTS: usize = 9;
+
+/// Move of the player during a solve, kept to undo it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Move {
+    Slide(usize, (usize, usize)), // Piece slid into the empty space, and the cell at the other end of the slide
+    Line(Vec<usize>),             // Pieces in line with the empty space slid at once, nearest first
+    Turn(usize),                  // Piece turned a quarter clockwise, in the rotation variant
+    Shift(Shift),                 // Row or column shifted, in the Loopover variant
+}
+
+/// Where a board is in a round: scrambled, then solved by the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Free,       // Not scrambled, moves are free and not counted
+    Scrambling, // Random moves being played, see `scramble_step`
+    Solving,    // Scrambled, the moves of the player count until solved
+}
+
+/// Position saved during a solve to come back to it later.
+#[derive(Clone)]
+pub struct Bookmark {
+    pub board: Board,      // The board at the time
+    pub path: Vec<usize>,  // Pieces moved since solved, see `Puzzle::path`
+    pub turns: Vec<usize>, // Quarter turns of each piece, see `Puzzle::turns`
+}
+
+pub struct Puzzle {
+    pub grid_size: GridSize,                  // Columns and rows of the board
+    pub stage: Stage,                         // Where the board is in the round
+    pub scramble_count: usize,                // Number of times the board has been scrambled
+    scramble_clock: Instant,                  // Time the scramble moves due are counted from
+    pub scramble_moves: Option<usize>, // Random moves of an animated scramble, scaled with the grid size if not set
+    pub scramble_difficulty: f32, // Least distance of a scramble from solved, see `SCRAMBLE_DIFFICULTY`
+    pub board: Board,             // The board itself
+    pub image_list: Vec<PathBuf>, // List of images to use
+    pub image_index_current: usize, // Index of the current image
+    pub image_original: image::DynamicImage, // Original image
+    pub filter: ResizeFilter,     // Filter resizing `image_original` to the board
+    pub crop: Option<[u32; 4]>, // Region of `image_original` played, picked with `Ctrl+I`, `None` for the middle
+    pub image_solved: image::DynamicImage, // Resized image and cut square
+    pub textures: Vec<Option<wgpu::Texture>>, // `pieces` on the GPU, made on the next update after they are cut
+    pub thumbnail: Option<wgpu::Texture>, // Small `image_solved`, made on the next update when shown
+
+    pub image_info: Vec<Option<packs::PackImage>>, // Pack details of each image in `image_list`
+    pub collection_current: usize, // Index of the collection `image_list` comes from
+    pub scramble_rng: Option<StdRng>, // Generator of the current scramble
+    pub scramble_seed: Option<u64>, // Seed of the current scramble, if seeded
+    pub challenge: Option<Challenge>, // Challenge being played, if any
+    recording: Option<Replay>,     // Replay of the solve in progress
+    pub move_count: usize,         // Moves made by the player in this solve
+    pub solve_started: Option<Instant>, // Time of the first move of this solve
+    pub path: Vec<usize>,          // Pieces moved since the board was solved
+    pub paused_at: Option<Instant>, // Time the solve was paused, if it is
+    pub bookmarks: Vec<Bookmark>,  // Positions saved with `B`, newest last
+    pub flag_generated: bool,      // Flag to indicate generated tile art replaces the image
+    pub flag_colors: bool, // Flag to indicate solid color tiles of a gradient replace the image
+    pub procedural_seed: u64, // Seed of the picture made up when there are no images
+    pub flag_rotation: bool, // Flag to indicate scrambled pieces are also turned
+    pub blanks: usize,     // Empty spaces of the board, more than one makes it easier
+    pub variant: Variant,  // Rules of the moves, Loopover boards have no empty space
+    pub turns: Vec<usize>, // Clockwise quarter turns of each piece, by piece number
+    pub flag_jigsaw: bool, // Flag to indicate the pieces have jigsaw edges
+    pub pieces: Vec<image::RgbaImage>, // Pieces cut from `image_solved` by number, with jigsaw edges if `flag_jigsaw`
+    pub hint_level: usize,             // Level of the hint shown for this position, 0 for none
+    pub hint_cost: usize,              // Cost of the hints taken in this solve
+    pub peeks: usize,                  // Peeks at the solved image in this solve
+    hint: Option<usize>,               // Piece the hint shown is about
+    undo_stack: Vec<Move>,             // Moves of this solve, newest last
+    redo_stack: Vec<Move>,             // Moves undone, to play them again
+}
+
+impl Puzzle {
+    /// New solved puzzle showing the first image of `collection`, or a
+    /// blank image if it has none. Images are `img_size` pixels on their
+    /// longest side, see `image_dimensions`.
+    pub fn new(
+        grid_size: GridSize,
+        collection: &packs::Collection,
+        collection_current: usize,
+        img_size: u32,
+    ) -> Self {
+        let image_list = collection.images.clone();
+        let image_info = collection.info.clone();
+        let image_index_current = 0;
+
+        // Start with a blank image, the first image is opened below.
+        let image_original = blank_image(img_size);
+
+        // Resize the original image to the shape of the grid to fit the window
+        let (width, height) = image_dimensions(grid_size, img_size);
+        let image_solved =
+            image_original.resize_to_fill(width, height, image::imageops::FilterType::Nearest);
+
+        let mut puzzle = Puzzle {
+            grid_size,
+            stage: Stage::Free,
+            scramble_count: 0,
+            scramble_clock: Instant::now(),
+            scramble_moves: None,
+            scramble_difficulty: SCRAMBLE_DIFFICULTY,
+            board: Board::solved(grid_size),
+            image_list,
+            image_index_current,
+            image_original,
+            filter: ResizeFilter::default(),
+            crop: None,
+            image_solved,
+            textures: vec![],
+            thumbnail: None,
+            image_info,
+            collection_current,
+            scramble_rng: None,
+            scramble_seed: None,
+            challenge: None,
+            recording: None,
+            move_count: 0,
+            solve_started: None,
+            path: vec![],
+            paused_at: None,
+            bookmarks: vec![],
+            flag_generated: false,
+            flag_colors: false,
+            procedural_seed: random(),
+            flag_rotation: false,
+            blanks: 1,
+            variant: Variant::Sliding,
+            turns: no_turns(grid_size),
+            flag_jigsaw: false,
+            pieces: vec![],
+            hint_level: 0,
+            hint_cost: 0,
+            peeks: 0,
+            hint: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+        };
+        puzzle.change_image();
+        puzzle
+    }
+
+    /// New solved puzzle with the same size and image as this one.
+    pub fn duplicate(&self) -> Self {
+        Puzzle {
+            grid_size: self.grid_size,
+            stage: Stage::Free,
+            scramble_count: 0,
+            scramble_clock: Instant::now(),
+            scramble_moves: self.scramble_moves,
+            scramble_difficulty: self.scramble_difficulty,
+            board: self.solved_board(),
+            image_list: self.image_list.clone(),
+            image_index_current: self.image_index_current,
+            image_original: self.image_original.clone(),
+            filter: self.filter,
+            crop: self.crop,
+            image_solved: self.image_solved.clone(),
+            textures: vec![],
+            thumbnail: None,
+            image_info: self.image_info.clone(),
+            collection_current: self.collection_current,
+            scramble_rng: None,
+            scramble_seed: None,
+            challenge: None,
+            recording: None,
+            move_count: 0,
+            solve_started: None,
+            path: vec![],
+            paused_at: None,
+            bookmarks: vec![],
+            flag_generated: self.flag_generated,
+            flag_colors: self.flag_colors,
+            procedural_seed: self.procedural_seed,
+            flag_rotation: self.flag_rotation,
+            blanks: self.blanks,
+            variant: self.variant,
+            turns: no_turns(self.grid_size),
+            flag_jigsaw: self.flag_jigsaw,
+            pieces: self.pieces.clone(),
+            hint_level: 0,
+            hint_cost: 0,
+            peeks: 0,
+            hint: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+        }
+    }
+
+    /// Reset board
+    pub fn reset(&mut self) {
+        self.board = self.solved_board();
+        self.turns = no_turns(self.grid_size);
+        self.path.clear();
+        self.bookmarks.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.hint_level = 0;
+        self.hint_cost = 0;
+        self.peeks = 0;
+        self.stage = Stage::Free;
+        self.move_count = 0;
+        self.solve_started = None;
+        self.paused_at = None;
+        self.recording = None;
+    }
+
+    /// Change the size of the grid, which resets the board.
+    pub fn set_grid_size(&mut self, grid_size: GridSize) {
+        self.grid_size = grid_size;
+        self.challenge = None;
+        self.reset();
+        // the generated art and the jigsaw pieces are made for the grid
+        self.resize(self.img_size());
+    }
+
+    /// Returns `true` if every piece is in its place, the right way up.
+    pub fn is_solved(&self) -> bool {
+        self.board.is_solved() && self.turns.iter().all(|&t| t == 0)
+    }
+
+    /// Returns `true` if the piece at `(ix, iy)` is in its place, the
+    /// right way up. The empty space is never in place, it is no piece.
+    pub fn is_piece_correct(&self, ix: usize, iy: usize) -> bool {
+        self.board.is_piece_correct(ix, iy) && self.turns[self.board[iy][ix]] == 0
+    }
+
+    /// Solved board of the grid size, with the empty spaces of the variant.
+    fn solved_board(&self) -> Board {
+        let blanks = match self.variant {
+            Variant::Sliding => self.blanks,
+            Variant::Loopover => 0,
+        };
+        Board::solved_with_blanks(self.grid_size, blanks)
+    }
+
+    /// Switch between the sliding and the Loopover variants. The board
+    /// changes shape, so it is reset and any solve given up.
+    pub fn toggle_loopover(&mut self) {
+        if self.stage != Stage::Free {
+            self.give_up();
+        }
+        self.variant = match self.variant {
+            Variant::Sliding => Variant::Loopover,
+            Variant::Loopover => Variant::Sliding,
+        };
+        self.reset();
+    }
+
+    /// Returns the indices of the empty space, the first one when there are
+    /// several.
+    pub fn index_empty(&self) -> (usize, usize) {
+        self.board.index_empty()
+    }
+
+    /// Empty space the piece at `(ix, iy)` slides into, if it can move.
+    pub fn empty_next_to(&self, ix: usize, iy: usize) -> Option<(usize, usize)> {
+        self.board.empty_next_to(ix, iy)
+    }
+
+    /// Nearest empty space in the row or the column of `(ix, iy)`, with
+    /// only pieces in between, if there is one.
+    pub fn empty_in_line(&self, ix: usize, iy: usize) -> Option<(usize, usize)> {
+        if self.board[iy][ix] == 0 {
+            return None;
+        }
+        let GridSize { width, height } = self.grid_size;
+        let rows = (0..height).map(|y| (ix, y));
+        let columns = (0..width).map(|x| (x, iy));
+        rows.chain(columns)
+            .filter(|&(x, y)| self.board[y][x] == 0)
+            .min_by_key(|&(x, y)| x.abs_diff(ix) + y.abs_diff(iy))
+    }
+
+    /// Returns the indices of `piece`.
+    pub fn index_of(&self, piece: usize) -> (usize, usize) {
+        self.board.index_of(piece)
+    }
+
+    /// When the user clicks on a piece, this function checks
+    /// if that piece can be moved and returns `true` if the piece
+    // can be moved, and `false` otherwise.
+    pub fn is_move_valid(&self, ix: usize, iy: usize) -> bool {
+        self.board.is_move_valid(ix, iy)
+    }
+
+    /// Indices of the pieces from the empty space up to `(ix, iy)`, nearest
+    /// first, if that piece is in line with the empty space. They all slide
+    /// with it, see `play_line`.
+    pub fn line_to(&self, ix: usize, iy: usize) -> Option<Vec<(usize, usize)>> {
+        let (empty_x, empty_y) = self.empty_in_line(ix, iy)?;
+        let step = |from: usize, to: usize| match from.cmp(&to) {
+            std::cmp::Ordering::Less => from + 1,
+            std::cmp::Ordering::Greater => from - 1,
+            std::cmp::Ordering::Equal => from,
+        };
+        let mut line = vec![];
+        let (mut x, mut y) = (empty_x, empty_y);
+        while (x, y) != (ix, iy) {
+            (x, y) = (step(x, ix), step(y, iy));
+            line.push((x, y));
+        }
+        Some(line)
+    }
+
+    /// Move the piece at `(ix, iy)` to the empty space.
+    /// Check if the move is valid.
+    pub fn try_move(&mut self, ix: usize, iy: usize) {
+        debug!("Trying to move piece at index {ix}, {iy}");
+        match self.board.try_move(ix, iy) {
+            Some(piece) => {
+                debug!("Move is valid");
+                self.moved(piece);
+            }
+            None => {
+                debug!("Move is invalid");
+            }
+        }
+    }
+
+    /// Slide the piece at `from` into the empty space at `to`, next to it.
+    fn try_move_to(&mut self, from: (usize, usize), to: (usize, usize)) {
+        if let Some(piece) = self.board.try_move_to(from, to) {
+            self.moved(piece);
+        }
+    }
+
+    /// Keep track of `piece` having slid into the empty space.
+    fn moved(&mut self, piece: usize) {
+        // with several empty spaces the piece alone doesn't tell where to
+        match self.board.blanks() {
+            1 => self.record(Step::Slide(piece)),
+            _ => {
+                let to = self.index_of(piece);
+                self.record(Step::SlideTo { piece, to });
+            }
+        }
+        self.hint_level = 0;
+        // Moving the same piece twice in a row undoes the first move.
+        if self.path.last() == Some(&piece) {
+            self.path.pop();
+        } else {
+            self.path.push(piece);
+        }
+    }
+
+    /// Move made by the player, as opposed to the scramble. Once the board
+    /// has been scrambled, moves are counted and the first one starts the
+    /// timer. Returns the solve if this move solved the puzzle.
+    pub fn play_move(&mut self, ix: usize, iy: usize) -> Option<history::Solve> {
+        if !self.is_move_valid(ix, iy) {
+            debug!("Move is invalid");
+            return None;
+        }
+        let piece = self.board[iy][ix];
+        self.try_move(ix, iy);
+        if self.stage != Stage::Solving {
+            return None;
+        }
+        self.undo_stack.push(Move::Slide(piece, (ix, iy)));
+        self.redo_stack.clear();
+        self.count_moves(1)
+    }
+
+    /// Slide the piece at `(ix, iy)` and the pieces between it and the
+    /// empty space, if they are in line. This counts as one move, or as
+    /// one move per piece with `count_each`. Returns the solve if this
+    /// solved the puzzle.
+    pub fn play_line(&mut self, ix: usize, iy: usize, count_each: bool) -> Option<history::Solve> {
+        let Some(line) = self.line_to(ix, iy) else {
+            debug!("Piece not in line with the empty space");
+            return None;
+        };
+        // each piece slides where the one before it was
+        let mut to = self.empty_in_line(ix, iy)?;
+        let mut slides = vec![];
+        for (x, y) in line {
+            slides.push(Move::Slide(self.board[y][x], (x, y)));
+            self.try_move_to((x, y), to);
+            to = (x, y);
+        }
+        if self.stage != Stage::Solving {
+            return None;
+        }
+        self.redo_stack.clear();
+        let moves = match count_each || slides.len() == 1 {
+            true => {
+                let moves = slides.len();
+                self.undo_stack.extend(slides);
+                moves
+            }
+            false => {
+                let pieces = slides
+                    .into_iter()
+                    .filter_map(|slide| match slide {
+                        Move::Slide(piece, _) => Some(piece),
+                        _ => None,
+                    })
+                    .collect();
+                self.undo_stack.push(Move::Line(pieces));
+                1
+            }
+        };
+        self.count_moves(moves)
+    }
+
+    /// Shift a row or a column in the Loopover variant, counted as one move.
+    /// Returns the solve if this solved the puzzle.
+    pub fn play_shift(&mut self, shift: Shift) -> Option<history::Solve> {
+        if self.variant != Variant::Loopover {
+            return None;
+        }
+        self.shifted(shift);
+        if self.stage != Stage::Solving {
+            return None;
+        }
+        self.undo_stack.push(Move::Shift(shift));
+        self.redo_stack.clear();
+        self.count_moves(1)
+    }
+
+    /// Shift a row or a column of the board and keep track of it.
+    fn shifted(&mut self, shift: Shift) {
+        self.board.shift(shift);
+        self.record(match shift {
+            Shift::Row(iy, by) => Step::ShiftRow { iy, by },
+            Shift::Column(ix, by) => Step::ShiftColumn { ix, by },
+        });
+        self.hint_level = 0;
+    }
+
+    /// Count `moves` moves of the solve, the first one starts the timer.
+    /// Returns the solve if these moves solved the puzzle.
+    fn count_moves(&mut self, moves: usize) -> Option<history::Solve> {
+        self.resume();
+        self.move_count += moves;
+        self.solve_started.get_or_insert_with(Instant::now);
+        match self.is_solved() {
+            true => Some(self.finish_solve()),
+            false => None,
+        }
+    }
+
+    /// Take back the last move of the solve, it no longer counts.
+    /// Returns `false` if there is no move to undo.
+    pub fn undo(&mut self) -> bool {
+        if self.stage != Stage::Solving {
+            return false;
+        }
+        let Some(mut last) = self.undo_stack.pop() else {
+            return false;
+        };
+        match &mut last {
+            // the piece is still next to the cell it came from
+            Move::Slide(piece, cell) => {
+                let from = self.index_of(*piece);
+                self.try_move_to(from, *cell);
+                *cell = from;
+            }
+            // the farthest piece is next to the empty space now
+            Move::Line(pieces) => self.slide_line(pieces, true),
+            Move::Shift(shift) => self.shifted(shift.inverse()),
+            Move::Turn(piece) => {
+                self.turns[*piece] = (self.turns[*piece] + 3) % 4;
+                self.record_turn(*piece);
+                self.hint_level = 0;
+            }
+        }
+        self.resume();
+        self.move_count -= 1;
+        self.redo_stack.push(last);
+        true
+    }
+
+    /// Play again the last move undone, it counts again. Returns `false`
+    /// if there is no move to redo. Redone moves never solve the puzzle,
+    /// the positions they go back to were not solved.
+    pub fn redo(&mut self) -> bool {
+        if self.stage != Stage::Solving {
+            return false;
+        }
+        let Some(mut next) = self.redo_stack.pop() else {
+            return false;
+        };
+        match &mut next {
+            Move::Slide(piece, cell) => {
+                let from = self.index_of(*piece);
+                self.try_move_to(from, *cell);
+                *cell = from;
+            }
+            Move::Line(pieces) => self.slide_line(pieces, false),
+            Move::Shift(shift) => self.shifted(*shift),
+            Move::Turn(piece) => {
+                self.turns[*piece] = (self.turns[*piece] + 1) % 4;
+                self.record_turn(*piece);
+                self.hint_level = 0;
+            }
+        }
+        self.resume();
+        self.move_count += 1;
+        self.undo_stack.push(next);
+        true
+    }
+
+    /// Slide the pieces of a line move again one cell along the line, away
+    /// from where the empty space was to `undo` it, towards it to redo it.
+    /// The pieces are still in line, so the first two give the direction.
+    fn slide_line(&mut self, pieces: &[usize], undo: bool) {
+        let ((x0, y0), (x1, y1)) = match pieces {
+            [first, second, ..] => (self.index_of(*first), self.index_of(*second)),
+            _ => return,
+        };
+        let (mut dx, mut dy) = (x1 as isize - x0 as isize, y1 as isize - y0 as isize);
+        let mut order = pieces.to_vec();
+        // the piece moving first is the one next to the empty space
+        if undo {
+            order.reverse();
+        } else {
+            (dx, dy) = (-dx, -dy);
+        }
+        for piece in order {
+            let (ix, iy) = self.index_of(piece);
+            let to = (ix.wrapping_add_signed(dx), iy.wrapping_add_signed(dy));
+            self.try_move_to((ix, iy), to);
+        }
+    }
+
+    /// Piece the hint shown is about, if one is shown.
+    pub fn hint_piece(&self) -> Option<usize> {
+        match self.hint_level {
+            0 => None,
+            _ => self.hint,
+        }
+    }
+
+    /// Piece to slide next towards the solution: the first move of the
+    /// shortest solution if the solver finds it in `HINT_TIME`, else the way back
+    /// of the scramble, else the move bringing the pieces closest to their
+    /// place.
+    fn best_move(&self) -> Option<usize> {
+        solver::best_move_within(&self.board, HINT_TIME)
+            .or_else(|| self.solution().first().copied())
+            .or_else(|| solver::closer_move(&self.board))
+    }
+
+    /// Show more of the hint for this position: the region to work on,
+    /// then the piece, then the move. Each level adds to the hint cost.
+    /// Returns the level now shown, or why there is no hint.
+    pub fn next_hint(&mut self) -> Result<usize, String> {
+        if self.stage != Stage::Solving {
+            return Err(String::from("Hints are for scrambled boards"));
+        }
+        if self.hint_level == 0 {
+            // the solver follows a single empty space
+            let reason = match self.variant {
+                Variant::Loopover => "No hints for Loopover boards",
+                Variant::Sliding if self.blanks > 1 => "No hints with several empty spaces",
+                Variant::Sliding => "No hint found",
+            };
+            self.hint = Some(self.best_move().ok_or_else(|| String::from(reason))?);
+        }
+        if self.hint_level < HINT_COSTS.len() {
+            self.hint_cost += HINT_COSTS[self.hint_level];
+            self.hint_level += 1;
+        }
+        Ok(self.hint_level)
+    }
+
+    /// Turn the piece at `(ix, iy)` a quarter clockwise, in the rotation
+    /// variant. Counts as a move like `play_move`, and returns the solve
+    /// if this solved the puzzle.
+    pub fn play_rotation(&mut self, ix: usize, iy: usize) -> Option<history::Solve> {
+        let piece = self.board[iy][ix];
+        if !self.flag_rotation || piece == 0 {
+            return None;
+        }
+        self.turns[piece] = (self.turns[piece] + 1) % 4;
+        self.record_turn(piece);
+        self.hint_level = 0;
+        if self.stage != Stage::Solving {
+            return None;
+        }
+        self.undo_stack.push(Move::Turn(piece));
+        self.redo_stack.clear();
+        self.count_moves(1)
+    }
+
+    /// Switch the rotation variant on or off. Off, all the pieces are
+    /// turned back the right way up.
+    pub fn toggle_rotation(&mut self) {
+        self.flag_rotation = !self.flag_rotation;
+        if !self.flag_rotation {
+            self.turns.fill(0);
+        }
+    }
+
+    /// Replay of a solve starting from the board as it is now.
+    fn new_recording(&self) -> Replay {
+        Replay {
+            width: self.grid_size.width,
+            height: self.grid_size.height,
+            image: self.image_list.get(self.image_index_current).cloned(),
+            board: self.board.rows().map(|row| row.to_vec()).collect(),
+            turns: self.turns.clone(),
+            steps: vec![],
+        }
+    }
+
+    /// Add `step` to the replay of the solve, if one is in progress.
+    fn record(&mut self, step: Step) {
+        if self.stage != Stage::Solving {
+            return;
+        }
+        let seconds = self.elapsed().as_secs_f64();
+        if let Some(recording) = self.recording.as_mut() {
+            recording.steps.push((seconds, step));
+        }
+    }
+
+    /// Add the turns of `piece` to the replay of the solve.
+    fn record_turn(&mut self, piece: usize) {
+        let turns = self.turns[piece];
+        self.record(Step::Turn { piece, turns });
+    }
+
+    /// Set the board up as the replay starts, on its grid size. The stage
+    /// is left free, so the steps played back count as nothing.
+    pub fn start_replay(&mut self, replay: &Replay) -> Result<(), String> {
+        let game = SavedGame {
+            board: replay.board.clone(),
+            turns: replay.turns.clone(),
+            path: vec![],
+            image: None,
+            playing: false,
+            moves: 0,
+            seconds: 0.0,
+            hint_cost: 0,
+            seed: None,
+            rotation: self.flag_rotation,
+        };
+        self.restore_game(game)
+    }
+
+    /// Play `step` of a replay back.
+    pub fn play_step(&mut self, step: Step) {
+        match step {
+            Step::Slide(piece) => {
+                let (ix, iy) = self.index_of(piece);
+                self.try_move(ix, iy);
+            }
+            Step::SlideTo { piece, to } => {
+                let from = self.index_of(piece);
+                self.try_move_to(from, to);
+            }
+            Step::ShiftRow { iy, by } => self.board.shift(Shift::Row(iy, by)),
+            Step::ShiftColumn { ix, by } => self.board.shift(Shift::Column(ix, by)),
+            Step::Turn { piece, turns } => {
+                if let Some(turned) = self.turns.get_mut(piece) {
+                    *turned = turns % 4;
+                }
+            }
+            Step::Jump { board, turns } => match Board::from_rows(board) {
+                Ok(board) if board.size() == self.grid_size && covers_pieces(&turns, &board) => {
+                    self.board = board;
+                    self.turns = turns;
+                    self.turns.resize(self.grid_size.cells() + 1, 0);
+                }
+                _ => debug!("Replay step doesn't fit the board"),
+            },
+        }
+    }
+
+    /// Frames of `replay` played on this board, `size` pixels on their
+    /// longest side, each with the seconds of the solve it shows. The board
+    /// is left as the replay starts.
+    pub fn replay_frames(
+        &mut self,
+        replay: &Replay,
+        size: u32,
+        numbers: bool,
+    ) -> Result<Vec<(f64, image::RgbaImage)>, String> {
+        self.start_replay(replay)?;
+        let steps = std::iter::once((0.0, None)).chain(
+            replay
+                .steps
+                .iter()
+                .map(|(seconds, step)| (*seconds, Some(step))),
+        );
+        let mut frames = vec![];
+        for (seconds, step) in steps {
+            if let Some(step) = step {
+                self.play_step(step.clone());
+            }
+            let picture = self.picture(self.img_size(), numbers);
+            let scale = size as f32 / picture.width().max(picture.height()) as f32;
+            let (width, height) = (
+                (picture.width() as f32 * scale) as u32,
+                (picture.height() as f32 * scale) as u32,
+            );
+            frames.push((seconds, image::imageops::thumbnail(&picture, width, height)));
+        }
+        self.start_replay(replay)?;
+        Ok(frames)
+    }
+
+    /// Record the solve in the history, and save its replay.
+    fn finish_solve(&mut self) -> history::Solve {
+        self.stage = Stage::Free;
+        if let Some(replay) = self.recording.take() {
+            if let Err(e) = replay.save() {
+                println!("Error saving the replay: {e}");
+            }
+        }
+        let seconds = self.elapsed().as_secs_f64();
+        let solve = history::Solve {
+            timestamp: clock::unix_now(),
+            image: self.image_name(),
+            size: self.grid_size.width,
+            height: Some(self.grid_size.height).filter(|&h| h != self.grid_size.width),
+            moves: self.move_count,
+            seconds,
+            seed: self.scramble_seed,
+            hint_cost: self.hint_cost,
+        };
+        if let Err(e) = history::record(&solve) {
+            println!("Error recording solve: {e}");
+        }
+        solve
+    }
+
+    /// The game as it is now, to save it.
+    pub fn saved_game(&self) -> SavedGame {
+        SavedGame {
+            board: self.board.rows().map(|row| row.to_vec()).collect(),
+            turns: self.turns.clone(),
+            path: self.path.clone(),
+            image: self.image_list.get(self.image_index_current).cloned(),
+            playing: self.stage == Stage::Solving,
+            moves: self.move_count,
+            seconds: self.elapsed().as_secs_f64(),
+            hint_cost: self.hint_cost,
+            seed: self.scramble_seed,
+            rotation: self.flag_rotation,
+        }
+    }
+
+    /// Swap the pieces of cells `a` and `b`, `(ix, iy)`, to set a position
+    /// up in the editor. Any solve in progress is dropped.
+    pub fn swap_cells(&mut self, a: (usize, usize), b: (usize, usize)) {
+        if self.stage != Stage::Free {
+            self.give_up();
+        }
+        self.board.swap(a, b);
+        self.path.clear();
+        self.hint_level = 0;
+    }
+
+    /// Start a solve from `board`, on its grid size, eg, a position shared
+    /// in the notation of `Board`. The board must be solvable.
+    pub fn start_from_position(&mut self, board: Board) {
+        if board.size() != self.grid_size {
+            self.set_grid_size(board.size());
+        }
+        self.variant = board.variant();
+        self.blanks = board.blanks().max(1);
+        self.reset();
+        self.challenge = None;
+        self.scramble_rng = None;
+        self.scramble_seed = None;
+        self.board = board;
+        self.start_solve();
+    }
+
+    /// Go back to a saved game, on the grid size of its board. The image is
+    /// left as it is. The timer waits for the next move to start again.
+    pub fn restore_game(&mut self, game: SavedGame) -> Result<(), String> {
+        let board = Board::from_rows(game.board)?;
+        if !board.is_solvable() {
+            return Err(String::from("Board can't be solved"));
+        }
+        if !covers_pieces(&game.turns, &board) {
+            return Err(String::from("Turns don't match the board"));
+        }
+        if board.size() != self.grid_size {
+            self.set_grid_size(board.size());
+        }
+        self.variant = board.variant();
+        self.blanks = board.blanks().max(1);
+        self.reset();
+        self.challenge = None;
+        self.scramble_rng = None;
+        self.board = board;
+        self.turns = game.turns;
+        self.turns.resize(self.grid_size.cells() + 1, 0);
+        self.path = game.path;
+        if game.playing {
+            self.stage = Stage::Solving;
+            self.recording = Some(self.new_recording());
+        }
+        self.move_count = game.moves;
+        self.hint_cost = game.hint_cost;
+        self.scramble_seed = game.seed;
+        self.flag_rotation = game.rotation;
+        if game.moves > 0 {
+            let now = Instant::now();
+            self.solve_started = Some(now - Duration::from_secs_f64(game.seconds));
+            self.paused_at = Some(now);
+        }
+        Ok(())
+    }
+
+    /// Drop the solve in progress without recording it, eg, when the
+    /// board is solved automatically.
+    pub fn give_up(&mut self) {
+        self.stage = Stage::Free;
+        self.move_count = 0;
+        self.solve_started = None;
+        self.paused_at = None;
+        self.recording = None;
+        self.hint_level = 0;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Time spent solving, not counting pauses.
+    pub fn elapsed(&self) -> Duration {
+        match self.solve_started {
+            Some(started) => self.paused_at.unwrap_or_else(Instant::now) - started,
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Stop the timer of the solve in progress, if any.
+    pub fn pause(&mut self) {
+        if self.solve_started.is_some() && self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// Restart the timer, leaving out the time it was paused.
+    pub fn resume(&mut self) {
+        if let (Some(paused_at), Some(started)) = (self.paused_at.take(), &mut self.solve_started) {
+            *started += paused_at.elapsed();
+        }
+    }
+
+    /// Start scrambling the board. If the current image comes from a pack
+    /// with curated seeds, the scramble starts from the solved board and
+    /// is driven by one of those seeds. Otherwise, unless `animated`, the
+    /// board is scrambled at once, see `scramble_instantly`. Either way the
+    /// randomness comes from `rng`, so the same seed gives the same boards.
+    /// A challenge being played is dropped, the board is no longer its own.
+    pub fn start_scramble(&mut self, animated: bool, rng: &mut StdRng) {
+        self.challenge = None;
+        let curated_seed = self
+            .current_pack_image()
+            .filter(|info| !info.seeds.is_empty())
+            .map(|info| info.seeds[rng.gen_range(0..info.seeds.len())]);
+        if curated_seed.is_none() {
+            self.scramble_rng = Some(StdRng::seed_from_u64(rng.gen()));
+            self.scramble_seed = None;
+        }
+        match curated_seed {
+            Some(seed) => self.start_seeded_scramble(seed),
+            None if !animated => self.scramble_instantly(),
+            None => {
+                // the solve in progress is dropped, it starts after the scramble
+                self.stage = Stage::Scrambling;
+                self.scramble_clock = Instant::now();
+                self.move_count = 0;
+                self.solve_started = None;
+                self.paused_at = None;
+            }
+        }
+    }
+
+    /// Scramble the board at once into a random solvable position, and
+    /// start the solve. The moves getting there are not known, so there
+    /// are no hints nor solution for it.
+    fn scramble_instantly(&mut self) {
+        self.reset();
+        for _ in 0..SCRAMBLE_DRAWS {
+            let rng = self.scramble_rng.get_or_insert_with(StdRng::from_entropy);
+            self.board.scramble_random(rng);
+            if self.is_scrambled_enough() {
+                break;
+            }
+        }
+        self.start_solve();
+    }
+
+    /// Scramble the solved board with random moves driven by `seed`,
+    /// so the same seed always gives the same board.
+    pub fn start_seeded_scramble(&mut self, seed: u64) {
+        debug!("Scrambling with seed {seed}");
+        self.reset();
+        self.scramble_rng = Some(StdRng::seed_from_u64(seed));
+        self.scramble_seed = Some(seed);
+        self.scramble_count = 0;
+        self.stage = Stage::Scrambling;
+        self.scramble_clock = Instant::now();
+    }
+
+    /// Start a new challenge on the current grid size with a seed drawn
+    /// from `rng`.
+    pub fn new_challenge(&mut self, rng: &mut StdRng) -> Challenge {
+        let challenge = Challenge {
+            size: self.grid_size,
+            seed: rng.gen::<u32>() as u64,
+        };
+        self.challenge = Some(challenge);
+        self.start_seeded_scramble(challenge.seed);
+        challenge
+    }
+
+    /// Play the moves of the scramble due by now, one every `interval`
+    /// since it started, rather than one per frame.
+    pub fn scramble_for(&mut self, interval: Duration) {
+        let mut due = (self.scramble_clock.elapsed().as_secs_f64() / interval.as_secs_f64()) as u32;
+        if due > SCRAMBLE_CATCH_UP {
+            due = SCRAMBLE_CATCH_UP;
+            self.scramble_clock = Instant::now();
+        } else {
+            self.scramble_clock += interval * due;
+        }
+        for _ in 0..due {
+            if self.stage == Stage::Scrambling {
+                self.scramble_step();
+            }
+        }
+    }
+
+    /// Random moves of an animated scramble of this board.
+    pub fn scramble_length(&self) -> usize {
+        self.scramble_moves
+            .unwrap_or_else(|| default_scramble_moves(self.grid_size))
+    }
+
+    /// Do one step of the scramble, the solve starts after the last one.
+    /// Seeded scrambles always take the default number of moves, so the
+    /// same seed gives the same board whatever the command line says.
+    pub fn scramble_step(&mut self) {
+        self.do_one_random_move();
+        self.scramble_count += 1;
+        let moves = match self.scramble_seed {
+            Some(_) => default_scramble_moves(self.grid_size),
+            None => self.scramble_length(),
+        };
+        // boards still too close to solved are scrambled some more
+        let done = self.is_scrambled_enough() || self.scramble_count > moves * SCRAMBLE_EXTENSION;
+        if self.scramble_count > moves && done {
+            self.scramble_count = 0;
+            self.start_solve();
+        }
+    }
+
+    /// Returns `true` if the board is far enough from solved to start the
+    /// solve. Seeded scrambles always take the default difficulty, as they
+    /// take the default number of moves.
+    fn is_scrambled_enough(&self) -> bool {
+        let difficulty = match self.scramble_seed {
+            Some(_) => SCRAMBLE_DIFFICULTY,
+            None => self.scramble_difficulty,
+        };
+        self.board.manhattan_distance() as f32 >= difficulty * self.grid_size.average_distance()
+    }
+
+    /// Start solving the scrambled board.
+    fn start_solve(&mut self) {
+        self.stage = Stage::Solving;
+        self.move_count = 0;
+        self.solve_started = None;
+        self.bookmarks.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.hint_cost = 0;
+        self.peeks = 0;
+        if self.flag_rotation {
+            self.turn_randomly();
+        }
+        self.recording = Some(self.new_recording());
+    }
+
+    /// Turn every piece randomly, with the scramble generator.
+    fn turn_randomly(&mut self) {
+        let pieces = self.grid_size.cells() - self.board.blanks();
+        let rng = self.scramble_rng.get_or_insert_with(StdRng::from_entropy);
+        for turns in self.turns.iter_mut().skip(1).take(pieces) {
+            *turns = rng.gen_range(0..4);
+        }
+    }
+
+    /// Save the current position in a new bookmark slot, returns its number.
+    pub fn bookmark(&mut self) -> usize {
+        if self.bookmarks.len() == BOOKMARK_SLOTS {
+            self.bookmarks.remove(0);
+        }
+        self.bookmarks.push(Bookmark {
+            board: self.board.clone(),
+            path: self.path.clone(),
+            turns: self.turns.clone(),
+        });
+        self.bookmarks.len()
+    }
+
+    /// Go back to the newest bookmark. When the board is already at a
+    /// bookmark, go to the one before it instead, so repeating this steps
+    /// through all of them. Returns the number of the slot restored.
+    /// The moves and the time of the solve keep counting.
+    pub fn restore_bookmark(&mut self) -> Option<usize> {
+        let count = self.bookmarks.len();
+        let current = |b: &Bookmark| b.board == self.board && b.turns == self.turns;
+        let index = match self.bookmarks.iter().position(current) {
+            Some(0) | None => count.checked_sub(1)?,
+            Some(index) => index - 1,
+        };
+        let bookmark = self.bookmarks[index].clone();
+        self.board = bookmark.board;
+        self.path = bookmark.path;
+        self.turns = bookmark.turns;
+        self.record(Step::Jump {
+            board: self.board.rows().map(|row| row.to_vec()).collect(),
+            turns: self.turns.clone(),
+        });
+        self.hint_level = 0;
+        // the moves since are not on the way back to the bookmark
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        Some(index + 1)
+    }
+
+    /// One random move, with the scramble generator.
+    fn do_one_random_move(&mut self) {
+        let rng = self.scramble_rng.get_or_insert_with(StdRng::from_entropy);
+        match self.variant {
+            Variant::Sliding => {
+                let piece = self.board.random_move(rng);
+                self.moved(piece);
+            }
+            Variant::Loopover => {
+                self.board.random_shift(rng);
+            }
+        }
+    }
+
+    /// The scrambled picture as shown on the board, `size` pixels on its
+    /// longest side, with the number of each piece drawn on it if `numbers`
+    /// is set.
+    pub fn picture(&self, size: u32, numbers: bool) -> image::RgbaImage {
+        let compose = |solved, pieces| {
+            pieces::compose(solved, &self.board, &self.turns, pieces, self.flag_jigsaw).to_rgba8()
+        };
+        let mut picture = match size == self.img_size() {
+            true => compose(&self.image_solved, &self.pieces),
+            false => {
+                let (width, height) = image_dimensions(self.grid_size, size);
+                let solved = self.fill(width, height);
+                let pieces = pieces::cut(&solved, self.grid_size, self.flag_jigsaw);
+                compose(&solved, &pieces)
+            }
+        };
+        if numbers {
+            pieces::draw_numbers(&mut picture, &self.board);
+        }
+        picture
+    }
+
+    /// Moves which solve the board, as the pieces to slide into the empty
+    /// space in order. These undo every move since the board was solved,
+    /// so they are not the shortest solution. Pieces turned in the rotation
+    /// variant are left as they are.
+    pub fn solution(&self) -> Vec<usize> {
+        self.path.iter().rev().copied().collect()
+    }
+
+    /// Resize the solved image to `img_size` pixels on its longest side.
+    pub fn resize(&mut self, img_size: u32) {
+        let (width, height) = image_dimensions(self.grid_size, img_size);
+        let cell_size = width / self.grid_size.width as u32;
+        self.image_solved = match self.flag_generated {
+            true => artwork::generate(self.grid_size, cell_size),
+            false if self.flag_colors => artwork::gradient(self.grid_size, cell_size),
+            false => self.fill(width, height),
+        };
+        self.pieces = pieces::cut(&self.image_solved, self.grid_size, self.flag_jigsaw);
+        self.textures.clear();
+        self.thumbnail = None;
+    }
+
+    /// Resize the image with another filter, from now on.
+    pub fn set_filter(&mut self, filter: ResizeFilter) {
+        self.filter = filter;
+        self.resize(self.img_size());
+    }
+
+    /// Region of the original image played, resized to `width` by `height`
+    /// pixels with the filter chosen, see `pieces::fill`.
+    fn fill(&self, width: u32, height: u32) -> image::DynamicImage {
+        pieces::fill(
+            &self.image_original,
+            self.crop,
+            width,
+            height,
+            self.filter.filter_type(),
+        )
+    }
+
+    /// Play the `region` of the original image, `[x, y, width, height]` in
+    /// pixels from its top left, or its middle if `None`.
+    pub fn set_crop(&mut self, region: Option<[u32; 4]>) {
+        self.crop = region;
+        self.resize(self.img_size());
+    }
+
+    /// Longest side of the solved image, in pixels.
+    pub fn img_size(&self) -> u32 {
+        let (width, height) = self.image_solved.dimensions();
+        width.max(height)
+    }
+
+    /// Switch the jigsaw edges of the pieces on or off.
+    pub fn toggle_jigsaw(&mut self) {
+        self.flag_jigsaw = !self.flag_jigsaw;
+        self.resize(self.img_size());
+    }
+
+    /// Switch between the solid color tiles and the image.
+    pub fn toggle_colors(&mut self) {
+        self.flag_colors = !self.flag_colors;
+        self.resize(self.img_size());
+    }
+
+    /// Make up a new picture, when there are no images. Returns `false`
+    /// if there are images, the picture is one of them.
+    pub fn new_procedural(&mut self) -> bool {
+        if !self.image_list.is_empty() {
+            return false;
+        }
+        self.procedural_seed = random();
+        self.change_image();
+        true
+    }
+
+    /// Switch between the generated tile art and the image.
+    pub fn toggle_generated(&mut self) {
+        self.flag_generated = !self.flag_generated;
+        self.resize(self.img_size());
+    }
+
+    /// Increment the image index and calls `change_image()`.
+    pub fn next_image(&mut self) {
+        if self.image_list.is_empty() {
+            return;
+        }
+        self.image_index_current = (self.image_index_current + 1) % self.image_list.len();
+        self.change_image();
+    }
+    /// Decrement the image index and calls `change_image()`.
+    pub fn previous_image(&mut self) {
+        if self.image_list.is_empty() {
+            return;
+        }
+        if self.image_index_current == 0 {
+            self.image_index_current = self.image_list.len() - 1;
+        } else {
+            self.image_index_current -= 1;
+        }
+        self.change_image();
+    }
+    /// Show another image of `image_list`, picked at random.
+    pub fn random_image(&mut self) {
+        let count = self.image_list.len();
+        if count < 2 {
+            return;
+        }
+        // any image but the current one
+        let index = (self.image_index_current + random_range(1, count)) % count;
+        self.set_image(index);
+    }
+    /// Show the image at `index` in `image_list`.
+    pub fn set_image(&mut self, index: usize) {
+        if index < self.image_list.len() {
+            self.image_index_current = index;
+            self.change_image();
+        }
+    }
+    /// Change the image to the one at the current index. Images which can't
+    /// be opened are skipped for the next one, left in the list so it keeps
+    /// the indices of the collection, and a picture is made up from
+    /// `procedural_seed` if none can.
+    pub fn change_image(&mut self) {
+        let count = self.image_list.len();
+        let opened = (0..count).find_map(|step| {
+            let index = (self.image_index_current + step) % count;
+            let path = &self.image_list[index];
+            match open_image(path, self.image_info.get(index).and_then(Option::as_ref)) {
+                Ok(image) => Some((index, image)),
+                Err(e) => {
+                    println!("Error opening image {}: {e}", path.display());
+                    None
+                }
+            }
+        });
+        self.image_original = match opened {
+            Some((index, image)) => {
+                self.image_index_current = index;
+                image
+            }
+            None => procedural::generate(self.procedural_seed, self.img_size()),
+        };
+        self.crop = None;
+        self.resize(self.img_size());
+    }
+
+    /// Take the images of `collection` as they are now, eg, once images
+    /// were added to its folder. The current image stays, and its place
+    /// in the list is kept if it is still in it. A made-up picture gives
+    /// way to the first image if there were no images, unless playing.
+    pub fn refresh_collection(&mut self, collection: &packs::Collection) {
+        let current = self.image_list.get(self.image_index_current).cloned();
+        self.image_list = collection.images.clone();
+        self.image_info = collection.info.clone();
+        let index = current
+            .as_ref()
+            .and_then(|current| self.image_list.iter().position(|p| p == current));
+        self.image_index_current = index.unwrap_or(0);
+        if current.is_none() && !self.image_list.is_empty() && self.stage == Stage::Free {
+            self.change_image();
+        }
+    }
+
+    /// Show the images of another collection, starting with its first one.
+    pub fn set_collection(&mut self, collection: &packs::Collection, index: usize) {
+        self.collection_current = index;
+        self.image_list = collection.images.clone();
+        self.image_info = collection.info.clone();
+        self.image_index_current = 0;
+        self.change_image();
+    }
+
+    /// Hint about the grid sizes recommended by the pack for this image,
+    /// if the current size is not one of them.
+    pub fn recommended_sizes(&self) -> Option<String> {
+        let info = self.current_pack_image()?;
+        let GridSize { width, height } = self.grid_size;
+        if info.sizes.is_empty() || (width == height && info.sizes.contains(&width)) {
+            return None;
+        }
+        let sizes: Vec<_> = info.sizes.iter().map(|n| format!("{n}x{n}")).collect();
+        Some(format!("Recommended size {}", sizes.join(", ")))
+    }
+
+    /// File name of the current image, empty for the blank image.
+    pub fn image_name(&self) -> String {
+        self.image_list
+            .get(self.image_index_current)
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Name, position in the list and resolution of the current image,
+    /// for the status bar.
+    pub fn image_status(&self) -> String {
+        if self.flag_generated {
+            return String::from("Generated art");
+        }
+        if self.flag_colors {
+            return String::from("Color gradient");
+        }
+        if self.image_list.is_empty() {
+            let kind = procedural::Kind::of_seed(self.procedural_seed);
+            return format!("{} {:X}", kind.name(), self.procedural_seed);
+        }
+        let (width, height) = self.image_original.dimensions();
+        format!(
+            "{}   {}/{}   {width}x{height}",
+            self.image_name(),
+            self.image_index_current + 1,
+            self.image_list.len()
+        )
+    }
+
+    /// Pack details of the current image, if it comes from a pack.
+    fn current_pack_image(&self) -> Option<&packs::PackImage> {
+        self.image_info.get(self.image_index_current)?.as_ref()
+    }
+}
+
+/// Width and height of the images of a board of `grid_size`, `img_size`
+/// pixels on the longest side. The cells are square and the same number
+/// of pixels, so the image has the shape of the grid.
+pub fn image_dimensions(grid_size: GridSize, img_size: u32) -> (u32, u32) {
+    let cell_size = (img_size / grid_size.longest() as u32).max(1);
+    (
+        cell_size * grid_size.width as u32,
+        cell_size * grid_size.height as u32,
+    )
+}
+
+/// Random moves of an animated scramble of a board of `grid_size`, unless
+/// set: enough to mix big boards as well as small ones.
+pub fn default_scramble_moves(grid_size: GridSize) -> usize {
+    (SCRAMBLE_MOVES * grid_size.cells() / 16).max(1)
+}
+
+/// Turns of the pieces of a board of `grid_size`, by piece number, all the
+/// right way up. There is room for a piece in every cell, as in the
+/// Loopover variant, and the first is for the empty space.
+fn no_turns(grid_size: GridSize) -> Vec<usize> {
+    vec![0; grid_size.cells() + 1]
+}
+
+/// Returns `true` if `turns` has the turns of every piece of `board`. Saved
+/// games and replays from before the Loopover variant have one less.
+fn covers_pieces(turns: &[usize], board: &Board) -> bool {
+    turns.len() > board.size().cells() - board.blanks()
+}
+
+/// Open an image the right way up, as its EXIF metadata says for photos,
+/// then cropped to the region chosen by the pack, if any, and shrunk to
+/// `MAX_IMAGE_SIZE` if bigger.
+pub fn open_image(
+    path: &Path,
+    info: Option<&packs::PackImage>,
+) -> image::ImageResult<image::DynamicImage> {
+    let image = image::open(path)?;
+    let image = match exif::orientation(path) {
+        Some(orientation) => exif::upright(image, orientation),
+        None => image,
+    };
+    let image = match info.and_then(|info| info.crop) {
+        Some([x, y, w, h]) => {
+            let (width, height) = image.dimensions();
+            let x = x.min(width - 1);
+            let y = y.min(height - 1);
+            image.crop_imm(x, y, w.min(width - x).max(1), h.min(height - y).max(1))
+        }
+        None => image,
+    };
+    let (width, height) = image.dimensions();
+    if width.max(height) <= MAX_IMAGE_SIZE {
+        return Ok(image);
+    }
+    debug!("Shrinking {} from {width}x{height}", path.display());
+    Ok(image.resize(
+        MAX_IMAGE_SIZE,
+        MAX_IMAGE_SIZE,
+        image::imageops::FilterType::Triangle,
+    ))
+}
+
+/// White image of `size` by `size` pixels, shown when there are no images.
+fn blank_image(size: u32) -> image::DynamicImage {
+    let white = image::RgbaImage::from_pixel(size, size, image::Rgba([255, 255, 255, 255]));
+    image::DynamicImage::ImageRgba8(white)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn a_new_scramble_drops_the_challenge() {
+        let images = packs::Collection::from_images("Test", vec![]);
+        let mut puzzle = Puzzle::new(GridSize::square(3), &images, 0, 32);
+        let mut rng = StdRng::seed_from_u64(0);
+        for animated in [false, true] {
+            let challenge = puzzle.new_challenge(&mut rng);
+            assert_eq!(puzzle.challenge, Some(challenge));
+            puzzle.start_scramble(animated, &mut rng);
+            assert_eq!(puzzle.challenge, None);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn undo_and_redo_retrace_the_moves(
+            size in 2..=5usize,
+            seed: u64,
+            clicks in prop::collection::vec((0..5usize, 0..5usize), 0..60),
+        ) {
+            let grid_size = GridSize::square(size);
+            let images = packs::Collection::from_images("Test", vec![]);
+            let mut puzzle = Puzzle::new(grid_size, &images, 0, 32);
+            puzzle.start_scramble(false, &mut StdRng::seed_from_u64(seed));
+            prop_assert_eq!(puzzle.stage, Stage::Solving);
+            let mut positions = vec![(puzzle.board.clone(), puzzle.move_count)];
+            for (ix, iy) in clicks {
+                if !puzzle.is_move_valid(ix % size, iy % size) {
+                    continue;
+                }
+                if puzzle.play_move(ix % size, iy % size).is_some() {
+                    // solved, the moves can no longer be undone
+                    return Ok(());
+                }
+                positions.push((puzzle.board.clone(), puzzle.move_count));
+            }
+            for (board, move_count) in positions.iter().rev().skip(1) {
+                prop_assert!(puzzle.undo());
+                prop_assert_eq!(&puzzle.board, board);
+                prop_assert_eq!(puzzle.move_count, *move_count);
+            }
+            prop_assert!(!puzzle.undo());
+            for (board, move_count) in positions.iter().skip(1) {
+                prop_assert!(puzzle.redo());
+                prop_assert_eq!(&puzzle.board, board);
+                prop_assert_eq!(puzzle.move_count, *move_count);
+            }
+            prop_assert!(!puzzle.redo());
+        }
+    }
+}
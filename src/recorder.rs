@@ -0,0 +1,135 @@
+//! Recording of the puzzle window, started and stopped with `F9`.
+//!
+//! Frames are captured as PNG into a folder of the profile while recording.
+//! When the recording stops they are encoded to MP4 with `ffmpeg` if it is
+//! installed, or to an animated GIF otherwise.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use nannou::image::codecs::gif::{GifEncoder, Repeat};
+use nannou::image::{self, Delay, Frame};
+use nannou::window::Window;
+
+use crate::{clock, profile};
+
+/// Folder in the profile where recordings are saved.
+static RECORDINGS_DIR: &str = "recordings";
+
+/// Frames per second of the recordings.
+static RECORD_FPS: u32 = 20;
+
+pub struct Recorder {
+    name: String,                  // File name of the recording, without extension
+    frames_dir: PathBuf,           // Folder with the captured frames
+    frame_count: usize,            // Number of frames captured so far
+    last_capture: Option<Instant>, // Time of the last frame captured
+}
+
+impl Recorder {
+    /// Start a new recording.
+    pub fn start() -> io::Result<Self> {
+        let name = format!("puzzle_{}", clock::timestamp_millis());
+        let frames_dir = profile::profile_path(RECORDINGS_DIR).join(&name);
+        fs::create_dir_all(&frames_dir)?;
+        debug!("Recording frames into {}", frames_dir.display());
+        Ok(Recorder {
+            name,
+            frames_dir,
+            frame_count: 0,
+            last_capture: None,
+        })
+    }
+
+    /// Capture the next frame drawn in `window`, if one is due.
+    pub fn capture(&mut self, window: &Window) {
+        let interval = Duration::from_secs(1) / RECORD_FPS;
+        if self
+            .last_capture
+            .is_some_and(|last| last.elapsed() < interval)
+        {
+            return;
+        }
+        self.last_capture = Some(Instant::now());
+        window.capture_frame(frame_path(&self.frames_dir, self.frame_count));
+        self.frame_count += 1;
+    }
+
+    /// Stop recording and encode the video in the background.
+    /// The receiver gets the path of the video once it is done.
+    pub fn finish(self, window: &Window) -> Receiver<io::Result<PathBuf>> {
+        if let Err(e) = window.await_capture_frame_jobs() {
+            println!("Error saving the recorded frames: {e:?}");
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let result = self.encode();
+            if result.is_ok() {
+                let _ = fs::remove_dir_all(&self.frames_dir);
+            }
+            let _ = sender.send(result);
+        });
+        receiver
+    }
+
+    fn encode(&self) -> io::Result<PathBuf> {
+        if self.frame_count == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "no frames recorded"));
+        }
+        let dir = profile::profile_path(RECORDINGS_DIR);
+        let mp4 = dir.join(format!("{}.mp4", self.name));
+        match self.encode_mp4(&mp4) {
+            Ok(()) => Ok(mp4),
+            Err(e) => {
+                debug!("Could not encode with ffmpeg ({e}), making a GIF");
+                let gif = dir.join(format!("{}.gif", self.name));
+                self.encode_gif(&gif)?;
+                Ok(gif)
+            }
+        }
+    }
+
+    fn encode_mp4(&self, path: &Path) -> io::Result<()> {
+        let status = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-framerate"])
+            .arg(RECORD_FPS.to_string())
+            .arg("-i")
+            .arg(self.frames_dir.join("frame_%05d.png"))
+            // H.264 needs even dimensions
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .arg(path)
+            .status()?;
+        match status.success() {
+            true => Ok(()),
+            false => Err(Error::other(format!("ffmpeg {status}"))),
+        }
+    }
+
+    fn encode_gif(&self, path: &Path) -> io::Result<()> {
+        let to_io = |e: image::ImageError| Error::other(e);
+        let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+        encoder.set_repeat(Repeat::Infinite).map_err(to_io)?;
+        let delay = Delay::from_numer_denom_ms(1000, RECORD_FPS);
+        for i in 0..self.frame_count {
+            let image = image::open(frame_path(&self.frames_dir, i)).map_err(to_io)?;
+            let frame = Frame::from_parts(image.to_rgba8(), 0, 0, delay);
+            encoder.encode_frame(frame).map_err(to_io)?;
+        }
+        Ok(())
+    }
+}
+
+fn frame_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("frame_{index:05}.png"))
+}
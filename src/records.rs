@@ -0,0 +1,81 @@
+//! Personal bests of each image and grid size, kept in the profile so the
+//! victory screen can tell whether a solve beat them. The best time and
+//! the fewest moves are kept apart, they need not come from the same solve.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::Solve;
+use crate::profile;
+
+/// File in the profile with the personal bests.
+static RECORDS_FILE: &str = "records.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Best {
+    pub seconds: f64, // Fastest solve
+    pub moves: usize, // Fewest moves of a solve
+}
+
+/// How a solve compares with the personal best of its image and size.
+#[derive(Clone, Copy, Debug)]
+pub struct Outcome {
+    pub previous: Option<Best>, // Best before the solve, `None` for the first solve
+    pub faster: bool,           // Whether the solve set the best time
+    pub fewer_moves: bool,      // Whether the solve set the fewest moves
+}
+
+/// Bests keyed by image file name and grid size, eg, `cat.png 4x4`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Records(BTreeMap<String, Best>);
+
+impl Records {
+    /// Bests saved in the profile, none if there is no file yet.
+    pub fn load() -> Self {
+        fs::read_to_string(profile::profile_dir().join(RECORDS_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = profile::profile_path(RECORDS_FILE);
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|text| fs::write(&path, text).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            println!("Error saving {}: {e}", path.display());
+        }
+    }
+
+    /// Compare `solve` with the bests of its image and size, and keep what
+    /// it beat. Solves helped by hints are compared but never kept.
+    pub fn update(&mut self, solve: &Solve) -> Outcome {
+        let size = solve.grid_size();
+        let key = format!("{} {}x{}", solve.image, size.width, size.height);
+        let previous = self.0.get(&key).copied();
+        let mut outcome = Outcome {
+            previous,
+            faster: previous.is_none_or(|best| solve.seconds < best.seconds),
+            fewer_moves: previous.is_none_or(|best| solve.moves < best.moves),
+        };
+        if solve.hint_cost > 0 {
+            outcome.faster = false;
+            outcome.fewer_moves = false;
+            return outcome;
+        }
+        let best = self.0.entry(key).or_insert(Best {
+            seconds: solve.seconds,
+            moves: solve.moves,
+        });
+        if outcome.faster {
+            best.seconds = solve.seconds;
+        }
+        if outcome.fewer_moves {
+            best.moves = solve.moves;
+        }
+        outcome
+    }
+}
@@ -0,0 +1,521 @@
+//! Drawing of the boards and of the particle effects over them: confetti
+//! when a board is solved, dust while it is scrambled and sparkles on hints.
+
+use std::f32::consts::FRAC_PI_2;
+use std::time::{Duration, Instant};
+
+use nannou::image::{self, GenericImageView};
+use nannou::prelude::*;
+use nannou::rand::rand::{thread_rng, Rng};
+
+use sliding_puzzle::GridSize;
+
+use crate::clock;
+use crate::numbering::Numbering;
+use crate::puzzle::{Puzzle, Stage};
+use crate::records::Outcome;
+use crate::{board_rect, board_size, PAD_HEIGHT_FACTOR};
+
+/// Particles burst out of a solved board.
+static CONFETTI_COUNT: usize = 150;
+
+/// Particles sparkling around a hinted piece.
+static SPARKLE_COUNT: usize = 30;
+
+/// Particles raised by each move of a scramble.
+static DUST_COUNT: usize = 2;
+
+/// How long a piece which can't move flashes red.
+pub static REJECT_FLASH: Duration = Duration::from_millis(300);
+
+/// Downward acceleration of the particles, in board sizes per second squared.
+static GRAVITY: f32 = 1.5;
+
+/// Share of the particles spawned with `reduced_motion` set.
+static REDUCED_MOTION_SHARE: f32 = 0.2;
+
+/// Draw `puzzle` centred in `rect`, with the labels of the pieces in the
+/// style `numbers` if given. With `outline`, the pieces in their place are
+/// outlined in green and the others in red.
+pub fn draw_puzzle(
+    draw: &Draw,
+    puzzle: &Puzzle,
+    rect: Rect,
+    numbers: Option<Numbering>,
+    outline: bool,
+) {
+    let board = board_rect(rect, puzzle.grid_size);
+    let cell_size = board.w() / puzzle.grid_size.width as f32;
+
+    let left = board.left();
+    let bottom = board.bottom();
+
+    // each piece is a texture of its own, drawn in its cell, in the order
+    // the jigsaw pieces overlap
+    for row in 0..puzzle.grid_size.height {
+        for col in 0..puzzle.grid_size.width {
+            let centre = pt2(
+                left + (col as f32 + 0.5) * cell_size,
+                bottom + (row as f32 + 0.5) * cell_size,
+            );
+            draw_piece(draw, puzzle, puzzle.board[row][col], centre, board);
+        }
+    }
+
+    // draw the lines between the cells, jigsaw pieces have their own outline
+    if !puzzle.flag_jigsaw {
+        let weight = (cell_size * 0.05).min(2.0);
+        for col in 0..=puzzle.grid_size.width {
+            let x = left + col as f32 * cell_size;
+            draw.line()
+                .start(pt2(x, bottom))
+                .end(pt2(x, board.top()))
+                .color(GREY)
+                .weight(weight);
+        }
+        for row in 0..=puzzle.grid_size.height {
+            let y = bottom + row as f32 * cell_size;
+            draw.line()
+                .start(pt2(left, y))
+                .end(pt2(board.right(), y))
+                .color(GREY)
+                .weight(weight);
+        }
+    }
+
+    let numbers = numbers.map(|numbering| numbering.compact(puzzle.grid_size.longest()));
+    let inset = (cell_size * 0.1).min(6.0);
+    for row in 0..puzzle.grid_size.height {
+        let y = bottom + row as f32 * cell_size + cell_size / 2.0;
+
+        for col in 0..puzzle.grid_size.width {
+            let x = left + col as f32 * cell_size + cell_size / 2.0;
+
+            let piece = puzzle.board[row][col];
+
+            if outline && piece != 0 {
+                let color = match puzzle.is_piece_correct(col, row) {
+                    true => GREEN,
+                    false => RED,
+                };
+                draw.rect()
+                    .x_y(x, y)
+                    .w_h(cell_size - inset, cell_size - inset)
+                    .no_fill()
+                    .stroke(color)
+                    .stroke_weight(inset / 2.0);
+            }
+
+            // draw the label of the piece
+            if let Some(numbering) = numbers {
+                numbering.draw(draw, piece, x, y, cell_size);
+            }
+        }
+    }
+
+    if puzzle.hint_level > 0 {
+        draw_hint(draw, puzzle, pt2(left, bottom), cell_size);
+    }
+}
+
+/// Draw the hint shown for `puzzle`, whose bottom left corner is at
+/// `corner`: the cells around the piece to move, the piece, and the move.
+fn draw_hint(draw: &Draw, puzzle: &Puzzle, corner: Point2, cell_size: f32) {
+    let Some(piece) = puzzle.hint_piece() else {
+        return;
+    };
+    let centre =
+        |(ix, iy): (usize, usize)| corner + vec2(ix as f32 + 0.5, iy as f32 + 0.5) * cell_size;
+    let (ix, iy) = puzzle.index_of(piece);
+    let color = rgba(1.0, 0.9, 0.2, 1.0);
+    if puzzle.hint_level == 1 {
+        // the piece and the empty space are next to each other, a block of
+        // 3 by 3 cells around them doesn't give the piece away
+        let GridSize { width, height } = puzzle.grid_size;
+        let (empty_x, empty_y) = puzzle.empty_next_to(ix, iy).unwrap_or(puzzle.index_empty());
+        let x0 = ix
+            .min(empty_x)
+            .saturating_sub(1)
+            .min(width.saturating_sub(3));
+        let y0 = iy
+            .min(empty_y)
+            .saturating_sub(1)
+            .min(height.saturating_sub(3));
+        let cells = vec2(3.min(width) as f32, 3.min(height) as f32);
+        let region = corner + (vec2(x0 as f32, y0 as f32) + cells / 2.0) * cell_size;
+        draw.rect()
+            .xy(region)
+            .wh(cells * cell_size)
+            .color(rgba(1.0, 0.9, 0.2, 0.2))
+            .stroke(color)
+            .stroke_weight(3.0);
+        return;
+    }
+    draw.rect()
+        .xy(centre((ix, iy)))
+        .w_h(cell_size - 6.0, cell_size - 6.0)
+        .no_fill()
+        .stroke(color)
+        .stroke_weight(4.0);
+    if puzzle.hint_level >= 3 {
+        draw.arrow()
+            .start(centre((ix, iy)))
+            .end(centre(
+                puzzle.empty_next_to(ix, iy).unwrap_or(puzzle.index_empty()),
+            ))
+            .weight(cell_size / 12.0)
+            .color(color);
+    }
+}
+
+/// Flash the piece at `cell` of `puzzle`, centred in `rect`, in red as it
+/// can't move, shaking it a little. `elapsed` is the time since the move
+/// was rejected, the flash fades out over `REJECT_FLASH`.
+pub fn draw_rejected(
+    draw: &Draw,
+    puzzle: &Puzzle,
+    cell: (usize, usize),
+    elapsed: Duration,
+    rect: Rect,
+) {
+    let board = board_rect(rect, puzzle.grid_size);
+    let cell_size = board.w() / puzzle.grid_size.width as f32;
+    let progress = (elapsed.as_secs_f32() / REJECT_FLASH.as_secs_f32()).min(1.0);
+    let shake = (progress * 6.0 * PI).sin() * (1.0 - progress) * cell_size * 0.05;
+    let (ix, iy) = cell;
+    draw.rect()
+        .x_y(
+            board.left() + (ix as f32 + 0.5) * cell_size + shake,
+            board.bottom() + (iy as f32 + 0.5) * cell_size,
+        )
+        .w_h(cell_size, cell_size)
+        .color(rgba(1.0, 0.0, 0.0, 0.5 * (1.0 - progress)));
+}
+
+/// Cover `board` while its solve is paused.
+pub fn draw_paused(draw: &Draw, board: Rect) {
+    let size = board.w().max(board.h());
+    draw.rect()
+        .xy(board.xy())
+        .wh(board.wh())
+        .color(rgba(0.0, 0.0, 0.0, 0.85));
+    draw.text("Paused")
+        .font_size((size / 10.0).max(8.0) as u32)
+        .xy(board.xy())
+        .w(board.w())
+        .center_justify()
+        .color(WHITE);
+}
+
+/// Dots turning around `xy` while something loads, `seconds` being the
+/// time of the app.
+#[cfg(feature = "url")]
+pub fn draw_spinner(draw: &Draw, xy: Point2, radius: f32, seconds: f32) {
+    let dots = 8;
+    let head = (seconds * dots as f32) as usize % dots;
+    for i in 0..dots {
+        let angle = -(i as f32) / dots as f32 * TAU;
+        let fade = ((i + dots - head) % dots) as f32 / dots as f32;
+        draw.ellipse()
+            .xy(xy + vec2(angle.sin(), angle.cos()) * radius)
+            .radius(radius / 5.0)
+            .color(rgba(1.0, 1.0, 1.0, 1.0 - fade * 0.8));
+    }
+}
+
+/// Board shown whole after it was solved, until a key starts a new round.
+pub struct Victory {
+    pub board: usize,                   // Index of the solved board
+    pub moves: usize,                   // Moves taken to solve it
+    pub seconds: f64,                   // Time taken to solve it
+    pub outcome: Outcome,               // How it compares with the personal best
+    pub texture: Option<wgpu::Texture>, // Solved image, made on the next update
+}
+
+/// Solved image shown over a board while the peek key is held.
+pub struct Peek {
+    pub board: usize,           // Index of the board peeked at
+    pub key: Option<Key>,       // Key to release to stop, any key if peeking from the palette
+    pub texture: wgpu::Texture, // Solved image of the board
+}
+
+/// Piece next to the empty space being dragged with the mouse.
+pub struct Drag {
+    pub board: usize,         // Index of the board
+    pub cell: (usize, usize), // Indices of the piece dragged
+    pub from: Point2,         // Mouse position when the button went down, in board points
+    pub offset: f32,          // How far the piece is on its way to the empty space, from 0 to 1
+    pub moved: bool,          // Whether the mouse moved enough for a drag rather than a click
+}
+
+/// Draw the piece of `drag` on its way to the empty space of `puzzle`,
+/// centred in `rect`, over the cell it leaves empty.
+pub fn draw_drag(draw: &Draw, puzzle: &Puzzle, drag: &Drag, rect: Rect) {
+    let board = board_rect(rect, puzzle.grid_size);
+    let cell_size = board.w() / puzzle.grid_size.width as f32;
+    let centre = |(ix, iy): (usize, usize)| {
+        pt2(
+            board.left() + (ix as f32 + 0.5) * cell_size,
+            board.bottom() + (iy as f32 + 0.5) * cell_size,
+        )
+    };
+    let from = centre(drag.cell);
+    let (ix, iy) = drag.cell;
+    let to = centre(puzzle.empty_next_to(ix, iy).unwrap_or(puzzle.index_empty()));
+    draw.rect().xy(from).w_h(cell_size, cell_size).color(BLACK);
+    let piece = puzzle.board[iy][ix];
+    draw_piece(draw, puzzle, piece, from.lerp(to, drag.offset), board);
+}
+
+/// Textures of the `pieces` of a puzzle, by piece number, `None` for the
+/// empty space.
+pub fn piece_textures(app: &App, pieces: &[image::RgbaImage]) -> Vec<Option<wgpu::Texture>> {
+    pieces
+        .iter()
+        .map(|piece| {
+            let image = image::DynamicImage::ImageRgba8(piece.clone());
+            (piece.width() > 0).then(|| wgpu::Texture::from_image(app, &image))
+        })
+        .collect()
+}
+
+/// Draw `piece` of `puzzle` centred on `centre`, turned as it is on the
+/// board, `board` being where the board is drawn. The textures have
+/// physical pixels, the piece is drawn to the size of the board in points.
+fn draw_piece(draw: &Draw, puzzle: &Puzzle, piece: usize, centre: Point2, board: Rect) {
+    let Some(Some(texture)) = puzzle.textures.get(piece) else {
+        return;
+    };
+    let scale = board.w() / puzzle.image_solved.width() as f32;
+    let [width, height] = texture.size();
+    // the turns are clockwise
+    let angle = -(puzzle.turns[piece] as f32) * FRAC_PI_2;
+    draw.texture(texture)
+        .xy(centre)
+        .w_h(width as f32 * scale, height as f32 * scale)
+        .rotate(angle);
+}
+
+/// Draw the victory screen over `board`: the whole image, the empty space
+/// filled in, under a banner with the moves taken.
+pub fn draw_victory(draw: &Draw, victory: &Victory, board: Rect) {
+    let size = board.w().max(board.h());
+    if let Some(texture) = &victory.texture {
+        draw.texture(texture).xy(board.xy()).wh(board.wh());
+    }
+    draw.rect()
+        .xy(board.xy())
+        .w_h(board.w(), size * 0.4)
+        .color(rgba(0.0, 0.0, 0.0, 0.7));
+    draw.text("Solved!")
+        .font_size((size / 8.0).max(8.0) as u32)
+        .x_y(board.x(), board.y() + size * 0.07)
+        .w(board.w())
+        .center_justify()
+        .color(WHITE);
+    let outcome = &victory.outcome;
+    let best = match outcome.previous {
+        Some(best) => format!(
+            "PB: {} / {} moves",
+            clock::format_clock(best.seconds as u64),
+            best.moves
+        ),
+        None => String::from("First solve of this image and size"),
+    };
+    let beaten = match (outcome.faster, outcome.fewer_moves) {
+        _ if outcome.previous.is_none() => "",
+        (true, true) => "New best time and fewest moves!\n",
+        (true, false) => "New best time!\n",
+        (false, true) => "New fewest moves!\n",
+        (false, false) => "",
+    };
+    let details = format!(
+        "{} moves, {}\n{best}\n{beaten}Press a key for a new round",
+        victory.moves,
+        clock::format_clock(victory.seconds as u64)
+    );
+    draw.text(&details)
+        .font_size((size / 24.0).max(6.0) as u32)
+        .x_y(board.x(), board.y() - size * 0.1)
+        .w(board.w())
+        .center_justify()
+        .color(WHITE);
+}
+
+/// Moves and time of the solve of `puzzle` in progress, right below the
+/// board centred in `rect`. The clock only ticks once a second.
+pub fn draw_hud(draw: &Draw, puzzle: &Puzzle, rect: Rect) {
+    if puzzle.stage != Stage::Solving {
+        return;
+    }
+    let pad = rect.h() * PAD_HEIGHT_FACTOR;
+    let text = format!(
+        "{} moves   {}",
+        puzzle.move_count,
+        clock::format_clock(puzzle.elapsed().as_secs())
+    );
+    draw.text(&text)
+        .font_size((pad / 3.0).max(6.0) as u32)
+        .x_y(
+            rect.x(),
+            board_rect(rect, puzzle.grid_size).bottom() - pad / 4.0,
+        )
+        .w_h(rect.w(), pad / 2.0)
+        .center_justify()
+        .align_text_middle_y()
+        .color(WHITE);
+}
+
+/// Details of the image of `puzzle` in the bottom padding of `rect`.
+pub fn draw_status_bar(draw: &Draw, puzzle: &Puzzle, rect: Rect) {
+    let pad = rect.h() * PAD_HEIGHT_FACTOR;
+    draw.text(&puzzle.image_status())
+        .font_size((pad / 4.0).max(6.0) as u32)
+        .x_y(rect.x(), rect.bottom() + pad / 4.0)
+        .w_h(rect.w(), pad / 2.0)
+        .center_justify()
+        .align_text_middle_y()
+        .color(GREY);
+}
+
+struct Particle {
+    board: usize,   // Index of the board the particle flies over
+    position: Vec2, // From the centre of the board, in board sizes
+    velocity: Vec2, // In board sizes per second
+    gravity: f32,   // Downward acceleration, in board sizes per second squared
+    size: f32,      // Side, in board sizes
+    color: Rgba,    // Color at birth, fading out over the lifetime
+    age: f32,       // Seconds since birth
+    lifetime: f32,  // Seconds to live
+}
+
+/// Particles of all the boards. They are kept in board sizes relative to
+/// their board, so they follow it when the window is resized.
+pub struct Particles {
+    particles: Vec<Particle>,
+    last_update: Option<Instant>, // Time of the last physics step
+    share: f32,                   // Share of the particles actually spawned
+}
+
+impl Particles {
+    pub fn new(reduced_motion: bool) -> Self {
+        Particles {
+            particles: vec![],
+            last_update: None,
+            share: match reduced_motion {
+                true => REDUCED_MOTION_SHARE,
+                false => 1.0,
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Number of particles to spawn for a nominal `count`.
+    fn count(&self, count: usize) -> usize {
+        (count as f32 * self.share).round() as usize
+    }
+
+    /// Burst of colored confetti from the middle of board `board`.
+    pub fn confetti(&mut self, board: usize) {
+        let mut rng = thread_rng();
+        for _ in 0..self.count(CONFETTI_COUNT) {
+            let angle = rng.gen_range(0.2..0.8) * PI;
+            let speed = rng.gen_range(0.6..1.4);
+            self.particles.push(Particle {
+                board,
+                position: vec2(rng.gen_range(-0.1..0.1), -0.2),
+                velocity: vec2(angle.cos(), angle.sin()) * speed,
+                gravity: GRAVITY,
+                size: rng.gen_range(0.01..0.025),
+                color: hsla(rng.gen(), 0.9, 0.6, 1.0).into(),
+                age: 0.0,
+                lifetime: rng.gen_range(1.5..2.5),
+            });
+        }
+    }
+
+    /// Sparkles around `position` of board `board`, in board sizes from
+    /// its centre.
+    pub fn sparkle(&mut self, board: usize, position: Vec2) {
+        let mut rng = thread_rng();
+        for _ in 0..self.count(SPARKLE_COUNT) {
+            let angle = rng.gen_range(0.0..TAU);
+            let speed = rng.gen_range(0.05..0.25);
+            self.particles.push(Particle {
+                board,
+                position,
+                velocity: vec2(angle.cos(), angle.sin()) * speed,
+                gravity: 0.0,
+                size: rng.gen_range(0.005..0.012),
+                color: rgba(1.0, 0.95, 0.5, 1.0),
+                age: 0.0,
+                lifetime: rng.gen_range(0.5..1.0),
+            });
+        }
+    }
+
+    /// A little dust rising from anywhere on board `board`.
+    pub fn dust(&mut self, board: usize) {
+        let mut rng = thread_rng();
+        for _ in 0..self.count(DUST_COUNT) {
+            self.particles.push(Particle {
+                board,
+                position: vec2(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5)),
+                velocity: vec2(rng.gen_range(-0.05..0.05), rng.gen_range(0.02..0.1)),
+                gravity: 0.0,
+                size: rng.gen_range(0.005..0.015),
+                color: rgba(0.8, 0.75, 0.65, 0.6),
+                age: 0.0,
+                lifetime: rng.gen_range(0.4..0.8),
+            });
+        }
+    }
+
+    /// Move the particles on by the time since the last step, and drop
+    /// the ones past their lifetime.
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        let dt = match self.last_update {
+            // a long gap means nothing was moving, don't jump ahead
+            Some(last) => (now - last).as_secs_f32().min(0.1),
+            None => 0.0,
+        };
+        self.last_update = Some(now);
+        for particle in &mut self.particles {
+            particle.velocity.y -= particle.gravity * dt;
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+        if self.particles.is_empty() {
+            self.last_update = None;
+        }
+    }
+
+    /// Drop the particles of the boards from `count` on, when there are
+    /// fewer boards.
+    pub fn truncate(&mut self, count: usize) {
+        self.particles.retain(|p| p.board < count);
+    }
+
+    /// Draw the particles over the boards laid out in `rects`.
+    pub fn draw(&self, draw: &Draw, rects: &[Rect]) {
+        for particle in &self.particles {
+            let Some(rect) = rects.get(particle.board) else {
+                continue;
+            };
+            let scale = board_size(*rect);
+            let mut color = particle.color;
+            color.alpha *= 1.0 - particle.age / particle.lifetime;
+            let size = particle.size * scale;
+            draw.rect()
+                .xy(rect.xy() + particle.position * scale)
+                .w_h(size, size)
+                .rotate(particle.velocity.angle())
+                .color(color);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Replays of the solves, recorded move by move with the time of each and
+//! saved as JSON in the `replays` folder of the profile. `Ctrl+R` plays
+//! the last one back and `--replay` plays one from a file, eg, one shared
+//! by someone else.
+//!
+//! Playback goes at the pace of the solve. `Space` pauses it, `Right`
+//! plays the next move, `Up` and `Down` change the speed and `Esc` stops.
+//!
+//! `Ctrl+G` turns a replay into an animated GIF next to the replays, to
+//! share the solve where a replay can't be played.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Error};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use log::debug;
+use nannou::image::codecs::gif::{GifEncoder, Repeat};
+use nannou::image::{self, Delay, Frame, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{clock, profile};
+
+/// Folder of the profile the replays are saved in.
+static REPLAYS_DIR: &str = "replays";
+
+/// Playback speeds, as a factor of the pace of the solve.
+static SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Index of the pace of the solve in `SPEEDS`.
+static NORMAL_SPEED: usize = 2;
+
+/// Size of the GIFs of the replays on their longest side, in pixels.
+pub static GIF_SIZE: u32 = 320;
+
+/// Shortest and longest time a move of a GIF stays on screen, the pace of
+/// the solve is kept in between. In milliseconds.
+static GIF_FRAME_MS: (u32, u32) = (80, 1000);
+
+/// Time the solved board stays on screen at the end of a GIF, in
+/// milliseconds, before the GIF starts over.
+static GIF_LAST_FRAME_MS: u32 = 2000;
+
+/// A change of the board during a solve.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Step {
+    Slide(usize), // Piece slid into the empty space
+    SlideTo {
+        piece: usize,
+        to: (usize, usize),
+    }, // Piece slid into the empty space at `to`, when the board has several
+    ShiftRow {
+        iy: usize,
+        by: isize,
+    }, // Row shifted right, left if negative, in the Loopover variant
+    ShiftColumn {
+        ix: usize,
+        by: isize,
+    }, // Column shifted up, down if negative, in the Loopover variant
+    Turn {
+        piece: usize,
+        turns: usize,
+    }, // Piece turned, to this many quarter turns
+    Jump {
+        board: Vec<Vec<usize>>,
+        turns: Vec<usize>,
+    }, // Back to a bookmarked position
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub width: usize,
+    pub height: usize,
+    pub image: Option<PathBuf>, // Image of the solve, `None` for the blank image
+    pub board: Vec<Vec<usize>>, // Scrambled board, by row from the bottom row, see `Board`
+    pub turns: Vec<usize>,      // Quarter turns of each piece when scrambled
+    pub steps: Vec<(f64, Step)>, // Changes of the board, with the seconds since the first move
+}
+
+impl Replay {
+    /// Save the replay in the profile, named after the time. Returns the path.
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let dir = profile::profile_path(REPLAYS_DIR);
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(format!("replay_{}.json", clock::timestamp_millis()));
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(&path, text).map_err(|e| e.to_string())?;
+        debug!("Saved replay to {}", path.display());
+        Ok(path)
+    }
+
+    /// Path of a new GIF of a replay, in the replays folder.
+    pub fn gif_path() -> PathBuf {
+        profile::profile_path(REPLAYS_DIR).join(format!("replay_{}.gif", clock::timestamp_millis()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    /// The last replay saved in the profile.
+    pub fn latest() -> Result<Self, String> {
+        let entries = fs::read_dir(profile::profile_dir().join(REPLAYS_DIR))
+            .map_err(|_| String::from("No replays yet"))?;
+        // the names sort by time
+        let path = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .max()
+            .ok_or_else(|| String::from("No replays yet"))?;
+        Self::load(&path)
+    }
+}
+
+/// A replay being played back on a board.
+pub struct Playback {
+    pub board: usize, // Index of the board it is played on
+    replay: Replay,   // Solve played back
+    next: usize,      // Index of the next step to play
+    position: f64,    // Seconds of the solve played so far
+    clock: Instant,   // Time `position` was last moved on
+    speed: usize,     // Index of the speed in `SPEEDS`
+    pub paused: bool, // Whether the clock waits, steps are still played with `Right`
+}
+
+impl Playback {
+    /// Playback of `replay` on board `board`, from the first move.
+    pub fn new(board: usize, replay: Replay) -> Self {
+        Playback {
+            board,
+            replay,
+            next: 0,
+            position: 0.0,
+            clock: Instant::now(),
+            speed: NORMAL_SPEED,
+            paused: false,
+        }
+    }
+
+    /// Steps due by now, at the speed of the playback.
+    pub fn due(&mut self) -> Vec<Step> {
+        if !self.paused {
+            self.position += self.clock.elapsed().as_secs_f64() * SPEEDS[self.speed];
+        }
+        self.clock = Instant::now();
+        let mut steps = vec![];
+        while let Some((seconds, step)) = self.replay.steps.get(self.next) {
+            if *seconds > self.position {
+                break;
+            }
+            steps.push(step.clone());
+            self.next += 1;
+        }
+        steps
+    }
+
+    /// The next step whenever it is due, the playback goes on from there.
+    pub fn step(&mut self) -> Option<Step> {
+        let (seconds, step) = self.replay.steps.get(self.next)?.clone();
+        self.position = self.position.max(seconds);
+        self.next += 1;
+        Some(step)
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.clock = Instant::now();
+    }
+
+    /// Play faster, or slower if `delta` is negative, within `SPEEDS`.
+    pub fn change_speed(&mut self, delta: isize) {
+        let speed = self.speed as isize + delta;
+        self.speed = speed.clamp(0, SPEEDS.len() as isize - 1) as usize;
+    }
+
+    /// Replay played back.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Returns `true` once every step was played.
+    pub fn is_over(&self) -> bool {
+        self.next >= self.replay.steps.len()
+    }
+
+    /// Progress and speed of the playback, with the keys.
+    pub fn status(&self) -> String {
+        let state = match (self.paused, self.is_over()) {
+            (_, true) => "over",
+            (true, false) => "paused",
+            (false, false) => "playing",
+        };
+        format!(
+            "Replay {}/{} x{} {state} - Space pause, Right step, Up/Down speed, Esc stop",
+            self.next,
+            self.replay.steps.len(),
+            SPEEDS[self.speed]
+        )
+    }
+}
+
+/// Encode `frames`, each with the seconds of the solve it shows, to an
+/// animated GIF at `path`. Each frame stays until the next one, as in the
+/// solve, within `GIF_FRAME_MS`.
+pub fn encode_gif(frames: Vec<(f64, RgbaImage)>, path: &Path) -> io::Result<()> {
+    let to_io = |e: image::ImageError| Error::other(e);
+    fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    encoder.set_repeat(Repeat::Infinite).map_err(to_io)?;
+    let times: Vec<f64> = frames.iter().map(|(seconds, _)| *seconds).collect();
+    for (i, (seconds, image)) in frames.into_iter().enumerate() {
+        let ms = match times.get(i + 1) {
+            Some(next) => {
+                (((next - seconds) * 1000.0) as u32).clamp(GIF_FRAME_MS.0, GIF_FRAME_MS.1)
+            }
+            None => GIF_LAST_FRAME_MS,
+        };
+        let frame = Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(ms, 1));
+        encoder.encode_frame(frame).map_err(to_io)?;
+    }
+    Ok(())
+}
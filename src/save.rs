@@ -0,0 +1,46 @@
+//! Game saved with `Ctrl+S` and loaded back with `Ctrl+L`, so a long solve
+//! on a large board can be finished another day. One game is kept, as JSON
+//! in the profile.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::profile;
+
+/// File in the profile with the saved game.
+static SAVE_FILE: &str = "save.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub board: Vec<Vec<usize>>, // Pieces by row, from the bottom row, see `Board`
+    pub turns: Vec<usize>,      // Quarter turns of each piece, see `Puzzle::turns`
+    pub path: Vec<usize>,       // Pieces moved since solved, see `Puzzle::path`
+    pub image: Option<PathBuf>, // Image shown, `None` for the blank image
+    pub playing: bool,          // Whether the board was being solved
+    pub moves: usize,           // Moves made in the solve
+    pub seconds: f64,           // Time spent on the solve
+    #[serde(default)]
+    pub hint_cost: usize, // Cost of the hints taken in the solve
+    #[serde(default)]
+    pub seed: Option<u64>, // Scramble seed, for seeded scrambles and challenges
+    #[serde(default)]
+    pub rotation: bool, // Whether the pieces are also turned
+}
+
+/// Save `game` in the profile, over the previous one. Returns the path.
+pub fn save(game: &SavedGame) -> io::Result<PathBuf> {
+    let path = profile::profile_path(SAVE_FILE);
+    fs::write(&path, serde_json::to_string_pretty(game)?)?;
+    debug!("Saved game to {}", path.display());
+    Ok(path)
+}
+
+/// The game saved in the profile.
+pub fn load() -> io::Result<SavedGame> {
+    let text = fs::read_to_string(profile::profile_dir().join(SAVE_FILE))?;
+    Ok(serde_json::from_str(&text)?)
+}
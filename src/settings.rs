@@ -0,0 +1,143 @@
+//! Settings overlay, opened with `O` over the boards and closed with `O`
+//! or `Esc`, to change the game while playing rather than on the command
+//! line or in the config file.
+//!
+//! Drawn with egui, which gets the raw window events to track the mouse
+//! and the keyboard. Input egui takes is not passed on to the boards.
+
+use nannou::prelude::*;
+use nannou::winit::event::WindowEvent as RawWindowEvent;
+use nannou_egui::{egui, Egui};
+use sliding_puzzle::GridSize;
+
+use crate::{Model, MAX_GRID_SIZE};
+
+/// Range of the animation speed, in moves per frame.
+static SPEEDS: std::ops::RangeInclusive<usize> = 1..=20;
+
+/// Range of the scramble length, in random moves.
+static SCRAMBLE_LENGTHS: std::ops::RangeInclusive<usize> = 10..=5000;
+
+pub struct Settings {
+    egui: Egui,     // Overlay drawn over the boards
+    pub open: bool, // Whether the overlay is shown
+}
+
+impl Settings {
+    /// Settings overlay for `window`, closed.
+    pub fn new(window: &Window) -> Self {
+        Settings {
+            egui: Egui::from_window(window),
+            open: false,
+        }
+    }
+
+    /// Returns `true` if egui takes the mouse, ie, it is over the overlay.
+    pub fn wants_pointer(&self) -> bool {
+        self.open && self.egui.ctx().wants_pointer_input()
+    }
+
+    /// Returns `true` if egui takes the keyboard, eg, a field has focus.
+    pub fn wants_keyboard(&self) -> bool {
+        self.open && self.egui.ctx().wants_keyboard_input()
+    }
+
+    /// Draw the overlay over the boards, if open.
+    pub fn draw(&self, frame: &Frame) {
+        if self.open {
+            self.egui.draw_to_frame(frame).unwrap();
+        }
+    }
+}
+
+/// Pass the raw window events to egui.
+pub fn raw_window_event(_app: &App, model: &mut Model, event: &RawWindowEvent) {
+    model.settings.egui.handle_raw_event(event);
+}
+
+/// Lay the overlay out for this frame and apply the changes made.
+pub fn update(app: &App, model: &mut Model) {
+    if !model.settings.open {
+        return;
+    }
+    let puzzle = model.puzzle();
+    let GridSize {
+        mut width,
+        mut height,
+    } = puzzle.grid_size;
+    let mut image = puzzle.image_index_current;
+    let mut scramble_moves = puzzle.scramble_length();
+    let names: Vec<String> = puzzle
+        .image_list
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let settings = &mut model.settings;
+    settings.egui.set_elapsed_time(app.duration.since_start);
+    let frame = settings.egui.begin_frame();
+    let ctx = frame.context();
+    let config = &mut model.config;
+    let mut open = true;
+    egui::Window::new("Settings")
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(&ctx, |ui| {
+            egui::Grid::new("settings").num_columns(2).show(ui, |ui| {
+                ui.label("Grid width");
+                ui.add(egui::Slider::new(&mut width, 2..=MAX_GRID_SIZE));
+                ui.end_row();
+                ui.label("Grid height");
+                ui.add(egui::Slider::new(&mut height, 2..=MAX_GRID_SIZE));
+                ui.end_row();
+                ui.label("Numbers");
+                ui.checkbox(&mut model.flag_show_numbers, "Show on the pieces");
+                ui.end_row();
+                ui.label("Animation speed");
+                ui.add(
+                    egui::Slider::new(&mut config.animation_speed, SPEEDS.clone())
+                        .suffix(" moves/frame"),
+                );
+                ui.end_row();
+                ui.label("Scramble");
+                ui.checkbox(&mut config.animated_scramble, "Animated");
+                ui.end_row();
+                ui.label("Scramble length");
+                ui.add(
+                    egui::Slider::new(&mut scramble_moves, SCRAMBLE_LENGTHS.clone())
+                        .suffix(" moves"),
+                );
+                ui.end_row();
+                ui.label("Image");
+                let selected = names.get(image).map_or("No image", |name| name.as_str());
+                egui::ComboBox::from_id_source("image")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (i, name) in names.iter().enumerate() {
+                            ui.selectable_value(&mut image, i, name);
+                        }
+                    });
+                ui.end_row();
+            });
+        });
+    frame.end();
+
+    let grid_size = GridSize { width, height };
+    if grid_size != model.puzzle().grid_size {
+        model.puzzle_mut().set_grid_size(grid_size);
+    }
+    if image != model.puzzle().image_index_current {
+        model.select_image(image);
+    }
+    if scramble_moves != model.puzzle().scramble_length() {
+        for puzzle in &mut model.puzzles {
+            puzzle.scramble_moves = Some(scramble_moves);
+        }
+    }
+    model.settings.open = open;
+}
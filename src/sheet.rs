@@ -0,0 +1,233 @@
+//! Printable puzzle sheet, a PDF with the scrambled board on the first page
+//! and the moves solving it on the next ones, for solving on paper.
+//!
+//! Cells are named by column letter and row number, `A1` being the top left
+//! one. A move names the piece to slide into the empty space and its cell.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use sliding_puzzle::GridSize;
+
+use crate::puzzle::Puzzle;
+
+/// A4 page size, in points.
+static PAGE_WIDTH: f32 = 595.0;
+static PAGE_HEIGHT: f32 = 842.0;
+
+/// Margin around the page content, in points.
+static MARGIN: f32 = 60.0;
+
+/// Longest side of the board picture, in pixels.
+static PICTURE_SIZE: u32 = 1500;
+
+/// Layout of the solution pages.
+static MOVES_PER_LINE: usize = 6;
+static LINE_HEIGHT: f32 = 14.0;
+
+/// Write the sheet of `puzzle` to the PDF file at `path`.
+pub fn write_sheet(puzzle: &Puzzle, path: &Path) -> io::Result<()> {
+    let picture = puzzle.picture(PICTURE_SIZE, true);
+    let rgb: Vec<u8> = picture
+        .pixels()
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&rgb)?;
+    let image = encoder.finish()?;
+
+    let mut pages = vec![board_page(puzzle.grid_size)];
+    pages.extend(solution_pages(puzzle));
+
+    // Objects 1 to 5 are shared, then each page and its content.
+    let mut pdf = Pdf::default();
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 6 + 2 * i))
+        .collect();
+    pdf.add("<< /Type /Catalog /Pages 2 0 R >>", None);
+    pdf.add(
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+        None,
+    );
+    pdf.add(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+        None,
+    );
+    pdf.add("<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>", None);
+    pdf.add(
+        &format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} \
+             /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode >>",
+            picture.width(),
+            picture.height()
+        ),
+        Some(&image),
+    );
+    for (i, content) in pages.iter().enumerate() {
+        pdf.add(
+            &format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << /Im1 5 0 R >> >> \
+                 /Contents {} 0 R >>",
+                7 + 2 * i
+            ),
+            None,
+        );
+        pdf.add("<< >>", Some(content.as_bytes()));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, pdf.finish())
+}
+
+/// Name of the cell at `(ix, iy)`, row 0 of the board being the bottom one.
+fn cell_name(grid_size: GridSize, ix: usize, iy: usize) -> String {
+    format!("{}{}", (b'A' + ix as u8) as char, grid_size.height - iy)
+}
+
+/// Page with the board picture, its grid and the cell names.
+fn board_page(grid_size: GridSize) -> String {
+    let cell = (PAGE_WIDTH - 2.0 * MARGIN) / grid_size.longest() as f32;
+    let (width, height) = (
+        cell * grid_size.width as f32,
+        cell * grid_size.height as f32,
+    );
+    let left = MARGIN;
+    let top = PAGE_HEIGHT - MARGIN - 50.0;
+    let bottom = top - height;
+
+    let mut page = String::new();
+    let title = format!("Sliding Puzzle {grid_size}");
+    page.push_str(&text("F1", 20.0, MARGIN, PAGE_HEIGHT - MARGIN, &title));
+    let _ = writeln!(page, "q {width} 0 0 {height} {left} {bottom} cm /Im1 Do Q");
+    page.push_str("0.5 G 1 w\n");
+    for col in 0..=grid_size.width {
+        let x = left + col as f32 * cell;
+        let _ = writeln!(page, "{x} {bottom} m {x} {top} l S");
+    }
+    for row in 0..=grid_size.height {
+        let y = bottom + row as f32 * cell;
+        let _ = writeln!(page, "{left} {y} m {} {y} l S", left + width);
+    }
+    for col in 0..grid_size.width {
+        let centre = (col as f32 + 0.5) * cell;
+        let letter = ((b'A' + col as u8) as char).to_string();
+        page.push_str(&text("F1", 12.0, left + centre - 4.0, top + 6.0, &letter));
+    }
+    for row in 0..grid_size.height {
+        let centre = (row as f32 + 0.5) * cell;
+        let number = (row + 1).to_string();
+        page.push_str(&text("F1", 12.0, left - 20.0, top - centre - 4.0, &number));
+    }
+    let note = "A move names the piece to slide into the empty space and its cell.";
+    page.push_str(&text("F1", 10.0, MARGIN, bottom - 30.0, note));
+    page
+}
+
+/// Pages listing the moves solving the board.
+fn solution_pages(puzzle: &Puzzle) -> Vec<String> {
+    // Play the solution on a copy of the board to name the cells.
+    let grid_size = puzzle.grid_size;
+    let mut board = puzzle.board.clone();
+    let mut moves = vec![];
+    for (i, piece) in puzzle.solution().into_iter().enumerate() {
+        let (ix, iy) = board.index_of(piece);
+        moves.push(format!(
+            "{:>3}. {piece:>2} {:<4}",
+            i + 1,
+            cell_name(grid_size, ix, iy)
+        ));
+        board.try_move(ix, iy);
+    }
+
+    let lines: Vec<String> = moves
+        .chunks(MOVES_PER_LINE)
+        .map(|chunk| chunk.join(" "))
+        .collect();
+    let lines_per_page = ((PAGE_HEIGHT - 2.0 * MARGIN - 40.0) / LINE_HEIGHT) as usize;
+    let title = match moves.len() {
+        0 => String::from("Solution: already solved"),
+        count => format!("Solution, {count} moves"),
+    };
+    let mut pages = vec![];
+    for (i, chunk) in lines.chunks(lines_per_page).enumerate() {
+        let mut page = String::new();
+        if i == 0 {
+            page.push_str(&text("F1", 20.0, MARGIN, PAGE_HEIGHT - MARGIN, &title));
+        }
+        for (j, line) in chunk.iter().enumerate() {
+            let y = PAGE_HEIGHT - MARGIN - 40.0 - j as f32 * LINE_HEIGHT;
+            page.push_str(&text("F2", 10.0, MARGIN, y, line));
+        }
+        pages.push(page);
+    }
+    if pages.is_empty() {
+        pages.push(text("F1", 20.0, MARGIN, PAGE_HEIGHT - MARGIN, &title));
+    }
+    pages
+}
+
+/// Content stream operators showing `line` at `(x, y)`.
+fn text(font: &str, size: f32, x: f32, y: f32, line: &str) -> String {
+    let line = line
+        .replace('\\', "\\\\")
+        .replace('(', "\\(")
+        .replace(')', "\\)");
+    format!("BT /{font} {size} Tf {x} {y} Td ({line}) Tj ET\n")
+}
+
+/// Bare PDF writer, objects are numbered from 1 in the order they are added.
+#[derive(Default)]
+struct Pdf {
+    buffer: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Pdf {
+    fn add(&mut self, dictionary: &str, stream: Option<&[u8]>) {
+        if self.buffer.is_empty() {
+            self.buffer.extend_from_slice(b"%PDF-1.4\n");
+        }
+        self.offsets.push(self.buffer.len());
+        let id = self.offsets.len();
+        match stream {
+            Some(stream) => {
+                // Add the length to the dictionary of the stream.
+                let dictionary = dictionary.strip_suffix(">>").unwrap_or(dictionary);
+                let _ = write!(
+                    self.buffer,
+                    "{id} 0 obj\n{dictionary} /Length {} >>\nstream\n",
+                    stream.len()
+                );
+                self.buffer.extend_from_slice(stream);
+                self.buffer.extend_from_slice(b"\nendstream\nendobj\n");
+            }
+            None => {
+                let _ = write!(self.buffer, "{id} 0 obj\n{dictionary}\nendobj\n");
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.buffer.len();
+        let count = self.offsets.len() + 1;
+        let _ = write!(self.buffer, "xref\n0 {count}\n0000000000 65535 f \n");
+        for offset in &self.offsets {
+            let _ = writeln!(self.buffer, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            self.buffer,
+            "trailer\n<< /Size {count} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n"
+        );
+        self.buffer
+    }
+}
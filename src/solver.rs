@@ -0,0 +1,268 @@
+//! Shortest solutions with IDA*, iterative deepening A*: depth-first
+//! searches of the moves, cut where the moves made plus the Manhattan
+//! distance of the pieces to their place pass a bound, the bound growing
+//! until a solution fits. The distance never overestimates the moves left,
+//! so the first solution found is one of the shortest.
+//!
+//! Boards up to 3x3 are solved at once, 4x4 ones can take a while, larger
+//! ones far too long, hence the `abort` flag and the limited searches.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{Board, GridSize};
+
+/// Nodes searched between two looks at the `abort` flag and the clock.
+const ABORT_CHECK_NODES: u64 = 10_000;
+
+/// Shortest solution of `board`, as the pieces to slide into the empty
+/// space in order. `progress` is told each new bound, the number of moves
+/// the solution has at least. Returns `None` if the board can't be solved
+/// or `abort` was set meanwhile.
+pub fn solve(board: &Board, abort: &AtomicBool, progress: impl FnMut(usize)) -> Option<Vec<usize>> {
+    Search::new(board, abort, u64::MAX)?.run(progress)
+}
+
+/// First move of a shortest solution of `board`, if one is found within
+/// `max_nodes` nodes of search.
+pub fn best_move(board: &Board, max_nodes: u64) -> Option<usize> {
+    let abort = AtomicBool::new(false);
+    let solution = Search::new(board, &abort, max_nodes)?.run(|_| {})?;
+    solution.first().copied()
+}
+
+/// First move of a shortest solution of `board`, if one is found within
+/// `time`, for a search while the player waits.
+pub fn best_move_within(board: &Board, time: Duration) -> Option<usize> {
+    let abort = AtomicBool::new(false);
+    let mut search = Search::new(board, &abort, u64::MAX)?;
+    search.deadline = Some(Instant::now() + time);
+    let solution = search.run(|_| {})?;
+    solution.first().copied()
+}
+
+/// Piece whose move takes the pieces closest to their place, by Manhattan
+/// distance. A cheap guess for boards too large to search, it doesn't
+/// always lead to the solution.
+pub fn closer_move(board: &Board) -> Option<usize> {
+    let abort = AtomicBool::new(false);
+    let search = Search::new(board, &abort, 0)?;
+    let blank = search.blank;
+    search
+        .neighbours()
+        .into_iter()
+        .flatten()
+        .min_by_key(|&cell| {
+            let piece = search.cells[cell];
+            search.distance(piece, blank) as isize - search.distance(piece, cell) as isize
+        })
+        .map(|cell| search.cells[cell])
+}
+
+/// Outcome of a search under a bound.
+enum Step {
+    Found,        // The board is solved, the moves are in `path`
+    Bound(usize), // Smallest cost past the bound, the next bound to try
+    Aborted,      // The `abort` flag was set, or the search ran out of nodes or time
+}
+
+struct Search<'a> {
+    size: GridSize,            // Size of the board
+    goal: Vec<usize>,          // Cell of each piece on the solved board
+    cells: Vec<usize>,         // Pieces row by row from the bottom, as `Board`
+    blank: usize,              // Cell of the empty space
+    path: Vec<usize>,          // Pieces moved so far
+    abort: &'a AtomicBool,     // Set to stop searching
+    nodes: u64,                // Nodes searched, to look at `abort` now and then
+    max_nodes: u64,            // Nodes searched at most before giving up
+    deadline: Option<Instant>, // Time to give up at, if any
+}
+
+impl<'a> Search<'a> {
+    /// Search of the solution of `board`, `None` if it can't be solved.
+    fn new(board: &Board, abort: &'a AtomicBool, max_nodes: u64) -> Option<Self> {
+        // the search follows a single empty space
+        if board.blanks() > 1 || !board.is_solvable() {
+            return None;
+        }
+        let size = board.size();
+        let cells: Vec<usize> = board.rows().flatten().copied().collect();
+        let mut goal = vec![0; size.cells()];
+        for (i, &piece) in Board::solved(size).rows().flatten().enumerate() {
+            goal[piece] = i;
+        }
+        let blank = cells.iter().position(|&p| p == 0)?;
+        Some(Search {
+            size,
+            goal,
+            cells,
+            blank,
+            path: vec![],
+            abort,
+            nodes: 0,
+            max_nodes,
+            deadline: None,
+        })
+    }
+
+    /// Search with growing bounds until a solution fits, see `solve`.
+    fn run(mut self, mut progress: impl FnMut(usize)) -> Option<Vec<usize>> {
+        let distance = (0..self.size.cells())
+            .map(|i| self.distance(self.cells[i], i))
+            .sum();
+        let mut bound = distance;
+        loop {
+            progress(bound);
+            match self.search(0, distance, bound, None) {
+                Step::Found => return Some(self.path),
+                Step::Bound(next) if next != usize::MAX => bound = next,
+                _ => return None,
+            }
+        }
+    }
+
+    /// Returns `true` if `abort` was set or the deadline has passed.
+    fn is_stopped(&self) -> bool {
+        self.abort.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
+    /// Cells next to the empty space, left, right, below and above.
+    fn neighbours(&self) -> [Option<usize>; 4] {
+        let width = self.size.width;
+        let blank = self.blank;
+        let (x, y) = (blank % width, blank / width);
+        [
+            (x > 0).then(|| blank - 1),
+            (x + 1 < width).then(|| blank + 1),
+            (y > 0).then(|| blank - width),
+            (y + 1 < self.size.height).then(|| blank + width),
+        ]
+    }
+
+    /// Manhattan distance of `piece` at cell `cell` to its place, 0 for
+    /// the empty space.
+    fn distance(&self, piece: usize, cell: usize) -> usize {
+        if piece == 0 {
+            return 0;
+        }
+        let width = self.size.width;
+        let goal = self.goal[piece];
+        (cell % width).abs_diff(goal % width) + (cell / width).abs_diff(goal / width)
+    }
+
+    /// Search on from `moves` moves made with a total `distance` of the
+    /// pieces to their place. `previous` is the cell the empty space came
+    /// from, going back there would undo the last move.
+    fn search(
+        &mut self,
+        moves: usize,
+        distance: usize,
+        bound: usize,
+        previous: Option<usize>,
+    ) -> Step {
+        let cost = moves + distance;
+        if cost > bound {
+            return Step::Bound(cost);
+        }
+        if distance == 0 {
+            return Step::Found;
+        }
+        self.nodes += 1;
+        if self.nodes > self.max_nodes
+            || self.nodes.is_multiple_of(ABORT_CHECK_NODES) && self.is_stopped()
+        {
+            return Step::Aborted;
+        }
+        let blank = self.blank;
+        let mut next_bound = usize::MAX;
+        for cell in self.neighbours().into_iter().flatten() {
+            if Some(cell) == previous {
+                continue;
+            }
+            let piece = self.cells[cell];
+            let distance = distance - self.distance(piece, cell) + self.distance(piece, blank);
+            self.cells[blank] = piece;
+            self.cells[cell] = 0;
+            self.blank = cell;
+            self.path.push(piece);
+            match self.search(moves + 1, distance, bound, Some(blank)) {
+                Step::Found => return Step::Found,
+                Step::Aborted => return Step::Aborted,
+                Step::Bound(cost) => next_bound = next_bound.min(cost),
+            }
+            self.path.pop();
+            self.blank = blank;
+            self.cells[cell] = piece;
+            self.cells[blank] = 0;
+        }
+        Step::Bound(next_bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Board of `rows` written from the top row, as it is drawn.
+    fn board(rows: &[&[usize]]) -> Board {
+        Board {
+            cells: rows.iter().rev().map(|row| row.to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn solves_a_short_scramble_optimally() {
+        // the Manhattan distance is 4, so 4 moves is the shortest
+        let mut scrambled = board(&[&[0, 1, 3], &[4, 2, 5], &[7, 8, 6]]);
+        let abort = AtomicBool::new(false);
+        let solution = solve(&scrambled, &abort, |_| {}).unwrap();
+        assert_eq!(solution, [1, 2, 5, 6]);
+        for piece in solution {
+            let (ix, iy) = scrambled.index_of(piece);
+            assert_eq!(scrambled.try_move(ix, iy), Some(piece));
+        }
+        assert!(scrambled.is_solved());
+        let solved = board(&[&[1, 2], &[3, 0]]);
+        assert_eq!(solve(&solved, &abort, |_| {}), Some(vec![]));
+        let unsolvable = board(&[&[1, 2, 3], &[4, 5, 6], &[8, 7, 0]]);
+        assert_eq!(solve(&unsolvable, &abort, |_| {}), None);
+    }
+
+    #[test]
+    fn best_move_is_the_first_of_a_solution() {
+        let scrambled = board(&[&[0, 1, 3], &[4, 2, 5], &[7, 8, 6]]);
+        assert_eq!(best_move(&scrambled, u64::MAX), Some(1));
+        let one_off = board(&[&[1, 2, 3], &[4, 5, 6], &[7, 0, 8]]);
+        assert_eq!(best_move(&one_off, u64::MAX), Some(8));
+    }
+
+    #[test]
+    fn best_move_none_when_not_searched() {
+        // unsolvable, no empty space and several empty spaces
+        let unsolvable = board(&[&[1, 2, 3], &[4, 5, 6], &[8, 7, 0]]);
+        assert_eq!(best_move(&unsolvable, u64::MAX), None);
+        let loopover = board(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 9]]);
+        assert_eq!(best_move(&loopover, u64::MAX), None);
+        let blanks = board(&[&[1, 2, 3], &[4, 5, 0], &[6, 0, 0]]);
+        assert_eq!(best_move(&blanks, u64::MAX), None);
+    }
+
+    #[test]
+    fn best_move_within_gives_up_in_time() {
+        let scrambled = board(&[&[0, 1, 3], &[4, 2, 5], &[7, 8, 6]]);
+        assert_eq!(
+            best_move_within(&scrambled, Duration::from_secs(60)),
+            Some(1)
+        );
+        let mut hard = Board::solved(GridSize::square(6));
+        hard.scramble_random(&mut StdRng::seed_from_u64(0));
+        let started = Instant::now();
+        assert_eq!(best_move_within(&hard, Duration::from_millis(10)), None);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
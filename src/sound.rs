@@ -0,0 +1,157 @@
+//! Sound effects for the moves, the moves which can't be made, the end of
+//! a scramble and a solve. The sounds are short tones made on the fly, so
+//! there are no files to ship.
+//!
+//! Music is played from the `.ogg` and `.mp3` files of the `music` folder,
+//! shuffled, under the effects.
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use nannou::rand::seq::SliceRandom;
+use nannou::rand::thread_rng;
+use rodio::source::{SineWave, SquareWave};
+use rodio::{Decoder, DeviceSinkBuilder, MixerDeviceSink, Player, Source};
+
+/// Folder of the music, next to the images folder.
+static MUSIC_DIR: &str = "music";
+
+/// Extensions of the music files played.
+static MUSIC_EXTENSIONS: [&str; 2] = ["ogg", "mp3"];
+
+#[derive(Clone, Copy, Debug)]
+pub enum Effect {
+    Slide,     // A piece slid into the empty space
+    Invalid,   // The piece can't move
+    Scrambled, // The scramble is over, the solve can start
+    Solved,    // The puzzle was solved
+}
+
+pub struct Sound {
+    sink: MixerDeviceSink, // Output device the effects are mixed into
+    volume: f32,           // Volume of the effects, from 0 to 1
+    pub muted: bool,       // Whether the effects are silenced
+    music: Player,         // Track playing, the effects play over it
+    tracks: Vec<PathBuf>,  // Music files, shuffled
+    next_track: usize,     // Index of the next track to play in `tracks`
+    music_muted: bool,     // Whether the music is silenced
+}
+
+impl Sound {
+    /// Sound played on the default output device, the effects at `volume`
+    /// and the music at `music_volume`, from 0 to 1.
+    pub fn new(volume: f32, music_volume: f32) -> Result<Self, String> {
+        let mut sink = DeviceSinkBuilder::open_default_sink().map_err(|e| e.to_string())?;
+        sink.log_on_drop(false);
+        let music = Player::connect_new(sink.mixer());
+        music.set_volume(music_volume.clamp(0.0, 1.0));
+        let mut tracks = music_files();
+        tracks.shuffle(&mut thread_rng());
+        Ok(Sound {
+            sink,
+            volume: volume.clamp(0.0, 1.0),
+            muted: false,
+            music,
+            tracks,
+            next_track: 0,
+            music_muted: false,
+        })
+    }
+
+    /// Start the next track once the one playing is over, shuffling the
+    /// tracks again after the last one. Tracks which can't be played are
+    /// dropped with a message.
+    pub fn poll(&mut self) {
+        while self.music.empty() && !self.music_muted && !self.tracks.is_empty() {
+            if self.next_track >= self.tracks.len() {
+                self.tracks.shuffle(&mut thread_rng());
+                self.next_track = 0;
+            }
+            let path = &self.tracks[self.next_track];
+            match File::open(path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| Decoder::try_from(file).map_err(|e| e.to_string()))
+            {
+                Ok(track) => {
+                    self.music.append(track);
+                    self.next_track += 1;
+                }
+                Err(e) => {
+                    println!("Error playing {}: {e}", path.display());
+                    self.tracks.remove(self.next_track);
+                }
+            }
+        }
+    }
+
+    /// Skip to the next track. Returns `false` if there is no music.
+    pub fn next_track(&mut self) -> bool {
+        self.music.clear();
+        self.poll();
+        !self.tracks.is_empty()
+    }
+
+    /// Silence the music, or start it again with the next track. Returns
+    /// `true` if it is muted.
+    pub fn toggle_music(&mut self) -> bool {
+        self.music_muted = !self.music_muted;
+        self.music.clear();
+        self.poll();
+        self.music_muted
+    }
+
+    /// Play `effect` over whatever is playing, unless muted.
+    pub fn play(&self, effect: Effect) {
+        if self.muted || self.volume == 0.0 {
+            return;
+        }
+        let mixer = self.sink.mixer();
+        match effect {
+            Effect::Slide => mixer.add(tone(880.0, 0, 40).amplify(self.volume * 0.5)),
+            Effect::Invalid => mixer.add(
+                SquareWave::new(110.0)
+                    .take_duration(Duration::from_millis(120))
+                    .amplify(self.volume * 0.2),
+            ),
+            Effect::Scrambled => {
+                mixer.add(tone(440.0, 0, 100).amplify(self.volume));
+                mixer.add(tone(660.0, 100, 150).amplify(self.volume));
+            }
+            // a rising arpeggio, C E G C
+            Effect::Solved => {
+                for (i, freq) in [523.3, 659.3, 784.0, 1046.5].into_iter().enumerate() {
+                    let length = if i == 3 { 400 } else { 120 };
+                    mixer.add(tone(freq, i as u64 * 120, length).amplify(self.volume));
+                }
+            }
+        }
+    }
+}
+
+/// Music files in the music folder, in no particular order.
+fn music_files() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(MUSIC_DIR) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| {
+                MUSIC_EXTENSIONS
+                    .iter()
+                    .any(|music| ext.eq_ignore_ascii_case(music))
+            })
+        })
+        .collect()
+}
+
+/// Sine at `freq` hertz fading out over `length` milliseconds, starting
+/// after `start` milliseconds.
+fn tone(freq: f32, start: u64, length: u64) -> impl Source {
+    let length = Duration::from_millis(length);
+    SineWave::new(freq)
+        .fade_out(length)
+        .take_duration(length)
+        .delay(Duration::from_millis(start))
+}
@@ -0,0 +1,169 @@
+//! State of the game, driving what `update`, `event` and `view` do: the
+//! start menu, a board being scrambled, playing, a game paused, a board
+//! just solved, a replay played back, the editor, the crop screen and the
+//! Klotski mode.
+//!
+//! The menu picks the grid size and the image of the focused board, which
+//! shows the choices as they are made. Playing starts a scramble.
+
+use nannou::prelude::*;
+
+use crate::crop::Cropper;
+use crate::klotski_game::KlotskiGame;
+use crate::puzzle::Puzzle;
+use crate::render::Victory;
+use crate::replay::Playback;
+use crate::{board_rect, PAD_HEIGHT_FACTOR};
+
+/// Height of a line of the menu, as a fraction of the window height.
+static ROW_HEIGHT_FACTOR: f32 = 0.1;
+
+pub enum GameState {
+    Menu(Menu),           // Start menu, shown at start and with `Home`
+    Scrambling,           // A board is being scrambled, input waits for it
+    Playing,              // The boards take the moves of the player
+    Paused,               // The timers wait and the boards are hidden, no moves are taken
+    Replaying(Playback),  // A replay is played back, the keys control it
+    Editing(Editor),      // A position is set up by swapping pieces freely
+    Cropping(Cropper),    // The region of the image played is picked before a solve
+    Klotski(KlotskiGame), // The block puzzle is played in place of the boards
+    Solved(Victory),      // A board was just solved, shown whole until a key is pressed
+}
+
+/// Lines of the menu, in order from the top.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Row {
+    Width,
+    Height,
+    Image,
+    Play,
+}
+
+impl Row {
+    pub const ALL: &'static [Row] = &[Row::Width, Row::Height, Row::Image, Row::Play];
+}
+
+#[derive(Default)]
+pub struct Menu {
+    pub selected: usize, // Index of the selected line in `Row::ALL`
+}
+
+impl Menu {
+    /// The selected line.
+    pub fn row(&self) -> Row {
+        Row::ALL[self.selected]
+    }
+
+    /// Move the selection by `delta` lines, wrapping around.
+    pub fn move_selection(&mut self, delta: isize) {
+        let count = Row::ALL.len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(count) as usize;
+    }
+
+    /// Line at `point` and whether it is on the right half of it, which
+    /// increases the value, the left half decreases it.
+    pub fn hit(&self, win: Rect, point: Point2) -> Option<(usize, bool)> {
+        (0..Row::ALL.len())
+            .find(|&i| row_rect(win, i).contains(point))
+            .map(|i| (i, point.x > win.x()))
+    }
+}
+
+/// Editor of the position of a board, opened with `Ctrl+E`. Clicking a
+/// piece then another cell swaps them, whatever the rule of sliding, and
+/// `Enter` starts a solve from the position if it can be solved.
+pub struct Editor {
+    pub board: usize,                     // Index of the board edited
+    pub selected: Option<(usize, usize)>, // Cell clicked first, to swap with the next one
+}
+
+impl Editor {
+    pub fn new(board: usize) -> Self {
+        Editor {
+            board,
+            selected: None,
+        }
+    }
+}
+
+/// Draw the selected cell of `editor` over `puzzle`, centred in `rect`,
+/// and whether the position can be solved below it.
+pub fn draw_editor(draw: &Draw, editor: &Editor, puzzle: &Puzzle, rect: Rect) {
+    let board = board_rect(rect, puzzle.grid_size);
+    let cell_size = board.w() / puzzle.grid_size.width as f32;
+    if let Some((ix, iy)) = editor.selected {
+        draw.rect()
+            .x_y(
+                board.left() + (ix as f32 + 0.5) * cell_size,
+                board.bottom() + (iy as f32 + 0.5) * cell_size,
+            )
+            .w_h(cell_size, cell_size)
+            .no_fill()
+            .stroke(YELLOW)
+            .stroke_weight((cell_size * 0.06).max(2.0));
+    }
+    let (text, color) = match puzzle.board.is_solvable() {
+        true => ("Solvable, Enter to play, Esc to leave", GREEN),
+        false => ("Not solvable, swap two pieces, Esc to leave", RED),
+    };
+    let pad = rect.h() * PAD_HEIGHT_FACTOR;
+    draw.text(text)
+        .font_size((pad / 3.0).max(6.0) as u32)
+        .x_y(rect.x(), board.bottom() - pad / 4.0)
+        .w(rect.w())
+        .center_justify()
+        .color(color);
+}
+
+/// Rect of line `index` of the menu, centred in `win`.
+fn row_rect(win: Rect, index: usize) -> Rect {
+    let h = win.h() * ROW_HEIGHT_FACTOR;
+    let top = win.y() + h * Row::ALL.len() as f32 / 2.0;
+    Rect::from_x_y_w_h(win.x(), top - h * (index as f32 + 0.5), win.w() * 0.6, h)
+}
+
+/// Draw `menu` in the middle of `win`, with the values of `puzzle`.
+pub fn draw_menu(draw: &Draw, menu: &Menu, puzzle: &Puzzle, win: Rect) {
+    let h = win.h() * ROW_HEIGHT_FACTOR;
+    draw.rect()
+        .xy(win.xy())
+        .wh(win.wh())
+        .color(rgba(0.0, 0.0, 0.0, 0.6));
+    draw.text("Sliding Puzzle")
+        .font_size((h / 2.0).max(8.0) as u32)
+        .x_y(win.x(), row_rect(win, 0).top() + h)
+        .w(win.w())
+        .center_justify()
+        .color(WHITE);
+    for (i, row) in Row::ALL.iter().enumerate() {
+        let text = match row {
+            Row::Width => format!("<  Width {}  >", puzzle.grid_size.width),
+            Row::Height => format!("<  Height {}  >", puzzle.grid_size.height),
+            Row::Image => format!("<  {}  >", puzzle.image_name()),
+            Row::Play => String::from("Play"),
+        };
+        let rect = row_rect(win, i);
+        if i == menu.selected {
+            draw.rect()
+                .xy(rect.xy())
+                .wh(rect.wh())
+                .color(rgba(1.0, 1.0, 1.0, 0.2));
+        }
+        draw.text(&text)
+            .font_size((h / 3.0).max(8.0) as u32)
+            .xy(rect.xy())
+            .w(rect.w())
+            .center_justify()
+            .color(WHITE);
+    }
+    let help = "Up and Down to pick, Left and Right to change, Enter to play";
+    draw.text(help)
+        .font_size((h / 5.0).max(6.0) as u32)
+        .x_y(
+            win.x(),
+            row_rect(win, Row::ALL.len() - 1).bottom() - h / 2.0,
+        )
+        .w(win.w())
+        .center_justify()
+        .color(GRAY);
+}
@@ -0,0 +1,124 @@
+//! Stats screen, opened with `F3`, summing up the solve history per grid
+//! size: how many solves, the time spent, the averages and the bests, and
+//! whether the recent solves are faster than the ones before them.
+//!
+//! Every solve is already kept in the history of the profile, with its
+//! time, image, size, moves, duration and seed, see `history`. The screen
+//! is worked out from it when opened.
+
+use nannou::prelude::*;
+use sliding_puzzle::GridSize;
+
+use crate::clock;
+use crate::history::Solve;
+
+/// Solves counted as recent for the trend, they are compared with as many
+/// solves before them.
+static TREND_SOLVES: usize = 10;
+
+/// Height of a line of the screen, in points.
+static LINE_HEIGHT: f32 = 28.0;
+
+/// Stats of the solves of one grid size.
+struct SizeStats {
+    size: GridSize,
+    solves: usize,       // Number of solves
+    total_seconds: f64,  // Time spent solving
+    average_moves: f64,  // Moves per solve
+    best_seconds: f64,   // Fastest solve
+    fewest_moves: usize, // Fewest moves of a solve
+    trend: Option<f64>,  // Change of the average time of the recent solves, from the ones before
+}
+
+impl SizeStats {
+    /// Stats of `solves`, all of grid size `size` and oldest first.
+    fn new(size: GridSize, solves: &[&Solve]) -> Self {
+        let total_seconds = solves.iter().map(|s| s.seconds).sum();
+        let total_moves: usize = solves.iter().map(|s| s.moves).sum();
+        let average =
+            |solves: &[&Solve]| solves.iter().map(|s| s.seconds).sum::<f64>() / solves.len() as f64;
+        let trend = (solves.len() >= 2 * TREND_SOLVES).then(|| {
+            let (before, recent) = solves.split_at(solves.len() - TREND_SOLVES);
+            let before = &before[before.len() - TREND_SOLVES..];
+            average(recent) / average(before) - 1.0
+        });
+        SizeStats {
+            size,
+            solves: solves.len(),
+            total_seconds,
+            average_moves: total_moves as f64 / solves.len() as f64,
+            best_seconds: solves.iter().map(|s| s.seconds).fold(f64::MAX, f64::min),
+            fewest_moves: solves.iter().map(|s| s.moves).min().unwrap_or(0),
+            trend,
+        }
+    }
+}
+
+pub struct Stats {
+    sizes: Vec<SizeStats>, // Stats of each grid size played, the smallest first
+}
+
+impl Stats {
+    /// Stats of `solves`, oldest first as in the history.
+    pub fn new(solves: &[Solve]) -> Self {
+        let mut sizes: Vec<GridSize> = solves.iter().map(Solve::grid_size).collect();
+        sizes.sort_by_key(|size| (size.cells(), size.width));
+        sizes.dedup();
+        let sizes = sizes
+            .into_iter()
+            .map(|size| {
+                let of_size: Vec<&Solve> =
+                    solves.iter().filter(|s| s.grid_size() == size).collect();
+                SizeStats::new(size, &of_size)
+            })
+            .collect();
+        Stats { sizes }
+    }
+
+    /// Draw the stats over the whole of `win`.
+    pub fn draw(&self, draw: &Draw, win: Rect) {
+        draw.rect()
+            .xy(win.xy())
+            .wh(win.wh())
+            .color(rgba(0.0, 0.0, 0.0, 0.85));
+        let mut lines = vec![String::from("Stats")];
+        if self.sizes.is_empty() {
+            lines.push(String::from("No solves yet"));
+        }
+        let solves: usize = self.sizes.iter().map(|s| s.solves).sum();
+        let seconds: f64 = self.sizes.iter().map(|s| s.total_seconds).sum();
+        if solves > 0 {
+            lines.push(format!(
+                "{solves} solves, {} in all",
+                clock::format_duration(seconds)
+            ));
+        }
+        for stats in &self.sizes {
+            let trend = match stats.trend {
+                Some(trend) if trend < 0.0 => format!(", {:.0}% faster lately", -trend * 100.0),
+                Some(trend) => format!(", {:.0}% slower lately", trend * 100.0),
+                None => String::new(),
+            };
+            lines.push(format!(
+                "{}: {} solves, average {} and {:.0} moves, best {} and {} moves{trend}",
+                stats.size,
+                stats.solves,
+                clock::format_duration(stats.total_seconds / stats.solves as f64),
+                stats.average_moves,
+                clock::format_duration(stats.best_seconds),
+                stats.fewest_moves
+            ));
+        }
+        lines.push(String::from("Press a key to close"));
+        let top = win.y() + LINE_HEIGHT * lines.len() as f32 / 2.0;
+        for (i, line) in lines.iter().enumerate() {
+            let size = if i == 0 { 24 } else { 14 };
+            draw.text(line)
+                .font_size(size)
+                .x_y(win.x(), top - LINE_HEIGHT * (i as f32 + 0.5))
+                .w(win.w())
+                .center_justify()
+                .color(if i + 1 == lines.len() { GRAY } else { WHITE });
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! System tray icon. With it, `M` hides the game into the tray instead of
+//! minimizing the window. Clicking the icon resumes the game, its menu
+//! also offers a new game or quitting.
+//!
+//! The tray is made the first time it is needed and then only shown and
+//! hidden, as the event handlers can only be set once.
+
+use std::sync::{mpsc, Arc, Mutex};
+
+use nannou::app::Proxy;
+use nannou::image::RgbaImage;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Command {
+    Resume,
+    NewGame,
+    Quit,
+}
+
+pub struct Tray {
+    icon: TrayIcon,
+    receiver: mpsc::Receiver<Command>,
+}
+
+impl Tray {
+    /// Make the tray icon, hidden. `proxy` wakes the app up when the
+    /// icon or its menu is clicked.
+    pub fn new(proxy: Proxy) -> Result<Self, String> {
+        let resume = MenuItem::new("Resume", true, None);
+        let new_game = MenuItem::new("New game", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+        let menu = Menu::new();
+        menu.append_items(&[&resume, &new_game, &quit])
+            .map_err(|e| e.to_string())?;
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_menu_on_left_click(false)
+            .with_tooltip("Sliding Puzzle (paused)")
+            .build()
+            .map_err(|e| e.to_string())?;
+        icon.set_visible(false).map_err(|e| e.to_string())?;
+
+        let (sender, receiver) = mpsc::channel();
+        let channel = Arc::new(Mutex::new((sender, proxy)));
+        let menu_channel = channel.clone();
+        let items = [
+            (resume.id().clone(), Command::Resume),
+            (new_game.id().clone(), Command::NewGame),
+            (quit.id().clone(), Command::Quit),
+        ];
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some((_, command)) = items.iter().find(|(id, _)| *id == event.id) {
+                send(&menu_channel, *command);
+            }
+        }));
+        TrayIconEvent::set_event_handler(Some(move |event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                send(&channel, Command::Resume);
+            }
+        }));
+        Ok(Tray { icon, receiver })
+    }
+
+    /// Show the tray icon with `image` as its picture.
+    pub fn show(&self, image: &RgbaImage) {
+        let icon = Icon::from_rgba(image.as_raw().clone(), image.width(), image.height());
+        match icon {
+            Ok(icon) => {
+                if let Err(e) = self.icon.set_icon(Some(icon)) {
+                    println!("Error setting the tray icon: {e}");
+                }
+            }
+            Err(e) => println!("Error making the tray icon: {e}"),
+        }
+        if let Err(e) = self.icon.set_visible(true) {
+            println!("Error showing the tray icon: {e}");
+        }
+    }
+
+    pub fn hide(&self) {
+        if let Err(e) = self.icon.set_visible(false) {
+            println!("Error hiding the tray icon: {e}");
+        }
+    }
+
+    /// Next command given from the tray, if any.
+    pub fn poll(&self) -> Option<Command> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Pass `command` on to the app and wake it up to handle it.
+fn send(channel: &Mutex<(mpsc::Sender<Command>, Proxy)>, command: Command) {
+    let (sender, proxy) = &*channel.lock().unwrap();
+    let _ = sender.send(command);
+    let _ = proxy.wakeup();
+}
@@ -0,0 +1,291 @@
+//! Twitch-chat-plays mode.
+//!
+//! Connects anonymously to the chat of a Twitch channel over IRC and turns
+//! the chat commands `!up`, `!down`, `!left` and `!right` into moves.
+//! The direction is the one the tile slides into the empty space.
+
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use nannou::app::Proxy;
+
+/// Twitch chat IRC server, plain text.
+static TWITCH_IRC: &str = "irc.chat.twitch.tv:6667";
+
+/// Minimum time between two moves applied from chat.
+static MOVE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long votes are collected in democracy mode.
+static VOTE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Maximum number of commands waiting in anarchy mode,
+/// older commands are dropped so chat doesn't lag behind.
+static MAX_QUEUED: usize = 5;
+
+/// How often the reader thread wakes up the app,
+/// so vote windows close even when chat is quiet.
+static TICK: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Anarchy,   // Every command applied in order, one per `MOVE_INTERVAL`
+    Democracy, // Votes tallied for `VOTE_WINDOW`, one per user, the most voted applied
+}
+
+pub struct Chat {
+    pub channel: String,
+    pub mode: Mode,
+    receiver: Receiver<(String, Command)>,
+    queue: VecDeque<Command>,
+    last_move: Instant,
+    votes: Vec<(Command, usize)>,
+    voters: HashSet<String>,
+    vote_started: Option<Instant>,
+}
+
+impl Chat {
+    /// Join the chat of `channel` and start reading it in a background thread.
+    /// The `proxy` is used to wake up the app when something happens.
+    pub fn connect(channel: &str, proxy: Proxy) -> io::Result<Self> {
+        let channel = channel.trim_start_matches('#').to_lowercase();
+        let mut stream = TcpStream::connect(TWITCH_IRC)?;
+        // Anonymous, read-only login.
+        write!(
+            stream,
+            "PASS SCHMOOPIIE\r\nNICK justinfan{}\r\nJOIN #{channel}\r\n",
+            std::process::id()
+        )?;
+        stream.set_read_timeout(Some(TICK))?;
+        debug!("Joined Twitch chat #{channel}");
+
+        let (sender, receiver) = mpsc::channel();
+        let mut writer = stream.try_clone()?;
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            loop {
+                match reader.read_line(&mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        if line.starts_with("PING") {
+                            let pong = line.replacen("PING", "PONG", 1);
+                            if writer.write_all(pong.as_bytes()).is_err() {
+                                break;
+                            }
+                        } else if let Some(message) = parse_privmsg(&line) {
+                            if sender.send(message).is_err() {
+                                break;
+                            }
+                        }
+                        line.clear();
+                    }
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                    Err(e) => {
+                        println!("Twitch chat connection lost: {e}");
+                        break;
+                    }
+                }
+                if proxy.wakeup().is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Chat {
+            channel,
+            mode: Mode::Anarchy,
+            receiver,
+            queue: VecDeque::new(),
+            last_move: Instant::now(),
+            votes: vec![],
+            voters: HashSet::new(),
+            vote_started: None,
+        })
+    }
+
+    /// Switch between anarchy and democracy, discarding pending commands.
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            Mode::Anarchy => Mode::Democracy,
+            Mode::Democracy => Mode::Anarchy,
+        };
+        self.queue.clear();
+        self.reset_votes();
+    }
+
+    /// Read the chat and return the command to apply now, if any.
+    pub fn poll(&mut self) -> Option<Command> {
+        while let Ok((user, command)) = self.receiver.try_recv() {
+            self.receive(user, command);
+        }
+        if self.last_move.elapsed() < MOVE_INTERVAL {
+            return None;
+        }
+        let command = match self.mode {
+            Mode::Anarchy => self.queue.pop_front(),
+            Mode::Democracy => match self.vote_started {
+                Some(started) if started.elapsed() >= VOTE_WINDOW => {
+                    // the first command voted for wins a tie
+                    let winner = self
+                        .votes
+                        .iter()
+                        .rev()
+                        .max_by_key(|(_, count)| *count)
+                        .map(|(command, _)| *command);
+                    debug!("Chat votes {:?}, winner {:?}", self.votes, winner);
+                    self.reset_votes();
+                    winner
+                }
+                _ => None,
+            },
+        };
+        if command.is_some() {
+            self.last_move = Instant::now();
+        }
+        command
+    }
+
+    fn receive(&mut self, user: String, command: Command) {
+        debug!("Chat command from {user}: {command:?}");
+        match self.mode {
+            Mode::Anarchy => {
+                if self.queue.len() == MAX_QUEUED {
+                    self.queue.pop_front();
+                }
+                self.queue.push_back(command);
+            }
+            Mode::Democracy => {
+                if !self.voters.insert(user) {
+                    return;
+                }
+                self.vote_started.get_or_insert_with(Instant::now);
+                match self.votes.iter_mut().find(|(c, _)| *c == command) {
+                    Some((_, count)) => *count += 1,
+                    None => self.votes.push((command, 1)),
+                }
+            }
+        }
+    }
+
+    fn reset_votes(&mut self) {
+        self.votes.clear();
+        self.voters.clear();
+        self.vote_started = None;
+    }
+}
+
+/// Extract the user and command from a chat line such as
+/// `:user!user@user.tmi.twitch.tv PRIVMSG #channel :!up`.
+fn parse_privmsg(line: &str) -> Option<(String, Command)> {
+    let (prefix, rest) = line.strip_prefix(':')?.split_once(" PRIVMSG ")?;
+    let user = prefix.split('!').next()?.to_string();
+    let (_channel, text) = rest.split_once(" :")?;
+    let command = match text.trim().to_lowercase().as_str() {
+        "!up" | "!u" => Command::Up,
+        "!down" | "!d" => Command::Down,
+        "!left" | "!l" => Command::Left,
+        "!right" | "!r" => Command::Right,
+        _ => return None,
+    };
+    Some((user, command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Chat in `mode` reading the commands of `sender`, ready for a move.
+    fn chat(mode: Mode) -> (Chat, mpsc::Sender<(String, Command)>) {
+        let (sender, receiver) = mpsc::channel();
+        let chat = Chat {
+            channel: String::from("test"),
+            mode,
+            receiver,
+            queue: VecDeque::new(),
+            last_move: Instant::now() - MOVE_INTERVAL,
+            votes: vec![],
+            voters: HashSet::new(),
+            vote_started: None,
+        };
+        (chat, sender)
+    }
+
+    #[test]
+    fn parse_privmsg_reads_moves() {
+        let line = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :!up";
+        assert_eq!(
+            parse_privmsg(line),
+            Some((String::from("viewer"), Command::Up))
+        );
+        let line = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel : !Right ";
+        assert_eq!(
+            parse_privmsg(line),
+            Some((String::from("viewer"), Command::Right))
+        );
+        let line = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :!l";
+        assert_eq!(
+            parse_privmsg(line),
+            Some((String::from("viewer"), Command::Left))
+        );
+    }
+
+    #[test]
+    fn parse_privmsg_ignores_other_lines() {
+        for line in [
+            ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :nice solve",
+            ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :!upup",
+            ":viewer!viewer@viewer.tmi.twitch.tv JOIN #channel",
+            "PING :tmi.twitch.tv",
+        ] {
+            assert_eq!(parse_privmsg(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn anarchy_keeps_the_latest_commands_in_order() {
+        let (mut chat, sender) = chat(Mode::Anarchy);
+        let commands = [Command::Up, Command::Left, Command::Down, Command::Right];
+        for (i, command) in commands.iter().cycle().take(MAX_QUEUED + 2).enumerate() {
+            sender.send((format!("viewer{i}"), *command)).unwrap();
+        }
+        for command in commands.iter().cycle().skip(2).take(MAX_QUEUED) {
+            assert_eq!(chat.poll(), Some(*command));
+            chat.last_move -= MOVE_INTERVAL;
+        }
+        assert_eq!(chat.poll(), None);
+    }
+
+    #[test]
+    fn democracy_tie_goes_to_the_first_voted() {
+        let (mut chat, sender) = chat(Mode::Democracy);
+        for (user, command) in [
+            ("a", Command::Up),
+            ("b", Command::Left),
+            ("c", Command::Left),
+            ("d", Command::Up),
+            // one vote per user
+            ("b", Command::Down),
+            ("c", Command::Down),
+        ] {
+            sender.send((String::from(user), command)).unwrap();
+        }
+        // votes are open for a while
+        assert_eq!(chat.poll(), None);
+        chat.vote_started = Some(Instant::now() - VOTE_WINDOW);
+        assert_eq!(chat.poll(), Some(Command::Up));
+        assert!(chat.votes.is_empty() && chat.voters.is_empty());
+    }
+}
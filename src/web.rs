@@ -0,0 +1,67 @@
+//! Images downloaded from the web: the image of the day, the images
+//! opened from a URL with `--url` or pasted with `Ctrl+U`, and the
+//! surprises of `Shift+S`. Downloads give up after `TIMEOUT`, and what
+//! isn't an image is turned down.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use log::debug;
+use nannou::app::Proxy;
+use nannou::image;
+
+/// Time a download may take, from connecting to the last byte.
+static TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Biggest file downloaded, in bytes.
+static MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Returns `true` if `text` looks like the URL of a web page or file.
+#[cfg(feature = "url")]
+pub fn is_url(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with("http://") || text.starts_with("https://"))
+        && !text.contains(char::is_whitespace)
+}
+
+/// Run `job` on a thread of its own. The receiver gets its result, `proxy`
+/// wakes the app up once it is there.
+pub fn in_background<F>(proxy: Proxy, job: F) -> Receiver<Result<PathBuf, String>>
+where
+    F: FnOnce() -> Result<PathBuf, String> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(job());
+        let _ = proxy.wakeup();
+    });
+    receiver
+}
+
+/// Download the image at `url` and save it as a PNG at `path`.
+pub fn download(url: &str, path: &Path) -> Result<(), String> {
+    debug!("Downloading {url}");
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .new_agent();
+    let bytes = agent
+        .get(url)
+        .call()
+        .and_then(|mut response| {
+            response
+                .body_mut()
+                .with_config()
+                .limit(MAX_BYTES)
+                .read_to_vec()
+        })
+        .map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes).map_err(|_| String::from("Not an image"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    image.save(path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,82 @@
+//! Snapshots of the webcam as puzzle images, taken with `C`. A frame is
+//! grabbed with `ffmpeg`, from `webcam_device` in the config or the first
+//! camera of the platform, cropped to a square in the middle and kept in
+//! the profile.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::{fs, io};
+
+use log::debug;
+use nannou::app::Proxy;
+use nannou::image::{self, GenericImageView};
+
+use crate::{clock, profile};
+
+/// Folder in the profile where the snapshots are kept.
+static WEBCAM_DIR: &str = "webcam";
+
+/// Input format of `ffmpeg` for the cameras of the platform, and the first
+/// camera. Windows cameras go by name, there is no first one to guess.
+static CAMERA_INPUT: (&str, &str) = if cfg!(target_os = "macos") {
+    ("avfoundation", "0")
+} else if cfg!(target_os = "windows") {
+    ("dshow", "")
+} else {
+    ("v4l2", "/dev/video0")
+};
+
+/// Take a snapshot of the webcam in the background. `device` is the camera
+/// as `ffmpeg` names it, empty for the first one. The receiver gets the
+/// path of the square picture, `proxy` wakes the app up once it is there.
+pub fn snapshot(device: &str, proxy: Proxy) -> Receiver<Result<PathBuf, String>> {
+    let (sender, receiver) = mpsc::channel();
+    let device = match device.is_empty() {
+        true => String::from(CAMERA_INPUT.1),
+        false => device.to_string(),
+    };
+    thread::spawn(move || {
+        let _ = sender.send(capture(&device));
+        let _ = proxy.wakeup();
+    });
+    receiver
+}
+
+/// Grab one frame of `device` and save it cropped to a square.
+fn capture(device: &str) -> Result<PathBuf, String> {
+    if device.is_empty() {
+        return Err(String::from("Set webcam_device in the config"));
+    }
+    let dir = profile::profile_path(WEBCAM_DIR);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("webcam_{}.png", clock::timestamp_millis()));
+    let input = match CAMERA_INPUT.0 {
+        "dshow" => format!("video={device}"),
+        _ => device.to_string(),
+    };
+    debug!("Taking a snapshot of {input}");
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", CAMERA_INPUT.0, "-i"])
+        .arg(&input)
+        .args(["-frames:v", "1"])
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => String::from("ffmpeg is not installed"),
+            _ => e.to_string(),
+        })?;
+    if !status.success() || !path.exists() {
+        return Err(String::from("No webcam found"));
+    }
+    let mut image = image::open(&path).map_err(|e| e.to_string())?;
+    let (width, height) = image.dimensions();
+    let side = width.min(height);
+    let square = image.crop((width - side) / 2, (height - side) / 2, side, side);
+    square.save(&path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
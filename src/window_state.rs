@@ -0,0 +1,38 @@
+//! Monitor and fullscreen mode of the puzzle window, remembered
+//! in the profile so the next run opens where the last one was.
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::profile;
+
+/// Name of the window state file in the profile.
+static WINDOW_STATE_FILE: &str = "window.toml";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowState {
+    pub monitor: Option<String>, // Name of the monitor the window is on
+    pub fullscreen: bool,
+}
+
+impl WindowState {
+    /// State saved by the last run, or the default state.
+    pub fn load() -> Self {
+        fs::read_to_string(profile::profile_dir().join(WINDOW_STATE_FILE))
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = profile::profile_path(WINDOW_STATE_FILE);
+        let result = toml::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|text| fs::write(&path, text).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            println!("Error saving {}: {e}", path.display());
+        }
+    }
+}
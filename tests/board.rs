@@ -0,0 +1,108 @@
+//! Invariants of the boards under any moves, checked with proptest on
+//! boards from 2x2 to 6x6.
+
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sliding_puzzle::{Board, GridSize};
+
+/// Boards from 2x2 to 6x6, not always square.
+fn grid_size() -> impl Strategy<Value = GridSize> {
+    (2..=6usize, 2..=6usize).prop_map(|(width, height)| GridSize { width, height })
+}
+
+/// Cells clicked, wrapped to the size of the board, most of them not next
+/// to the empty space.
+fn clicks() -> impl Strategy<Value = Vec<(usize, usize)>> {
+    prop::collection::vec((0..6usize, 0..6usize), 0..200)
+}
+
+/// Play `clicks` on `board`, as the player would, ignoring the moves
+/// which aren't valid.
+fn play(board: &mut Board, clicks: &[(usize, usize)]) {
+    let GridSize { width, height } = board.size();
+    for &(ix, iy) in clicks {
+        board.try_move(ix % width, iy % height);
+    }
+}
+
+/// Parity of the position of `board`, which no move changes: that of
+/// the inversions, with the row of the empty space on even widths, see
+/// `Board::is_solvable`.
+fn parity(board: &Board) -> usize {
+    let (_, iy) = board.index_empty();
+    match board.size().width % 2 {
+        0 => (board.inversions() + iy) % 2,
+        _ => board.inversions() % 2,
+    }
+}
+
+proptest! {
+    #[test]
+    fn one_empty_space_after_any_moves(size in grid_size(), clicks in clicks()) {
+        let mut board = Board::solved(size);
+        play(&mut board, &clicks);
+        prop_assert_eq!(board.blanks(), 1);
+        prop_assert_eq!(board.size(), size);
+    }
+
+    #[test]
+    fn moves_keep_the_parity(size in grid_size(), clicks in clicks()) {
+        let mut board = Board::solved(size);
+        let GridSize { width, height } = size;
+        for (ix, iy) in clicks {
+            let before = parity(&board);
+            board.try_move(ix % width, iy % height);
+            prop_assert_eq!(parity(&board), before, "{} changed parity", board);
+            prop_assert!(board.is_solvable(), "{board} can't be solved");
+        }
+    }
+
+    #[test]
+    fn moving_a_piece_back_undoes_a_move(
+        size in grid_size(),
+        clicks in clicks(),
+        (ix, iy) in (0..6usize, 0..6usize),
+    ) {
+        let mut board = Board::solved(size);
+        play(&mut board, &clicks);
+        let (ix, iy) = (ix % size.width, iy % size.height);
+        let before = board.clone();
+        let Some(to) = board.empty_next_to(ix, iy) else {
+            return Ok(());
+        };
+        let Some(piece) = board.try_move(ix, iy) else {
+            return Ok(());
+        };
+        prop_assert_eq!(board.index_of(piece), to);
+        prop_assert_eq!(board.try_move_to(to, (ix, iy)), Some(piece));
+        prop_assert_eq!(board, before);
+    }
+
+    #[test]
+    fn shifting_back_undoes_a_shift(size in grid_size(), seed: u64, shifts in 0..50usize) {
+        let mut board = Board::solved_with_blanks(size, 0);
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..shifts {
+            board.random_shift(&mut rng);
+        }
+        let before = board.clone();
+        let shift = board.random_shift(&mut rng);
+        board.shift(shift.inverse());
+        prop_assert_eq!(board, before);
+    }
+
+    #[test]
+    fn scrambled_boards_can_be_solved(size in grid_size(), seed: u64, blanks in 1..=3usize) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = Board::solved_with_blanks(size, blanks);
+        board.scramble_random(&mut rng);
+        prop_assert!(board.is_solvable(), "{board} can't be solved");
+        prop_assert!(!board.is_solved());
+        prop_assert_eq!(board.blanks(), blanks);
+
+        let mut board = Board::solved_with_blanks(size, blanks);
+        board.scramble(100, &mut rng);
+        prop_assert!(board.is_solvable(), "{board} can't be solved");
+    }
+}